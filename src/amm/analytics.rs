@@ -0,0 +1,227 @@
+//! Analytics de LP: impermanent loss, receita de taxas, PnL e APR entre dois snapshots do pool.
+//!
+//! Tudo é cotado em Y (numerário) ao spot de cada snapshot, `p = spot_price_x_in_y` (WAD). Para uma
+//! posição de `shares` constantes entre `entry` e `now`, com `(x0, y0)` e `(x1, y1)` a parte das
+//! reservas (floor, ADR-0001) em cada ponta:
+//! - `entry_value = x0·p0 + y0`; `hold_value = x0·p1 + y0` (só segurar os ativos);
+//! - `lp_value = x1·p1 + y1` (marcação a mercado da posição);
+//! - `no_fee_value = 2·√(x0·y0·p1)`: a liquidez de entrada `√(x0·y0)` levada ao preço `p1` sem taxas;
+//! - `impermanent_loss = no_fee_value − hold_value` (≤ 0, AM-GM) — efeito só do preço;
+//! - `fee_income = 2·√(x1·y1·p1) − no_fee_value` (≥ 0) — crescimento de `√k` por share;
+//! - `net_pnl = lp_value − entry_value`.
+//!
+//! Arredondamento: valores em Y são floor (nunca superestimam a posição); IL e receita de taxa são
+//! saturados no sinal teórico (arredondamento de 1–2 wei não inverte o sinal). Razões e APRs são WAD
+//! com sinal, nearest-even. APR = retorno / `entry_value` anualizado por `SECONDS_PER_YEAR / dt`.
+//! `pool_pnl` analisa o supply inteiro do snapshot de entrada (adds/removes depois dele ficam de fora).
+
+use super::errors::AmmError;
+use super::guardrails::{checked_add, div_nearest_even_u256, ensure_nonzero, mul_div_floor_u256, mul_u128_to_u256, u256_to_u128_checked};
+use super::liquidity::isqrt_u256;
+use super::pool::Pool;
+use super::pricing::spot_price_x_in_y;
+use super::types::{Reserves, U256, Wad, SECONDS_PER_YEAR, WAD};
+
+/// Estado do pool num instante: o que basta para avaliar qualquer posição nele.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSnapshot {
+    pub timestamp: u64,
+    pub reserves: Reserves,
+    pub total_shares: Wad,
+}
+
+impl PoolSnapshot {
+    pub fn of(pool: &Pool, timestamp: u64) -> Self {
+        Self { timestamp, reserves: pool.reserves(), total_shares: pool.total_shares() }
+    }
+
+    /// Parte das reservas de `shares` (floor).
+    fn underlying(&self, shares: Wad) -> Result<(Wad, Wad), AmmError> {
+        if self.total_shares == 0 { return Err(AmmError::EmptyPool); }
+        if shares > self.total_shares { return Err(AmmError::InsufficientShares { requested: shares, available: self.total_shares }); }
+        let total = U256::from(self.total_shares);
+        Ok((
+            u256_to_u128_checked(mul_u128_to_u256(self.reserves.x, shares) / total)?,
+            u256_to_u128_checked(mul_u128_to_u256(self.reserves.y, shares) / total)?,
+        ))
+    }
+
+    fn spot(&self) -> Result<Wad, AmmError> { spot_price_x_in_y(self.reserves.x, self.reserves.y) }
+}
+
+/// Resultado da análise; valores em Y (WAD), razões e APRs em WAD com sinal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LpPnl {
+    pub shares: Wad,
+    /// Segundos entre os snapshots
+    pub elapsed: u64,
+    pub entry_value: Wad,
+    pub hold_value: Wad,
+    pub lp_value: Wad,
+    pub fee_income: Wad,
+    /// ≤ 0
+    pub impermanent_loss: i128,
+    /// `impermanent_loss / hold_value`
+    pub impermanent_loss_wad: i128,
+    pub net_pnl: i128,
+    /// `fee_income / entry_value`, anualizado
+    pub fee_apr_wad: i128,
+    /// `net_pnl / entry_value`, anualizado
+    pub net_apr_wad: i128,
+}
+
+/// `x·p + y` em Y (floor).
+fn value_in_y(x: Wad, y: Wad, p: Wad) -> Result<Wad, AmmError> {
+    let xp = u256_to_u128_checked(mul_div_floor_u256(U256::from(x), U256::from(p), U256::from(WAD))?)?;
+    checked_add(xp, y)
+}
+
+/// `2·√(x·y·p)` em Y (floor): valor de uma posição CPMM de liquidez `√(x·y)` ao preço `p`.
+fn liquidity_value(x: Wad, y: Wad, p: Wad) -> Result<Wad, AmmError> {
+    let l2p = mul_div_floor_u256(mul_u128_to_u256(x, y), U256::from(p), U256::from(WAD))?;
+    u256_to_u128_checked(isqrt_u256(l2p) * U256::from(2u8))
+}
+
+fn signed_diff(a: Wad, b: Wad) -> Result<i128, AmmError> {
+    if a >= b { i128::try_from(a - b) } else { i128::try_from(b - a).map(|d| -d) }.map_err(|_| AmmError::Overflow)
+}
+
+/// `num·scale/den` com sinal (nearest-even); 0 se `den == 0`.
+fn signed_ratio(num: i128, scale: U256, den: U256) -> Result<i128, AmmError> {
+    if den.is_zero() { return Ok(0); }
+    let q = div_nearest_even_u256(U256::from(num.unsigned_abs()) * scale, den)?;
+    let q = i128::try_from(u256_to_u128_checked(q)?).map_err(|_| AmmError::Overflow)?;
+    Ok(if num < 0 { -q } else { q })
+}
+
+/// Retorno `pnl / base` anualizado sobre `elapsed` segundos (WAD); 0 se `elapsed == 0`.
+fn annualized(pnl: i128, base: Wad, elapsed: u64) -> Result<i128, AmmError> {
+    if elapsed == 0 { return Ok(0); }
+    signed_ratio(pnl, U256::from(WAD) * U256::from(SECONDS_PER_YEAR), mul_u128_to_u256(base, u128::from(elapsed)))
+}
+
+/// IL teórico do CPMM para um movimento de preço `r = p1/p0` (WAD): `2·√r/(1+r) − 1` (WAD, ≤ 0).
+pub fn impermanent_loss_wad(price_ratio: Wad) -> Result<i128, AmmError> {
+    ensure_nonzero(price_ratio)?;
+    // 2·√r·WAD/(WAD + r), com √r em WAD = √(r·WAD)
+    let sqrt_r = isqrt_u256(mul_u128_to_u256(price_ratio, WAD));
+    let num = sqrt_r * U256::from(2u8) * U256::from(WAD);
+    let ratio = u256_to_u128_checked(div_nearest_even_u256(num, U256::from(WAD) + U256::from(price_ratio))?)?;
+    signed_diff(ratio.min(WAD), WAD)
+}
+
+/// Analisa `shares` (constantes no intervalo) entre os snapshots `entry` e `now`.
+pub fn position_pnl(entry: &PoolSnapshot, now: &PoolSnapshot, shares: Wad) -> Result<LpPnl, AmmError> {
+    ensure_nonzero(shares)?;
+    if now.timestamp < entry.timestamp { return Err(AmmError::TimestampRegression { last: entry.timestamp, now: now.timestamp }); }
+    let (x0, y0) = entry.underlying(shares)?;
+    // a posição de entrada vale `shares` do supply de hoje
+    let (x1, y1) = now.underlying(shares.min(now.total_shares))?;
+    let (p0, p1) = (entry.spot()?, now.spot()?);
+
+    let entry_value = value_in_y(x0, y0, p0)?;
+    let hold_value = value_in_y(x0, y0, p1)?;
+    let lp_value = value_in_y(x1, y1, p1)?;
+    let no_fee_value = liquidity_value(x0, y0, p1)?;
+    let impermanent_loss = signed_diff(no_fee_value, hold_value)?.min(0);
+    let fee_income = liquidity_value(x1, y1, p1)?.saturating_sub(no_fee_value);
+    let net_pnl = signed_diff(lp_value, entry_value)?;
+    let elapsed = now.timestamp - entry.timestamp;
+    let fee_income_signed = i128::try_from(fee_income).map_err(|_| AmmError::Overflow)?;
+    Ok(LpPnl {
+        shares,
+        elapsed,
+        entry_value,
+        hold_value,
+        lp_value,
+        fee_income,
+        impermanent_loss,
+        impermanent_loss_wad: signed_ratio(impermanent_loss, U256::from(WAD), U256::from(hold_value))?,
+        net_pnl,
+        fee_apr_wad: annualized(fee_income_signed, entry_value, elapsed)?,
+        net_apr_wad: annualized(net_pnl, entry_value, elapsed)?,
+    })
+}
+
+/// Analisa o pool inteiro: o supply do snapshot de entrada como uma única posição.
+pub fn pool_pnl(entry: &PoolSnapshot, now: &PoolSnapshot) -> Result<LpPnl, AmmError> {
+    position_pnl(entry, now, entry.total_shares)
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::Direction;
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn t_il_formula_known_points() {
+        assert_eq!(impermanent_loss_wad(WAD).unwrap(), 0);
+        // 2x: 2·√2/3 − 1 ≈ −5,7191%
+        let il = impermanent_loss_wad(2 * WAD).unwrap();
+        assert!((il + 57_190_958_417_936_634).abs() <= 2, "{il}");
+        // simétrico em r ↔ 1/r
+        assert!((impermanent_loss_wad(WAD / 2).unwrap() - il).abs() <= 2);
+        assert_eq!(impermanent_loss_wad(0).unwrap_err(), AmmError::ZeroAmount);
+    }
+
+    #[test]
+    fn t_no_fee_pool_matches_il_formula() {
+        let mut pool = Pool::new(1_000_000 * WAD, 1_000_000 * WAD, 0).unwrap();
+        let entry = PoolSnapshot::of(&pool, 0);
+        // preço de X em Y sobe ~4x
+        pool.swap_exact_out(Direction::YtoX, 500_000 * WAD, Wad::MAX).unwrap();
+        let now = PoolSnapshot::of(&pool, 30 * DAY);
+        let r = pool_pnl(&entry, &now).unwrap();
+        let ratio = spot_price_x_in_y(now.reserves.x, now.reserves.y).unwrap();
+        let expected = impermanent_loss_wad(ratio).unwrap();
+        assert!((r.impermanent_loss_wad - expected).abs() <= 1_000, "{} vs {}", r.impermanent_loss_wad, expected);
+        assert!(r.fee_income <= 2);
+        assert!(r.impermanent_loss < 0);
+        assert_eq!(r.net_pnl, r.lp_value as i128 - r.entry_value as i128);
+        // sem taxa, LP − hold ≈ IL
+        assert!((r.lp_value as i128 - r.hold_value as i128 - r.impermanent_loss).abs() <= 4);
+    }
+
+    #[test]
+    fn t_fees_show_up_as_income_and_apr() {
+        let mut pool = Pool::new(1_000_000 * WAD, 1_000_000 * WAD, 3_000).unwrap();
+        let entry = PoolSnapshot::of(&pool, 1_000);
+        // ida e volta: preço quase igual, taxa fica no pool
+        for _ in 0..10 {
+            let out = pool.swap_exact_in(Direction::XtoY, 100_000 * WAD, 0).unwrap().amount_out;
+            pool.swap_exact_in(Direction::YtoX, out, 0).unwrap();
+        }
+        let now = PoolSnapshot::of(&pool, 1_000 + 365 * DAY);
+        let shares = entry.total_shares / 4;
+        let r = position_pnl(&entry, &now, shares).unwrap();
+        // ~ 0,3% × ~190k por volta × 10 voltas / 4 de receita, em Y
+        assert!(r.fee_income > 1_000 * WAD && r.fee_income < 2_000 * WAD, "{}", r.fee_income);
+        // o preço andou: LP − hold = taxa + IL (a menos do floor)
+        assert!(r.lp_value > r.hold_value);
+        assert!((r.lp_value as i128 - r.hold_value as i128 - (r.fee_income as i128 + r.impermanent_loss)).abs() <= 4);
+        // um ano: APR = retorno simples
+        assert_eq!(r.fee_apr_wad, signed_ratio(r.fee_income as i128, U256::from(WAD), U256::from(r.entry_value)).unwrap());
+        assert!(r.net_apr_wad <= r.fee_apr_wad);
+        // pool inteiro = 4× a posição (a menos do floor)
+        let p = pool_pnl(&entry, &now).unwrap();
+        assert!(p.fee_income.abs_diff(4 * r.fee_income) <= 8);
+    }
+
+    #[test]
+    fn t_input_validation() {
+        let pool = Pool::new(1_000 * WAD, 1_000 * WAD, 0).unwrap();
+        let (a, b) = (PoolSnapshot::of(&pool, 10), PoolSnapshot::of(&pool, 5));
+        assert_eq!(position_pnl(&a, &b, WAD).unwrap_err(), AmmError::TimestampRegression { last: 10, now: 5 });
+        assert_eq!(position_pnl(&a, &a, 0).unwrap_err(), AmmError::ZeroAmount);
+        let s = a.total_shares;
+        assert_eq!(position_pnl(&a, &a, s + 1).unwrap_err(), AmmError::InsufficientShares { requested: s + 1, available: s });
+        // intervalo vazio: sem APR
+        let r = pool_pnl(&a, &a).unwrap();
+        assert_eq!((r.net_pnl, r.net_apr_wad, r.fee_apr_wad, r.impermanent_loss), (0, 0, 0, 0));
+    }
+}
@@ -0,0 +1,376 @@
+//! Leilão em lote (batch auction) para swaps CPMM: intents coletados numa janela de tempo e
+//! liquidados a um **preço uniforme**, sem vantagem para quem ordena as transações (MEV) e com
+//! resultado determinístico — os totais não dependem da ordem de chegada.
+//!
+//! Liquidação (`clear_batch`):
+//! 1. os fluxos opostos se compensam: o X de quem vai X→Y paga o Y de quem vai Y→X e vice-versa;
+//!    só o excedente do lado dominante (pelo spot) toca o pool, num único `get_amount_out`;
+//! 2. com totais `a` (dominante) e `b` (oposto), o dominante envia `d` ao pool e recebe `o`; o preço
+//!    uniforme é a razão exata `(b + o) / a` e `d` é o **maior** input com `(a - d)·(b + o) ≥ a·b`,
+//!    i.e. o lado oposto ainda cabe no que sobra do dominante (busca binária, `d = 0` sempre serve;
+//!    inputs que a taxa zera contam como viáveis na busca, para ela não parar abaixo desse limiar, e
+//!    se o resultado cair ali vira `d = 0`);
+//! 3. fills pró-rata ao input, floor (ADR-0001): quem envia o ativo dominante recebe `in·(b+o)/a`,
+//!    quem envia o oposto recebe `in·a/(b+o)`; a sobra de arredondamento fica no lote (`dust`);
+//! 4. ordens com fill abaixo de `max(min_out, 1)` saem do lote (input devolvido) e a conta é refeita
+//!    até nenhuma sair.
+//!
+//! O `ClearingCertificate` guarda o estado do pool usado e o resultado: `verify` refaz a liquidação
+//! só com ele e os intents, e `digest` dá o sha256 canônico para publicação.
+
+use sha2::{Digest, Sha256};
+
+use super::errors::AmmError;
+use super::guardrails::{checked_add, checked_sub, div_nearest_even_u256_to_u128, ensure_fee, ensure_nonzero, ensure_reserves, mul_u128_to_u256, u256_to_u128_checked};
+use super::pool::Pool;
+use super::swap::get_amount_out;
+use super::types::{Direction, Ppm, Reserves, U256, Wad, WAD};
+
+/// Intent de swap exact-in submetido ao lote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapIntent {
+    pub direction: Direction,
+    /// Input bruto
+    pub amount_in: Wad,
+    /// Menor output aceito ao preço uniforme
+    pub min_out: Wad,
+}
+
+/// Execução de um intent; `amount_in == 0` marca ordem excluída (input devolvido).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fill {
+    pub direction: Direction,
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+}
+
+impl Fill {
+    pub fn is_filled(&self) -> bool { self.amount_in > 0 }
+}
+
+/// Certificado do preço de liquidação: estado do pool usado + resultado agregado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClearingCertificate {
+    /// Reservas do pool antes da liquidação
+    pub reserves: Reserves,
+    pub fee_ppm: Ppm,
+    /// Mínimo de reserva do pool
+    pub min_reserve: Wad,
+    /// Input somado das ordens executadas, por ativo
+    pub total_in_x: Wad,
+    pub total_in_y: Wad,
+    /// Sentido do excedente que vai ao pool
+    pub net_direction: Direction,
+    /// Perna única no pool (0 se os fluxos se compensaram inteiramente)
+    pub pool_in: Wad,
+    pub pool_out: Wad,
+    /// Preço uniforme como razão exata: `price_x` de X vale `price_y` de Y
+    pub price_x: Wad,
+    pub price_y: Wad,
+    /// `price_y / price_x` (Y por X, WAD, nearest-even); 0 em lote vazio
+    pub clearing_price: Wad,
+    /// Sobra de arredondamento retida pelo lote, por ativo
+    pub dust_x: Wad,
+    pub dust_y: Wad,
+}
+
+impl ClearingCertificate {
+    /// Refaz a liquidação a partir do certificado e confere certificado e fills.
+    pub fn verify(&self, intents: &[SwapIntent], fills: &[Fill]) -> bool {
+        clear_batch(self.reserves, self.fee_ppm, self.min_reserve, intents)
+            .is_ok_and(|c| c.certificate == *self && c.fills == fills)
+    }
+
+    /// sha256 dos campos em ordem de declaração (big-endian; sentido: 0 = X→Y, 1 = Y→X).
+    pub fn digest(&self) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(self.reserves.x.to_be_bytes());
+        h.update(self.reserves.y.to_be_bytes());
+        h.update(self.fee_ppm.to_be_bytes());
+        for v in [self.min_reserve, self.total_in_x, self.total_in_y] { h.update(v.to_be_bytes()); }
+        h.update([match self.net_direction { Direction::XtoY => 0u8, Direction::YtoX => 1 }]);
+        for v in [self.pool_in, self.pool_out, self.price_x, self.price_y, self.clearing_price, self.dust_x, self.dust_y] {
+            h.update(v.to_be_bytes());
+        }
+        h.finalize().into()
+    }
+}
+
+/// Resultado da liquidação: um fill por intent (mesma ordem) e o certificado.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchClearing {
+    pub fills: Vec<Fill>,
+    pub certificate: ClearingCertificate,
+}
+
+/// Liquida `intents` a preço uniforme contra um pool no estado dado, sem alterá-lo.
+pub fn clear_batch(reserves: Reserves, fee_ppm: Ppm, min_reserve: Wad, intents: &[SwapIntent]) -> Result<BatchClearing, AmmError> {
+    ensure_fee(fee_ppm)?;
+    ensure_reserves(reserves.x, reserves.y)?;
+    for i in intents { ensure_nonzero(i.amount_in)?; }
+    let mut active = vec![true; intents.len()];
+    loop {
+        let c = clear_active(reserves, fee_ppm, min_reserve, intents, &active)?;
+        let mut excluded = false;
+        for ((i, f), a) in intents.iter().zip(&c.fills).zip(active.iter_mut()) {
+            if f.is_filled() && f.amount_out < i.min_out.max(1) {
+                *a = false;
+                excluded = true;
+            }
+        }
+        if !excluded { return Ok(c); }
+    }
+}
+
+/// Uma rodada da liquidação só com os intents `active`.
+fn clear_active(reserves: Reserves, fee_ppm: Ppm, min_reserve: Wad, intents: &[SwapIntent], active: &[bool]) -> Result<BatchClearing, AmmError> {
+    let (mut total_in_x, mut total_in_y) = (0, 0);
+    for (i, _) in intents.iter().zip(active).filter(|(_, &a)| a) {
+        match i.direction {
+            Direction::XtoY => total_in_x = checked_add(total_in_x, i.amount_in)?,
+            Direction::YtoX => total_in_y = checked_add(total_in_y, i.amount_in)?,
+        }
+    }
+    // lado dominante: o de maior valor ao spot (empate: X→Y)
+    let net = if mul_u128_to_u256(total_in_x, reserves.y) >= mul_u128_to_u256(total_in_y, reserves.x) { Direction::XtoY } else { Direction::YtoX };
+    let (a, b) = net.orient(total_in_x, total_in_y);
+    let (r_in, r_out) = net.orient(reserves.x, reserves.y);
+
+    // output do pool para `d`; `None` se o input é pequeno demais para sobrar algo após a taxa
+    let leg = |d: Wad| -> Result<Option<Wad>, AmmError> {
+        if d == 0 { return Ok(Some(0)); }
+        match get_amount_out(r_in, r_out, d, fee_ppm) {
+            Ok(o) if r_out - o < min_reserve => Err(AmmError::MinReserveBreached),
+            Ok(o) => Ok(Some(o)),
+            Err(AmmError::InputTooSmall) => Ok(None),
+            Err(e) => Err(e),
+        }
+    };
+    let fits = |d: Wad| match leg(d) {
+        Ok(Some(o)) => U256::from(a - d) * (U256::from(b) + U256::from(o)) >= mul_u128_to_u256(a, b),
+        // abaixo do menor input útil: viável para a busca (senão ela encolhe para 0 com `d` bom acima)
+        Ok(None) => true,
+        Err(_) => false,
+    };
+    let mut d = a;
+    if a > 0 && !fits(a) {
+        // invariante: fits(lo) e !fits(hi)
+        let (mut lo, mut hi) = (0, a);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if fits(mid) { lo = mid } else { hi = mid }
+        }
+        // parou no limite do pool, não no do preço: o lote não cabe no pool
+        leg(hi)?;
+        d = if leg(lo)?.is_some() { lo } else { 0 };
+    }
+    let o = leg(d)?.unwrap_or(0);
+
+    let (price_x, price_y) = net.orient(a, checked_add(b, o)?);
+    let mut fills = Vec::with_capacity(intents.len());
+    let (mut out_x, mut out_y) = (0, 0);
+    for (i, &on) in intents.iter().zip(active) {
+        let (p_in, p_out) = i.direction.orient(price_x, price_y);
+        let amount_out = match (on, p_in) {
+            (false, _) | (_, 0) => 0,
+            _ => u256_to_u128_checked(mul_u128_to_u256(i.amount_in, p_out) / U256::from(p_in))?,
+        };
+        match i.direction {
+            Direction::XtoY => out_y = checked_add(out_y, amount_out)?,
+            Direction::YtoX => out_x = checked_add(out_x, amount_out)?,
+        }
+        let amount_in = if on { i.amount_in } else { 0 };
+        fills.push(Fill { direction: i.direction, amount_in, amount_out });
+    }
+    // disponível: X = total_in_x ± perna do pool; Y idem
+    let (avail_x, avail_y) = match net {
+        Direction::XtoY => (checked_sub(total_in_x, d)?, checked_add(total_in_y, o)?),
+        Direction::YtoX => (checked_add(total_in_x, o)?, checked_sub(total_in_y, d)?),
+    };
+    let clearing_price = if price_x == 0 { 0 } else { div_nearest_even_u256_to_u128(mul_u128_to_u256(price_y, WAD), U256::from(price_x))? };
+    Ok(BatchClearing {
+        fills,
+        certificate: ClearingCertificate {
+            reserves,
+            fee_ppm,
+            min_reserve,
+            total_in_x,
+            total_in_y,
+            net_direction: net,
+            pool_in: d,
+            pool_out: o,
+            price_x,
+            price_y,
+            clearing_price,
+            dust_x: checked_sub(avail_x, out_x)?,
+            dust_y: checked_sub(avail_y, out_y)?,
+        },
+    })
+}
+
+/// Lote aberto em `[opens_at, closes_at)`: recebe intents e é liquidado depois de fechar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchAuction {
+    opens_at: u64,
+    closes_at: u64,
+    intents: Vec<SwapIntent>,
+}
+
+impl BatchAuction {
+    /// Lote que aceita intents por `window` segundos a partir de `opens_at`.
+    pub fn new(opens_at: u64, window: u64) -> Result<Self, AmmError> {
+        ensure_nonzero(window as Wad)?;
+        let closes_at = opens_at.checked_add(window).ok_or(AmmError::Overflow)?;
+        Ok(Self { opens_at, closes_at, intents: Vec::new() })
+    }
+
+    pub fn opens_at(&self) -> u64 { self.opens_at }
+    pub fn closes_at(&self) -> u64 { self.closes_at }
+    pub fn intents(&self) -> &[SwapIntent] { &self.intents }
+
+    /// Registra o intent recebido em `now`; devolve o índice do fill correspondente.
+    pub fn submit(&mut self, intent: SwapIntent, now: u64) -> Result<usize, AmmError> {
+        if now < self.opens_at { return Err(AmmError::BatchNotOpen { opens_at: self.opens_at, now }); }
+        if now >= self.closes_at { return Err(AmmError::BatchClosed { closes_at: self.closes_at, now }); }
+        ensure_nonzero(intent.amount_in)?;
+        self.intents.push(intent);
+        Ok(self.intents.len() - 1)
+    }
+
+    /// Cota a liquidação contra o estado atual de `pool`.
+    pub fn quote(&self, pool: &Pool) -> Result<BatchClearing, AmmError> {
+        clear_batch(pool.reserves(), pool.fee_ppm(), pool.min_reserve(), &self.intents)
+    }
+
+    /// Liquida o lote fechado: aplica a perna líquida ao pool e esvazia o lote. Atômico.
+    pub fn clear(&mut self, pool: &mut Pool, now: u64) -> Result<BatchClearing, AmmError> {
+        if now < self.closes_at { return Err(AmmError::BatchOpen { closes_at: self.closes_at, now }); }
+        let c = self.quote(pool)?;
+        let cert = &c.certificate;
+        if cert.pool_in > 0 { pool.swap_exact_in(cert.net_direction, cert.pool_in, cert.pool_out)?; }
+        self.intents.clear();
+        Ok(c)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::MIN_RESERVE;
+
+    const FEE3: Ppm = 3000;
+
+    fn pool_1m() -> Pool { Pool::new(1_000_000u128 * WAD, 1_000_000u128 * WAD, FEE3).unwrap() }
+
+    fn xy(amount: Wad) -> SwapIntent { SwapIntent { direction: Direction::XtoY, amount_in: amount, min_out: 0 } }
+    fn yx(amount: Wad) -> SwapIntent { SwapIntent { direction: Direction::YtoX, amount_in: amount, min_out: 0 } }
+
+    #[test]
+    fn t_one_sided_batch_is_one_pool_swap() {
+        let p = pool_1m();
+        let c = clear_batch(p.reserves(), FEE3, p.min_reserve(), &[xy(6_000 * WAD), xy(4_000 * WAD)]).unwrap();
+        let single = p.clone().swap_exact_in(Direction::XtoY, 10_000 * WAD, 0).unwrap();
+        let cert = c.certificate;
+        assert_eq!((cert.pool_in, cert.pool_out), (10_000 * WAD, single.amount_out));
+        // pró-rata, floor
+        assert_eq!(c.fills[0].amount_out, single.amount_out * 6 / 10);
+        assert_eq!(c.fills[1].amount_out, single.amount_out * 4 / 10);
+        assert_eq!(cert.dust_y, single.amount_out - c.fills[0].amount_out - c.fills[1].amount_out);
+        assert!(cert.dust_y <= 1);
+    }
+
+    #[test]
+    fn t_opposing_flows_are_netted() {
+        let p = pool_1m();
+        let intents = [xy(10_000 * WAD), yx(8_000 * WAD)];
+        let c = clear_batch(p.reserves(), FEE3, p.min_reserve(), &intents).unwrap();
+        let cert = c.certificate;
+        assert_eq!(cert.net_direction, Direction::XtoY);
+        // só o excedente vai ao pool
+        assert!(cert.pool_in > 0 && cert.pool_in < 2_000 * WAD);
+        // uniforme = preço médio da perna no pool (taxa + impacto), abaixo do spot
+        assert!(cert.clearing_price < WAD && cert.clearing_price > WAD - WAD / 100);
+        let leg_price = div_nearest_even_u256_to_u128(mul_u128_to_u256(cert.pool_out, WAD), U256::from(cert.pool_in)).unwrap();
+        assert!(cert.clearing_price.abs_diff(leg_price) <= 1_000);
+        // ambos os lados recebem mais do que trocando sozinhos no pool
+        let alone_x = p.clone().swap_exact_in(Direction::XtoY, 10_000 * WAD, 0).unwrap().amount_out;
+        let alone_y = p.clone().swap_exact_in(Direction::YtoX, 8_000 * WAD, 0).unwrap().amount_out;
+        assert!(c.fills[0].amount_out > alone_x);
+        assert!(c.fills[1].amount_out > alone_y);
+        // conservação: nada é criado
+        assert_eq!(c.fills[1].amount_out + cert.pool_in + cert.dust_x, cert.total_in_x);
+        assert_eq!(c.fills[0].amount_out + cert.dust_y, cert.total_in_y + cert.pool_out);
+        assert!(cert.verify(&intents, &c.fills));
+    }
+
+    #[test]
+    fn t_order_below_min_out_is_excluded() {
+        let p = pool_1m();
+        let greedy = SwapIntent { direction: Direction::YtoX, amount_in: 1_000 * WAD, min_out: 1_010 * WAD };
+        let intents = [xy(5_000 * WAD), greedy, yx(1_000 * WAD)];
+        let c = clear_batch(p.reserves(), FEE3, p.min_reserve(), &intents).unwrap();
+        assert!(!c.fills[1].is_filled());
+        assert_eq!(c.fills[1].amount_out, 0);
+        assert_eq!(c.certificate.total_in_y, 1_000 * WAD);
+        assert!(c.fills[0].is_filled() && c.fills[2].is_filled());
+        assert!(c.certificate.verify(&intents, &c.fills));
+    }
+
+    #[test]
+    fn t_search_passes_inputs_eaten_by_fee() {
+        // taxa de 99,9999%: inputs < 1e6 não rendem nada no pool; a busca não pode encolher para d = 0
+        let reserves = Reserves::new(2 * WAD, 2 * WAD);
+        let intents = [xy(3_500_000), yx(2)];
+        let c = clear_batch(reserves, 999_999, MIN_RESERVE, &intents).unwrap();
+        let cert = c.certificate;
+        assert_eq!((cert.pool_in, cert.pool_out), (1_166_666, 1));
+        assert_eq!(c.fills[1].amount_out + cert.pool_in + cert.dust_x, cert.total_in_x);
+        assert_eq!(c.fills[0].amount_out + cert.dust_y, cert.total_in_y + cert.pool_out);
+        assert!(cert.verify(&intents, &c.fills));
+    }
+
+    #[test]
+    fn t_certificate_detects_tampering() {
+        let p = pool_1m();
+        let intents = [xy(3_000 * WAD), yx(5_000 * WAD)];
+        let c = clear_batch(p.reserves(), FEE3, p.min_reserve(), &intents).unwrap();
+        assert_eq!(c.certificate.net_direction, Direction::YtoX);
+        let mut fills = c.fills.clone();
+        fills[0].amount_out += 1;
+        assert!(!c.certificate.verify(&intents, &fills));
+        let mut cert = c.certificate;
+        cert.clearing_price += 1;
+        assert!(!cert.verify(&intents, &c.fills));
+        assert_ne!(cert.digest(), c.certificate.digest());
+        assert_eq!(c.certificate.digest(), clear_batch(p.reserves(), FEE3, p.min_reserve(), &intents).unwrap().certificate.digest());
+    }
+
+    #[test]
+    fn t_auction_window_and_atomic_clear() {
+        let mut pool = Pool::with_min_reserve(1_000 * WAD, 1_000 * WAD, FEE3, 500 * WAD).unwrap();
+        let mut b = BatchAuction::new(100, 10).unwrap();
+        assert_eq!(b.submit(xy(WAD), 99).unwrap_err(), AmmError::BatchNotOpen { opens_at: 100, now: 99 });
+        assert_eq!(b.submit(xy(WAD), 110).unwrap_err(), AmmError::BatchClosed { closes_at: 110, now: 110 });
+        assert_eq!(b.submit(xy(0), 100).unwrap_err(), AmmError::ZeroAmount);
+        assert_eq!(b.submit(xy(2_000 * WAD), 100).unwrap(), 0);
+        assert_eq!(b.clear(&mut pool, 109).unwrap_err(), AmmError::BatchOpen { closes_at: 110, now: 109 });
+        // excedente maior que o pool aceita: erro e nada muda
+        let before = pool.clone();
+        assert_eq!(b.clear(&mut pool, 110).unwrap_err(), AmmError::MinReserveBreached);
+        assert_eq!(pool, before);
+        assert_eq!(b.intents().len(), 1);
+
+        assert_eq!(b.submit(yx(1_900 * WAD), 105).unwrap(), 1);
+        let quoted = b.quote(&pool).unwrap();
+        let c = b.clear(&mut pool, 110).unwrap();
+        assert_eq!(c, quoted);
+        let cert = c.certificate;
+        let mut expected = before.clone();
+        expected.swap_exact_in(cert.net_direction, cert.pool_in, 0).unwrap();
+        assert_eq!(pool, expected);
+        assert!(b.intents().is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+//! Matemática pura da liquidez concentrada (estilo Uniswap v3), sem estado.
+//! Preço raiz em Q64.96 (`sqrtP = √(y/x)·2^96`, `x` = token0, `y` = token1) e tick `i` com
+//! `sqrtP(i) = √(1.0001^i)·2^96`. A liquidez `L` (u128) é a de `x·y = L²` dentro da faixa ativa.
+//!
+//! - `sqrt_ratio_at_tick`: constantes mágicas da v3 (bit a bit idêntico), por cima;
+//! - `tick_at_sqrt_ratio`: maior tick com `sqrt_ratio_at_tick(tick) <= sqrtP` (busca binária);
+//! - deltas de token: `Δx = L·2^96·(b - a)/(a·b)`, `Δy = L·(b - a)/2^96`;
+//! - próximo preço dado input/output: exato em 512 bits (`mul_div_*_u256`), sem o atalho da v3.
+//!
+//! Política (ADR-0001), a favor do pool: o que entra é ceil, o que sai é floor; o próximo preço
+//! arredonda para o lado que exige mais input / entrega menos output; taxa ceil.
+
+use super::errors::AmmError;
+use super::guardrails::{checked_sub, ensure_fee, mul_div_ceil_u256, mul_div_floor_u256, narrow, u256_to_u128_checked, widen};
+use super::types::{Ppm, U256, U512, Wad, PPM_SCALE};
+
+/// Menor tick suportado (`1.0001^MIN_TICK ≈ 2^-128`).
+pub const MIN_TICK: i32 = -887_272;
+/// Maior tick suportado.
+pub const MAX_TICK: i32 = 887_272;
+/// `sqrt_ratio_at_tick(MIN_TICK)`.
+pub const MIN_SQRT_RATIO: U256 = U256([4_295_128_739, 0, 0, 0]);
+/// `sqrt_ratio_at_tick(MAX_TICK)` (= 1461446703485210103287273052203988822378723970342).
+pub const MAX_SQRT_RATIO: U256 = U256([0x5d95_1d52_6398_8d26, 0xefd1_fc6a_5064_8849, 0xfffd_8963, 0]);
+/// `2^96` (1,0 em Q64.96).
+pub const Q96: U256 = U256([0, 1 << 32, 0, 0]);
+/// `2^128` (escala do fee growth).
+pub const Q128: U256 = U256([0, 0, 1, 0]);
+
+/// `2^128 / √1.0001^(2^k)` para `k = 0..19`, arredondados como na v3.
+pub(crate) const TICK_RATIOS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// Tick dentro de `[MIN_TICK, MAX_TICK]`.
+#[inline]
+pub fn ensure_tick(tick: i32) -> Result<(), AmmError> {
+    if (MIN_TICK..=MAX_TICK).contains(&tick) { Ok(()) } else { Err(AmmError::InvalidTick { tick }) }
+}
+
+/// Preço raiz dentro de `[MIN_SQRT_RATIO, MAX_SQRT_RATIO)`.
+#[inline]
+pub fn ensure_sqrt_price(sqrt_price_x96: U256) -> Result<(), AmmError> {
+    if sqrt_price_x96 < MIN_SQRT_RATIO || sqrt_price_x96 >= MAX_SQRT_RATIO { Err(AmmError::InvalidSqrtPrice) } else { Ok(()) }
+}
+
+/// `√(1.0001^tick)·2^96`, arredondado para cima (Q128.128 → Q64.96).
+pub fn sqrt_ratio_at_tick(tick: i32) -> Result<U256, AmmError> {
+    ensure_tick(tick)?;
+    let abs = tick.unsigned_abs();
+    let mut ratio = if abs & 1 != 0 { U256::from(TICK_RATIOS[0]) } else { Q128 };
+    for (k, c) in TICK_RATIOS.iter().enumerate().skip(1) {
+        if abs & (1 << k) != 0 { ratio = (ratio * U256::from(*c)) >> 128; }
+    }
+    if tick > 0 { ratio = U256::MAX / ratio; }
+    let round = if (ratio & U256::from(u32::MAX)).is_zero() { 0u8 } else { 1 };
+    Ok((ratio >> 32) + U256::from(round))
+}
+
+/// Maior tick com `sqrt_ratio_at_tick(tick) <= sqrt_price_x96`.
+pub fn tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Result<i32, AmmError> {
+    ensure_sqrt_price(sqrt_price_x96)?;
+    let (mut lo, mut hi) = (MIN_TICK, MAX_TICK);
+    while lo < hi {
+        let mid = lo + (hi - lo + 1) / 2;
+        if sqrt_ratio_at_tick(mid)? <= sqrt_price_x96 { lo = mid; } else { hi = mid - 1; }
+    }
+    Ok(lo)
+}
+
+#[inline]
+fn sorted(a: U256, b: U256) -> (U256, U256) { if a <= b { (a, b) } else { (b, a) } }
+
+/// `Δx = L·2^96·(b - a)/(a·b)` entre dois preços raiz (em qualquer ordem).
+pub fn amount0_delta(sqrt_a: U256, sqrt_b: U256, liquidity: u128, round_up: bool) -> Result<Wad, AmmError> {
+    let (a, b) = sorted(sqrt_a, sqrt_b);
+    if a.is_zero() { return Err(AmmError::InvalidSqrtPrice); }
+    let num = U256::from(liquidity) << 96;
+    let v = if round_up {
+        let t = mul_div_ceil_u256(num, b - a, b)?;
+        t / a + if (t % a).is_zero() { U256::zero() } else { U256::one() }
+    } else {
+        mul_div_floor_u256(num, b - a, b)? / a
+    };
+    u256_to_u128_checked(v)
+}
+
+/// `Δy = L·(b - a)/2^96` entre dois preços raiz (em qualquer ordem).
+pub fn amount1_delta(sqrt_a: U256, sqrt_b: U256, liquidity: u128, round_up: bool) -> Result<Wad, AmmError> {
+    let (a, b) = sorted(sqrt_a, sqrt_b);
+    let v = if round_up { mul_div_ceil_u256(U256::from(liquidity), b - a, Q96)? } else { mul_div_floor_u256(U256::from(liquidity), b - a, Q96)? };
+    u256_to_u128_checked(v)
+}
+
+/// Reserva virtual disponível de um lado (para o contexto de `InsufficientLiquidity`).
+fn virtual_reserve(v: U256) -> u128 { u256_to_u128_checked(v).unwrap_or(u128::MAX) }
+
+/// Preço após somar (`add`) ou retirar `amount` de token0: `L·2^96·P / (L·2^96 ± amount·P)`, ceil.
+fn next_sqrt_from_amount0(sqrt_p: U256, liquidity: u128, amount: Wad, add: bool) -> Result<U256, AmmError> {
+    if amount == 0 { return Ok(sqrt_p); }
+    let num = widen(U256::from(liquidity) << 96);
+    let product = widen(U256::from(amount)) * widen(sqrt_p);
+    let den = if add {
+        num + product
+    } else {
+        if product >= num { return Err(AmmError::InsufficientLiquidity { requested: amount, available: virtual_reserve((U256::from(liquidity) << 96) / sqrt_p) }); }
+        num - product
+    };
+    let (q, r) = (num * widen(sqrt_p)).div_mod(den);
+    narrow(if r.is_zero() { q } else { q + U512::from(1u8) })
+}
+
+/// Preço após somar ou retirar `amount` de token1: `P ± amount·2^96/L` (soma floor, retirada ceil).
+fn next_sqrt_from_amount1(sqrt_p: U256, liquidity: u128, amount: Wad, add: bool) -> Result<U256, AmmError> {
+    let l = U256::from(liquidity);
+    if add {
+        sqrt_p.checked_add(mul_div_floor_u256(U256::from(amount), Q96, l)?).ok_or(AmmError::Overflow)
+    } else {
+        let q = mul_div_ceil_u256(U256::from(amount), Q96, l)?;
+        if q >= sqrt_p { return Err(AmmError::InsufficientLiquidity { requested: amount, available: virtual_reserve((l * sqrt_p) >> 96) }); }
+        Ok(sqrt_p - q)
+    }
+}
+
+/// Próximo preço raiz dado um input líquido (`zero_for_one`: token0 entra, preço cai).
+pub fn next_sqrt_price_from_input(sqrt_p: U256, liquidity: u128, amount_in: Wad, zero_for_one: bool) -> Result<U256, AmmError> {
+    if sqrt_p.is_zero() || liquidity == 0 { return Err(AmmError::InsufficientLiquidity { requested: amount_in, available: 0 }); }
+    if zero_for_one { next_sqrt_from_amount0(sqrt_p, liquidity, amount_in, true) } else { next_sqrt_from_amount1(sqrt_p, liquidity, amount_in, true) }
+}
+
+/// Próximo preço raiz dado um output (`zero_for_one`: token1 sai, preço cai).
+pub fn next_sqrt_price_from_output(sqrt_p: U256, liquidity: u128, amount_out: Wad, zero_for_one: bool) -> Result<U256, AmmError> {
+    if sqrt_p.is_zero() || liquidity == 0 { return Err(AmmError::InsufficientLiquidity { requested: amount_out, available: 0 }); }
+    if zero_for_one { next_sqrt_from_amount1(sqrt_p, liquidity, amount_out, false) } else { next_sqrt_from_amount0(sqrt_p, liquidity, amount_out, false) }
+}
+
+/// Resultado de um passo de swap dentro de uma faixa de liquidez constante.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    /// Preço raiz ao fim do passo (o alvo, se alcançado)
+    pub sqrt_price_x96: U256,
+    /// Input líquido (sem taxa), ceil
+    pub amount_in: Wad,
+    /// Output, floor
+    pub amount_out: Wad,
+    /// Taxa cobrada sobre o input, ceil
+    pub fee: Wad,
+}
+
+/// Um passo de swap de `sqrt_current` em direção a `sqrt_target` com liquidez `L`.
+/// `amount_remaining` é input bruto (exact-in) ou output pedido (exact-out). Se o alvo não é
+/// alcançado em exact-in, todo o restante é consumido (a sobra após o input vira taxa).
+pub fn compute_swap_step(
+    sqrt_current: U256,
+    sqrt_target: U256,
+    liquidity: u128,
+    amount_remaining: Wad,
+    exact_in: bool,
+    fee_ppm: Ppm,
+) -> Result<SwapStep, AmmError> {
+    ensure_fee(fee_ppm)?;
+    let zero_for_one = sqrt_current >= sqrt_target;
+    let scale = U256::from(PPM_SCALE);
+    let keep = U256::from(PPM_SCALE - fee_ppm);
+    let delta_in = |a, b| if zero_for_one { amount0_delta(a, b, liquidity, true) } else { amount1_delta(a, b, liquidity, true) };
+    let delta_out = |a, b| if zero_for_one { amount1_delta(a, b, liquidity, false) } else { amount0_delta(a, b, liquidity, false) };
+
+    let (sqrt_next, full_in, full_out) = if exact_in {
+        let less_fee = u256_to_u128_checked(mul_div_floor_u256(U256::from(amount_remaining), keep, scale)?)?;
+        let to_target = delta_in(sqrt_target, sqrt_current)?;
+        if less_fee >= to_target {
+            (sqrt_target, Some(to_target), None)
+        } else {
+            (next_sqrt_price_from_input(sqrt_current, liquidity, less_fee, zero_for_one)?, None, None)
+        }
+    } else {
+        let to_target = delta_out(sqrt_target, sqrt_current)?;
+        if amount_remaining >= to_target {
+            (sqrt_target, None, Some(to_target))
+        } else {
+            (next_sqrt_price_from_output(sqrt_current, liquidity, amount_remaining, zero_for_one)?, None, None)
+        }
+    };
+
+    let amount_in = match full_in { Some(v) => v, None => delta_in(sqrt_next, sqrt_current)? };
+    let mut amount_out = match full_out { Some(v) => v, None => delta_out(sqrt_next, sqrt_current)? };
+    if !exact_in { amount_out = amount_out.min(amount_remaining); }
+    let fee = if exact_in && sqrt_next != sqrt_target {
+        checked_sub(amount_remaining, amount_in)?
+    } else {
+        u256_to_u128_checked(mul_div_ceil_u256(U256::from(amount_in), U256::from(fee_ppm), keep)?)?
+    };
+    Ok(SwapStep { sqrt_price_x96: sqrt_next, amount_in, amount_out, fee })
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::WAD;
+
+    #[test]
+    fn t_tick_bounds_match_v3() {
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK).unwrap(), MIN_SQRT_RATIO);
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK).unwrap(), MAX_SQRT_RATIO);
+        assert_eq!(sqrt_ratio_at_tick(0).unwrap(), Q96);
+        assert_eq!(MAX_SQRT_RATIO, U256::from_dec_str("1461446703485210103287273052203988822378723970342").unwrap());
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK + 1).unwrap_err(), AmmError::InvalidTick { tick: MAX_TICK + 1 });
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK - 1).unwrap_err(), AmmError::InvalidTick { tick: MIN_TICK - 1 });
+    }
+
+    #[test]
+    fn t_tick_roundtrip() {
+        for tick in [MIN_TICK, -500_000, -1, 0, 1, 60, 123_456, MAX_TICK - 1] {
+            let p = sqrt_ratio_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_ratio(p).unwrap(), tick);
+            // um wei abaixo do preço do tick já pertence ao tick anterior
+            if tick > MIN_TICK { assert_eq!(tick_at_sqrt_ratio(p - U256::one()).unwrap(), tick - 1); }
+        }
+        assert_eq!(tick_at_sqrt_ratio(MAX_SQRT_RATIO).unwrap_err(), AmmError::InvalidSqrtPrice);
+        assert_eq!(tick_at_sqrt_ratio(MIN_SQRT_RATIO - U256::one()).unwrap_err(), AmmError::InvalidSqrtPrice);
+    }
+
+    #[test]
+    fn t_deltas_round_against_trader() {
+        let (a, b) = (sqrt_ratio_at_tick(-600).unwrap(), sqrt_ratio_at_tick(600).unwrap());
+        let l = 1_000_000 * WAD;
+        for f in [amount0_delta, amount1_delta] {
+            let (up, down) = (f(a, b, l, true).unwrap(), f(a, b, l, false).unwrap());
+            assert!(up == down || up == down + 1);
+            assert_eq!(f(b, a, l, true).unwrap(), up); // ordem indiferente
+        }
+        // preço 1: L=1e24 em ±600 ticks ≈ 3% de cada lado
+        let dx = amount0_delta(Q96, b, l, false).unwrap();
+        assert!(dx > 29_000 * WAD && dx < 30_000 * WAD);
+    }
+
+    #[test]
+    fn t_next_price_consistent_with_deltas() {
+        let p = sqrt_ratio_at_tick(100).unwrap();
+        let l = 50_000 * WAD;
+        let dx = 10 * WAD;
+        // input de token0 derruba o preço; o input necessário para voltar ao preço novo cabe em dx
+        let down = next_sqrt_price_from_input(p, l, dx, true).unwrap();
+        assert!(down < p);
+        assert!(amount0_delta(down, p, l, true).unwrap() <= dx);
+        let up = next_sqrt_price_from_input(p, l, dx, false).unwrap();
+        assert!(up > p);
+        assert!(amount1_delta(p, up, l, true).unwrap() <= dx);
+        // output: o pool entrega pelo menos o pedido
+        let out = next_sqrt_price_from_output(p, l, dx, true).unwrap();
+        assert!(amount1_delta(out, p, l, false).unwrap() >= dx);
+        assert!(matches!(next_sqrt_price_from_output(p, l, u128::MAX, false), Err(AmmError::InsufficientLiquidity { .. })));
+        assert!(next_sqrt_price_from_input(p, 0, dx, true).is_err());
+    }
+
+    #[test]
+    fn t_swap_step_exact_in_and_out() {
+        let (p, target) = (Q96, sqrt_ratio_at_tick(-1_000).unwrap());
+        let l = 1_000_000 * WAD;
+        // exact-in pequeno: não alcança o alvo e consome todo o restante
+        let s = compute_swap_step(p, target, l, WAD, true, 3_000).unwrap();
+        assert!(s.sqrt_price_x96 > target && s.sqrt_price_x96 < p);
+        assert_eq!(s.amount_in + s.fee, WAD);
+        assert!(s.fee >= WAD * 3 / 1_000);
+        // exact-in grande: para no alvo e sobra input
+        let s = compute_swap_step(p, target, l, 1_000_000 * WAD, true, 3_000).unwrap();
+        assert_eq!(s.sqrt_price_x96, target);
+        assert!(s.amount_in + s.fee < 1_000_000 * WAD);
+        // exact-out: entrega exatamente o pedido
+        let s = compute_swap_step(p, target, l, WAD, false, 3_000).unwrap();
+        assert_eq!(s.amount_out, WAD);
+        assert!(s.amount_in > WAD);
+        // sem liquidez: pula direto ao alvo sem movimentar tokens
+        let s = compute_swap_step(p, target, 0, WAD, true, 3_000).unwrap();
+        assert_eq!((s.sqrt_price_x96, s.amount_in, s.amount_out, s.fee), (target, 0, 0, 0));
+    }
+}
@@ -0,0 +1,462 @@
+//! Pool de liquidez concentrada (CPMM por faixas de ticks, estilo Uniswap v3).
+//! Cada posição `(dono, tick_lower, tick_upper)` fornece liquidez `L` só enquanto o preço está
+//! em `[sqrtP(lower), sqrtP(upper))`; a liquidez ativa muda ao cruzar ticks via `liquidity_net`.
+//!
+//! - swap em passos (`clmm_math::compute_swap_step`) até o próximo tick inicializado; ao cruzá-lo,
+//!   aplica `liquidity_net` e inverte o `fee_growth_outside` do tick;
+//! - taxa por unidade de liquidez em Q128 (`fee_growth_global`, aritmética modular como no oráculo:
+//!   só diferenças importam); cada posição acumula `Δ(fee_growth_inside)·L` em `tokens_owed`;
+//! - mint cobra os tokens por cima (ceil), burn credita por baixo (floor) — ADR-0001;
+//! - `balances` são os tokens de fato no pool; pagamentos nunca passam deles.
+//!
+//! Operações são atômicas: o estado só é gravado quando a operação inteira dá certo.
+
+use std::collections::BTreeMap;
+
+use super::clmm_math::{
+    amount0_delta, amount1_delta, compute_swap_step, ensure_sqrt_price, sqrt_ratio_at_tick, tick_at_sqrt_ratio,
+    MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK, Q128,
+};
+use super::errors::AmmError;
+use super::guardrails::{checked_add, checked_sub, ensure_fee, ensure_nonzero, mul_div_floor_u256, u256_to_u128_checked};
+use super::types::{AccountId, Direction, Ppm, Reserves, U256, Wad};
+
+/// Maior espaçamento de ticks aceito.
+pub const MAX_TICK_SPACING: i32 = 16_384;
+
+/// Estado de um tick inicializado (referenciado por ao menos uma posição).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TickInfo {
+    /// Liquidez total das posições que usam o tick como borda
+    pub liquidity_gross: u128,
+    /// Variação da liquidez ativa ao cruzar o tick da esquerda para a direita
+    pub liquidity_net: i128,
+    /// Fee growth (Q128) do lado oposto ao preço atual, por token
+    pub fee_growth_outside_x: U256,
+    pub fee_growth_outside_y: U256,
+}
+
+/// Posição de liquidez numa faixa.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionInfo {
+    pub liquidity: u128,
+    /// Fee growth dentro da faixa (Q128) na última atualização
+    pub fee_growth_inside_last_x: U256,
+    pub fee_growth_inside_last_y: U256,
+    /// Tokens a receber em `collect` (burns + taxas acumuladas)
+    pub tokens_owed_x: Wad,
+    pub tokens_owed_y: Wad,
+}
+
+/// Chave de posição: `(dono, tick_lower, tick_upper)`.
+pub type PositionKey = (AccountId, i32, i32);
+
+/// Resultado de um swap no pool concentrado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClSwapReceipt {
+    pub direction: Direction,
+    /// Input bruto (inclui a taxa)
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+    /// Parte do input retida como taxa (somada por passo, ceil)
+    pub fee: Wad,
+    /// Preço raiz e tick ao fim do swap
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    /// Ticks inicializados cruzados
+    pub ticks_crossed: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConcentratedPool {
+    fee_ppm: Ppm,
+    tick_spacing: i32,
+    max_liquidity_per_tick: u128,
+    sqrt_price_x96: U256,
+    tick: i32,
+    liquidity: u128,
+    fee_growth_global_x: U256,
+    fee_growth_global_y: U256,
+    ticks: BTreeMap<i32, TickInfo>,
+    positions: BTreeMap<PositionKey, PositionInfo>,
+    balances: Reserves,
+}
+
+#[inline]
+fn wrapping_sub(a: U256, b: U256) -> U256 { a.overflowing_sub(b).0 }
+
+/// Liquidez máxima por tick para que a soma de todos os ticks caiba em u128.
+fn max_liquidity_per_tick(tick_spacing: i32) -> u128 {
+    let min = (MIN_TICK / tick_spacing) * tick_spacing;
+    let max = (MAX_TICK / tick_spacing) * tick_spacing;
+    u128::MAX / ((max - min) / tick_spacing + 1) as u128
+}
+
+impl ConcentratedPool {
+    /// Pool vazio com preço inicial `sqrt_price_x96` (Q64.96), taxa e espaçamento de ticks.
+    pub fn new(sqrt_price_x96: U256, fee_ppm: Ppm, tick_spacing: i32) -> Result<Self, AmmError> {
+        ensure_fee(fee_ppm)?;
+        if !(1..=MAX_TICK_SPACING).contains(&tick_spacing) {
+            return Err(AmmError::InvalidTickSpacing { spacing: tick_spacing });
+        }
+        let tick = tick_at_sqrt_ratio(sqrt_price_x96)?;
+        Ok(Self {
+            fee_ppm,
+            tick_spacing,
+            max_liquidity_per_tick: max_liquidity_per_tick(tick_spacing),
+            sqrt_price_x96,
+            tick,
+            liquidity: 0,
+            fee_growth_global_x: U256::zero(),
+            fee_growth_global_y: U256::zero(),
+            ticks: BTreeMap::new(),
+            positions: BTreeMap::new(),
+            balances: Reserves::new(0, 0),
+        })
+    }
+
+    pub fn fee_ppm(&self) -> Ppm { self.fee_ppm }
+    pub fn tick_spacing(&self) -> i32 { self.tick_spacing }
+    pub fn sqrt_price_x96(&self) -> U256 { self.sqrt_price_x96 }
+    /// Tick atual: maior tick com `sqrtP(tick) <= sqrt_price_x96`.
+    pub fn tick(&self) -> i32 { self.tick }
+    /// Liquidez ativa no preço atual.
+    pub fn liquidity(&self) -> u128 { self.liquidity }
+    /// Fee growth global `(x, y)` em Q128.
+    pub fn fee_growth_global(&self) -> (U256, U256) { (self.fee_growth_global_x, self.fee_growth_global_y) }
+    pub fn tick_info(&self, tick: i32) -> Option<&TickInfo> { self.ticks.get(&tick) }
+    /// Ticks inicializados em ordem crescente.
+    pub fn ticks(&self) -> impl Iterator<Item = (i32, &TickInfo)> { self.ticks.iter().map(|(t, i)| (*t, i)) }
+    pub fn position(&self, owner: AccountId, tick_lower: i32, tick_upper: i32) -> Option<&PositionInfo> {
+        self.positions.get(&(owner, tick_lower, tick_upper))
+    }
+    /// Tokens de fato no pool.
+    pub fn balances(&self) -> Reserves { self.balances }
+
+    fn check_range(&self, lower: i32, upper: i32) -> Result<(), AmmError> {
+        let aligned = lower % self.tick_spacing == 0 && upper % self.tick_spacing == 0;
+        if lower >= upper || lower < MIN_TICK || upper > MAX_TICK || !aligned {
+            return Err(AmmError::InvalidTickRange { lower, upper });
+        }
+        Ok(())
+    }
+
+    /// Fee growth `(x, y)` acumulado dentro de `[lower, upper)` (v3 `getFeeGrowthInside`).
+    pub fn fee_growth_inside(&self, lower: i32, upper: i32) -> (U256, U256) {
+        let (gx, gy) = (self.fee_growth_global_x, self.fee_growth_global_y);
+        let lo = self.ticks.get(&lower).copied().unwrap_or_default();
+        let hi = self.ticks.get(&upper).copied().unwrap_or_default();
+        let (below_x, below_y) = if self.tick >= lower {
+            (lo.fee_growth_outside_x, lo.fee_growth_outside_y)
+        } else {
+            (wrapping_sub(gx, lo.fee_growth_outside_x), wrapping_sub(gy, lo.fee_growth_outside_y))
+        };
+        let (above_x, above_y) = if self.tick < upper {
+            (hi.fee_growth_outside_x, hi.fee_growth_outside_y)
+        } else {
+            (wrapping_sub(gx, hi.fee_growth_outside_x), wrapping_sub(gy, hi.fee_growth_outside_y))
+        };
+        (wrapping_sub(wrapping_sub(gx, below_x), above_x), wrapping_sub(wrapping_sub(gy, below_y), above_y))
+    }
+
+    /// Aplica `delta` de liquidez ao tick (inicializando o `outside` se o tick ficar ativo agora).
+    fn update_tick(&mut self, tick: i32, delta: i128, upper: bool) -> Result<(), AmmError> {
+        let (current, gx, gy, max) = (self.tick, self.fee_growth_global_x, self.fee_growth_global_y, self.max_liquidity_per_tick);
+        let info = self.ticks.entry(tick).or_default();
+        let gross = info.liquidity_gross.checked_add_signed(delta).ok_or(AmmError::Overflow)?;
+        if gross > max { return Err(AmmError::Overflow); }
+        if info.liquidity_gross == 0 && tick <= current {
+            // por convenção, todo o crescimento anterior aconteceu abaixo do tick
+            info.fee_growth_outside_x = gx;
+            info.fee_growth_outside_y = gy;
+        }
+        info.liquidity_gross = gross;
+        let net = if upper { info.liquidity_net.checked_sub(delta) } else { info.liquidity_net.checked_add(delta) };
+        info.liquidity_net = net.ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    /// Atualiza ticks, taxas da posição e liquidez ativa; devolve os tokens da faixa para `|delta|`.
+    fn modify_position(&mut self, owner: AccountId, lower: i32, upper: i32, delta: i128) -> Result<(Wad, Wad), AmmError> {
+        self.check_range(lower, upper)?;
+        let key = (owner, lower, upper);
+        let mut pos = self.positions.get(&key).copied().unwrap_or_default();
+        if delta == 0 && pos.liquidity == 0 { return Err(AmmError::InsufficientShares { requested: 0, available: 0 }); }
+        if delta < 0 && delta.unsigned_abs() > pos.liquidity {
+            return Err(AmmError::InsufficientShares { requested: delta.unsigned_abs(), available: pos.liquidity });
+        }
+
+        if delta != 0 {
+            self.update_tick(lower, delta, false)?;
+            self.update_tick(upper, delta, true)?;
+        }
+        // taxas acumuladas desde a última atualização, sobre a liquidez antiga
+        let (inside_x, inside_y) = self.fee_growth_inside(lower, upper);
+        let liq = U256::from(pos.liquidity);
+        let owed_x = mul_div_floor_u256(wrapping_sub(inside_x, pos.fee_growth_inside_last_x), liq, Q128)?;
+        let owed_y = mul_div_floor_u256(wrapping_sub(inside_y, pos.fee_growth_inside_last_y), liq, Q128)?;
+        pos.tokens_owed_x = checked_add(pos.tokens_owed_x, u256_to_u128_checked(owed_x)?)?;
+        pos.tokens_owed_y = checked_add(pos.tokens_owed_y, u256_to_u128_checked(owed_y)?)?;
+        pos.fee_growth_inside_last_x = inside_x;
+        pos.fee_growth_inside_last_y = inside_y;
+        pos.liquidity = pos.liquidity.checked_add_signed(delta).ok_or(AmmError::Overflow)?;
+
+        // tokens da faixa: abaixo só x, acima só y, dentro os dois (e a liquidez ativa muda)
+        let (abs, up) = (delta.unsigned_abs(), delta > 0);
+        let (sqrt_lower, sqrt_upper) = (sqrt_ratio_at_tick(lower)?, sqrt_ratio_at_tick(upper)?);
+        let amounts = if self.tick < lower {
+            (amount0_delta(sqrt_lower, sqrt_upper, abs, up)?, 0)
+        } else if self.tick < upper {
+            self.liquidity = self.liquidity.checked_add_signed(delta).ok_or(AmmError::Overflow)?;
+            (amount0_delta(self.sqrt_price_x96, sqrt_upper, abs, up)?, amount1_delta(sqrt_lower, self.sqrt_price_x96, abs, up)?)
+        } else {
+            (0, amount1_delta(sqrt_lower, sqrt_upper, abs, up)?)
+        };
+        self.positions.insert(key, pos);
+        // ticks sem posição deixam de ser inicializados (depois de usados no fee growth acima)
+        for t in [lower, upper] {
+            if self.ticks.get(&t).is_some_and(|i| i.liquidity_gross == 0) { self.ticks.remove(&t); }
+        }
+        Ok(amounts)
+    }
+
+    /// Adiciona `liquidity` à posição `[tick_lower, tick_upper)`; devolve `(x, y)` depositados (ceil).
+    pub fn mint(&mut self, owner: AccountId, tick_lower: i32, tick_upper: i32, liquidity: u128) -> Result<(Wad, Wad), AmmError> {
+        ensure_nonzero(liquidity)?;
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        let mut next = self.clone();
+        let (x, y) = next.modify_position(owner, tick_lower, tick_upper, delta)?;
+        next.balances = Reserves::new(checked_add(next.balances.x, x)?, checked_add(next.balances.y, y)?);
+        *self = next;
+        Ok((x, y))
+    }
+
+    /// Remove `liquidity` da posição; os tokens (floor) vão para `tokens_owed` até o `collect`.
+    /// Com `liquidity = 0` só acumula as taxas pendentes.
+    pub fn burn(&mut self, owner: AccountId, tick_lower: i32, tick_upper: i32, liquidity: u128) -> Result<(Wad, Wad), AmmError> {
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        let mut next = self.clone();
+        let (x, y) = next.modify_position(owner, tick_lower, tick_upper, -delta)?;
+        let pos = next.positions.get_mut(&(owner, tick_lower, tick_upper)).ok_or(AmmError::Overflow)?;
+        pos.tokens_owed_x = checked_add(pos.tokens_owed_x, x)?;
+        pos.tokens_owed_y = checked_add(pos.tokens_owed_y, y)?;
+        *self = next;
+        Ok((x, y))
+    }
+
+    /// Acumula as taxas pendentes e paga tudo o que a posição tem a receber; posição vazia é removida.
+    pub fn collect(&mut self, owner: AccountId, tick_lower: i32, tick_upper: i32) -> Result<(Wad, Wad), AmmError> {
+        let key = (owner, tick_lower, tick_upper);
+        let mut next = self.clone();
+        let pos = next.positions.get(&key).copied().ok_or(AmmError::InsufficientShares { requested: 0, available: 0 })?;
+        if pos.liquidity > 0 { next.modify_position(owner, tick_lower, tick_upper, 0)?; }
+        let pos = next.positions.remove(&key).ok_or(AmmError::Overflow)?;
+        let (x, y) = (pos.tokens_owed_x, pos.tokens_owed_y);
+        next.balances = Reserves::new(checked_sub(next.balances.x, x)?, checked_sub(next.balances.y, y)?);
+        if pos.liquidity > 0 {
+            next.positions.insert(key, PositionInfo { tokens_owed_x: 0, tokens_owed_y: 0, ..pos });
+        }
+        *self = next;
+        Ok((x, y))
+    }
+
+    /// Próximo tick inicializado no sentido do swap (ou o limite da faixa de ticks).
+    fn next_tick(&self, zero_for_one: bool) -> (i32, bool) {
+        let found = if zero_for_one { self.ticks.range(..=self.tick).next_back() } else { self.ticks.range(self.tick + 1..).next() };
+        match found {
+            Some((t, _)) => (*t, true),
+            None => (if zero_for_one { MIN_TICK } else { MAX_TICK }, false),
+        }
+    }
+
+    /// Cruza `tick`: inverte o `outside` e devolve o `liquidity_net`.
+    fn cross(&mut self, tick: i32) -> i128 {
+        let (gx, gy) = (self.fee_growth_global_x, self.fee_growth_global_y);
+        let info = self.ticks.get_mut(&tick).expect("tick inicializado");
+        info.fee_growth_outside_x = wrapping_sub(gx, info.fee_growth_outside_x);
+        info.fee_growth_outside_y = wrapping_sub(gy, info.fee_growth_outside_y);
+        info.liquidity_net
+    }
+
+    /// Loop de swap: `amount` é input bruto (exact-in) ou output pedido (exact-out).
+    /// Devolve `(input bruto, output, taxa, ticks cruzados)` e exige preenchimento total.
+    fn swap_steps(&mut self, dir: Direction, amount: Wad, exact_in: bool) -> Result<(Wad, Wad, Wad, u32), AmmError> {
+        ensure_nonzero(amount)?;
+        let zero_for_one = dir == Direction::XtoY;
+        let limit = if zero_for_one { MIN_SQRT_RATIO + U256::one() } else { MAX_SQRT_RATIO - U256::one() };
+        let (mut remaining, mut amount_in, mut amount_out, mut fee, mut crossed) = (amount, 0u128, 0u128, 0u128, 0u32);
+
+        while remaining > 0 && self.sqrt_price_x96 != limit {
+            let start = self.sqrt_price_x96;
+            let (tick_next, initialized) = self.next_tick(zero_for_one);
+            let sqrt_next = sqrt_ratio_at_tick(tick_next)?;
+            let target = if zero_for_one { sqrt_next.max(limit) } else { sqrt_next.min(limit) };
+            let step = compute_swap_step(start, target, self.liquidity, remaining, exact_in, self.fee_ppm)?;
+            self.sqrt_price_x96 = step.sqrt_price_x96;
+
+            let gross = checked_add(step.amount_in, step.fee)?;
+            remaining = checked_sub(remaining, if exact_in { gross } else { step.amount_out })?;
+            amount_in = checked_add(amount_in, gross)?;
+            amount_out = checked_add(amount_out, step.amount_out)?;
+            fee = checked_add(fee, step.fee)?;
+            if self.liquidity > 0 {
+                let growth = mul_div_floor_u256(U256::from(step.fee), Q128, U256::from(self.liquidity))?;
+                let global = if zero_for_one { &mut self.fee_growth_global_x } else { &mut self.fee_growth_global_y };
+                *global = global.overflowing_add(growth).0;
+            }
+
+            if self.sqrt_price_x96 == sqrt_next {
+                if initialized {
+                    let net = self.cross(tick_next);
+                    let net = if zero_for_one { net.checked_neg().ok_or(AmmError::Overflow)? } else { net };
+                    self.liquidity = self.liquidity.checked_add_signed(net).ok_or(AmmError::Overflow)?;
+                    crossed += 1;
+                }
+                self.tick = if zero_for_one { tick_next - 1 } else { tick_next };
+            } else if self.sqrt_price_x96 != start {
+                self.tick = tick_at_sqrt_ratio(self.sqrt_price_x96)?;
+            }
+        }
+
+        if remaining > 0 {
+            let (requested, available) = if exact_in { (amount, amount_in) } else { (amount, amount_out) };
+            return Err(AmmError::InsufficientLiquidity { requested, available });
+        }
+        let (bx, by) = (self.balances.x, self.balances.y);
+        self.balances = match dir {
+            Direction::XtoY => Reserves::new(checked_add(bx, amount_in)?, checked_sub(by, amount_out)?),
+            Direction::YtoX => Reserves::new(checked_sub(bx, amount_out)?, checked_add(by, amount_in)?),
+        };
+        Ok((amount_in, amount_out, fee, crossed))
+    }
+
+    fn swap(&mut self, dir: Direction, amount: Wad, exact_in: bool) -> Result<ClSwapReceipt, AmmError> {
+        let (amount_in, amount_out, fee, ticks_crossed) = self.swap_steps(dir, amount, exact_in)?;
+        ensure_sqrt_price(self.sqrt_price_x96)?;
+        Ok(ClSwapReceipt { direction: dir, amount_in, amount_out, fee, sqrt_price_x96: self.sqrt_price_x96, tick: self.tick, ticks_crossed })
+    }
+
+    /// Swap com input exato (bruto); exige output ≥ `min_out`.
+    pub fn swap_exact_in(&mut self, dir: Direction, amount_in: Wad, min_out: Wad) -> Result<ClSwapReceipt, AmmError> {
+        let mut next = self.clone();
+        let r = next.swap(dir, amount_in, true)?;
+        if r.amount_out == 0 { return Err(AmmError::InputTooSmall); }
+        if r.amount_out < min_out { return Err(AmmError::SlippageExceeded { expected: min_out, actual: r.amount_out }); }
+        *self = next;
+        Ok(r)
+    }
+
+    /// Swap com output exato; exige input bruto ≤ `max_in`.
+    pub fn swap_exact_out(&mut self, dir: Direction, amount_out: Wad, max_in: Wad) -> Result<ClSwapReceipt, AmmError> {
+        let mut next = self.clone();
+        let r = next.swap(dir, amount_out, false)?;
+        if r.amount_in > max_in { return Err(AmmError::SlippageExceeded { expected: max_in, actual: r.amount_in }); }
+        *self = next;
+        Ok(r)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::clmm_math::Q96;
+    use crate::amm::types::WAD;
+
+    const L: u128 = 1_000_000 * WAD;
+
+    fn pool() -> ConcentratedPool { ConcentratedPool::new(Q96, 3_000, 60).unwrap() }
+
+    #[test]
+    fn t_new_validates() {
+        assert_eq!(pool().tick(), 0);
+        assert!(ConcentratedPool::new(Q96, 1_000_000, 60).is_err());
+        assert_eq!(ConcentratedPool::new(Q96, 3_000, 0).unwrap_err(), AmmError::InvalidTickSpacing { spacing: 0 });
+        assert_eq!(ConcentratedPool::new(Q96, 3_000, MAX_TICK_SPACING + 1).unwrap_err(), AmmError::InvalidTickSpacing { spacing: MAX_TICK_SPACING + 1 });
+        assert_eq!(ConcentratedPool::new(MAX_SQRT_RATIO, 3_000, 60).unwrap_err(), AmmError::InvalidSqrtPrice);
+        let p = ConcentratedPool::new(sqrt_ratio_at_tick(-61).unwrap(), 3_000, 60).unwrap();
+        assert_eq!(p.tick(), -61);
+    }
+
+    #[test]
+    fn t_mint_range_sides() {
+        let mut p = pool();
+        // faixa em volta do preço: deposita os dois tokens e ativa a liquidez
+        let (x, y) = p.mint(1, -600, 600, L).unwrap();
+        assert!(x > 0 && y > 0);
+        assert_eq!(p.liquidity(), L);
+        // acima do preço: só x; abaixo: só y; nenhuma ativa
+        let (x, y) = p.mint(2, 600, 1_200, L).unwrap();
+        assert!(x > 0 && y == 0);
+        let (x, y) = p.mint(2, -1_200, -600, L).unwrap();
+        assert!(x == 0 && y > 0);
+        assert_eq!(p.liquidity(), L);
+        assert_eq!(p.tick_info(600).unwrap().liquidity_net, 0); // fim de uma faixa, início de outra
+        assert_eq!(p.tick_info(-1_200).unwrap().liquidity_net, L as i128);
+        // faixa inválida
+        for (lo, hi) in [(600, -600), (0, 0), (-601, 600), (MIN_TICK - 60, 0)] {
+            assert!(matches!(p.mint(1, lo, hi, L), Err(AmmError::InvalidTickRange { .. })));
+        }
+    }
+
+    #[test]
+    fn t_swap_crosses_ticks_and_changes_liquidity() {
+        let mut p = pool();
+        p.mint(1, -120, 120, L).unwrap();
+        p.mint(2, -1_200, 1_200, L).unwrap();
+        assert_eq!(p.liquidity(), 2 * L);
+        // empurra o preço para baixo de -120: a faixa estreita sai
+        let r = p.swap_exact_in(Direction::XtoY, 20_000 * WAD, 0).unwrap();
+        assert!(r.tick < -120);
+        assert_eq!(r.ticks_crossed, 1);
+        assert_eq!(p.liquidity(), L);
+        // volta acima: entra de novo
+        let r = p.swap_exact_in(Direction::YtoX, 20_000 * WAD, 0).unwrap();
+        assert!((-120..120).contains(&r.tick), "tick={}", r.tick);
+        assert_eq!(r.ticks_crossed, 1);
+        assert_eq!(p.liquidity(), 2 * L);
+    }
+
+    #[test]
+    fn t_fees_accrue_only_to_in_range_positions() {
+        let mut p = pool();
+        p.mint(1, -600, 600, L).unwrap();
+        p.mint(2, 600, 1_200, L).unwrap(); // fora da faixa
+        let r = p.swap_exact_in(Direction::XtoY, 1_000 * WAD, 0).unwrap();
+        let (fx, fy) = p.collect(1, -600, 600).unwrap();
+        assert_eq!(fy, 0);
+        assert!(fx <= r.fee && r.fee - fx <= 1, "fee={} coletado={}", r.fee, fx);
+        assert_eq!(p.collect(2, 600, 1_200).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn t_roundtrip_burn_collect_is_solvent() {
+        let mut p = pool();
+        p.mint(1, -600, 600, L).unwrap();
+        p.mint(2, -60, 180, L / 3).unwrap();
+        p.swap_exact_in(Direction::XtoY, 5_000 * WAD, 0).unwrap();
+        p.swap_exact_out(Direction::YtoX, 7_000 * WAD, u128::MAX).unwrap();
+        for (o, lo, hi, l) in [(1, -600, 600, L), (2, -60, 180, L / 3)] {
+            p.burn(o, lo, hi, l).unwrap();
+            p.collect(o, lo, hi).unwrap();
+            assert!(p.position(o, lo, hi).is_none());
+        }
+        // tudo pago; sobra só poeira de arredondamento a favor do pool
+        assert_eq!(p.liquidity(), 0);
+        assert_eq!(p.ticks().count(), 0);
+        assert!(p.balances().x < 10 && p.balances().y < 10, "{:?}", p.balances());
+    }
+
+    #[test]
+    fn t_swap_errors_are_atomic() {
+        let mut p = pool();
+        p.mint(1, -60, 60, WAD).unwrap();
+        let before = p.clone();
+        assert!(matches!(p.swap_exact_in(Direction::XtoY, 1_000 * WAD, 0), Err(AmmError::InsufficientLiquidity { .. })));
+        assert!(matches!(p.swap_exact_out(Direction::XtoY, WAD / 1_000, 1), Err(AmmError::SlippageExceeded { .. })));
+        assert!(matches!(p.burn(1, -60, 60, 2 * WAD), Err(AmmError::InsufficientShares { .. })));
+        assert_eq!(p, before);
+    }
+}
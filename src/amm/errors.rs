@@ -1,6 +1,11 @@
 //! Erros padronizados do AMM
+//! Cada variante tem um código estável (numérico e string) para APIs e mensagens pt/en.
+//! Os códigos **nunca** são reaproveitados: variantes novas recebem o próximo número livre.
+//! Erros só do módulo de crédito usam o prefixo `CREDIT_` no código string; os demais, `AMM_`.
 use core::fmt;
 
+use super::types::MarketPhase;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AmmError {
     ZeroAmount,
@@ -8,20 +13,353 @@ pub enum AmmError {
     MinReserveBreached,
     Overflow,
     InputTooSmall,
+    /// Resultado real fora do limite do usuário (`min_out` em exact-in, `max_in` em exact-out)
+    SlippageExceeded { expected: u128, actual: u128 },
+    /// Burn de mais shares do que existem
+    InsufficientShares { requested: u128, available: u128 },
+    /// Pool sem supply de shares (`total_shares == 0`)
+    EmptyPool,
+    /// `fee_ppm` fora de `0..1_000_000`
+    FeeTooHigh { fee_ppm: u32 },
+    /// Pedido de output maior que a reserva disponível
+    InsufficientLiquidity { requested: u128, available: u128 },
+    /// Timestamp anterior ao último registrado
+    TimestampRegression { last: u64, now: u64 },
+    /// Janela pedida maior que o histórico disponível
+    OracleWindowUnavailable { window: u64, available: u64 },
+    /// Coeficiente de amplificação fora de `1..=MAX_AMP` (StableSwap)
+    InvalidAmplification { amp: u64 },
+    /// Iteração de Newton sem convergência no limite de passos
+    NoConvergence,
+    /// Pesos inválidos (zero, abaixo do mínimo, soma ≠ 1 ou quantidade ≠ ativos)
+    InvalidWeights,
+    /// Índice de ativo fora do pool ou repetido no par in/out
+    InvalidAssetIndex { index: usize, len: usize },
+    /// Quantidade de outcomes fora da faixa suportada pelo mercado
+    InvalidOutcomeCount { count: usize },
+    /// Operação de negociação em mercado já resolvido
+    MarketResolved,
+    /// Resgate antes da resolução do mercado
+    MarketNotResolved,
+    /// Operação ou transição não permitida na fase atual do mercado
+    WrongPhase { expected: MarketPhase, actual: MarketPhase },
+    /// Fonte de resolução ainda sem resultado
+    ResolutionUnavailable,
+    /// Vetor de payout não soma 1 (WAD)
+    InvalidPayout { yes: u128, no: u128 },
+    /// Settlement com claims ainda não pagos
+    OutstandingClaims { owed: u128 },
+    /// Faixa de ticks inválida (fora dos limites, desalinhada ao espaçamento ou `lower >= upper`)
+    InvalidTickRange { lower: i32, upper: i32 },
+    /// Preço raiz (Q64.96) fora de `[MIN_SQRT_RATIO, MAX_SQRT_RATIO)`
+    InvalidSqrtPrice,
+    /// Nenhum caminho de pools liga os dois tokens dentro do limite de hops
+    NoRoute { token_in: u32, token_out: u32 },
+    /// Intent submetido depois do fechamento da janela do lote
+    BatchClosed { closes_at: u64, now: u64 },
+    /// Liquidação pedida antes do fechamento da janela do lote
+    BatchOpen { closes_at: u64, now: u64 },
+    /// Evento do log que não reproduz a operação registrada (sequência, output ou hash)
+    InvalidEvent { seq: u64 },
+    /// Soma dos saldos de LP diferente do `total_shares` do pool
+    ShareSupplyMismatch { balances: u128, total_shares: u128 },
+    /// Parâmetros de crédito inválidos (haircut ≥ 100% ou fora de `0 < ltv ≤ limiar ≤ 100%`)
+    InvalidCreditParams,
+    /// Dívida acima do limite permitido pelo colateral (LTV máximo)
+    LtvExceeded { debt: u128, limit: u128 },
+    /// Saque de mais colateral do que o depositado
+    InsufficientCollateral { requested: u128, available: u128 },
+    /// Parâmetros de modelo de juros inválidos (joelho, alvo ou limites de taxa)
+    InvalidRateModel,
+    /// Resgate de mais oferta do que o saldo do fornecedor
+    InsufficientSupply { requested: u128, available: u128 },
+    /// Capacidade do buffer de observações inválida (precisa de ao menos uma posição)
+    InvalidCapacity,
+    /// Conta sem posição (nada a resgatar ou sacar)
+    UnknownAccount { account: u64 },
+    /// Espaçamento de ticks fora de `1..=MAX_TICK_SPACING`
+    InvalidTickSpacing { spacing: i32 },
+    /// Tick fora de `[MIN_TICK, MAX_TICK]`
+    InvalidTick { tick: i32 },
+    /// Intent submetido antes da abertura da janela do lote
+    BatchNotOpen { opens_at: u64, now: u64 },
+    /// Par inválido: o mesmo token nos dois lados do pool
+    InvalidPair { token: u32 },
+    /// Parte do protocolo fora de `0..=1_000_000` ppm da taxa
+    ProtocolFeeTooHigh { protocol_fee_ppm: u32 },
+}
+
+/// Idioma das mensagens de erro.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang { Pt, En }
+
+impl AmmError {
+    /// Código numérico estável.
+    pub fn code(&self) -> u16 {
+        use AmmError::*;
+        match self {
+            ZeroAmount => 1001,
+            ZeroReserve => 1002,
+            MinReserveBreached => 1003,
+            Overflow => 1004,
+            InputTooSmall => 1005,
+            SlippageExceeded { .. } => 1006,
+            InsufficientShares { .. } => 1007,
+            EmptyPool => 1008,
+            FeeTooHigh { .. } => 1009,
+            InsufficientLiquidity { .. } => 1010,
+            TimestampRegression { .. } => 1011,
+            OracleWindowUnavailable { .. } => 1012,
+            InvalidAmplification { .. } => 1013,
+            NoConvergence => 1014,
+            InvalidWeights => 1015,
+            InvalidAssetIndex { .. } => 1016,
+            InvalidOutcomeCount { .. } => 1017,
+            MarketResolved => 1018,
+            MarketNotResolved => 1019,
+            WrongPhase { .. } => 1020,
+            ResolutionUnavailable => 1021,
+            InvalidPayout { .. } => 1022,
+            OutstandingClaims { .. } => 1023,
+            InvalidTickRange { .. } => 1024,
+            InvalidSqrtPrice => 1025,
+            NoRoute { .. } => 1026,
+            BatchClosed { .. } => 1027,
+            BatchOpen { .. } => 1028,
+            InvalidEvent { .. } => 1029,
+            ShareSupplyMismatch { .. } => 1030,
+            InvalidCreditParams => 1031,
+            LtvExceeded { .. } => 1032,
+            InsufficientCollateral { .. } => 1033,
+            InvalidRateModel => 1034,
+            InsufficientSupply { .. } => 1035,
+            InvalidCapacity => 1036,
+            UnknownAccount { .. } => 1037,
+            InvalidTickSpacing { .. } => 1038,
+            InvalidTick { .. } => 1039,
+            BatchNotOpen { .. } => 1040,
+            InvalidPair { .. } => 1041,
+            ProtocolFeeTooHigh { .. } => 1042,
+        }
+    }
+
+    /// Código string estável (`AMM_*`).
+    pub fn code_str(&self) -> &'static str {
+        use AmmError::*;
+        match self {
+            ZeroAmount => "AMM_ZERO_AMOUNT",
+            ZeroReserve => "AMM_ZERO_RESERVE",
+            MinReserveBreached => "AMM_MIN_RESERVE_BREACHED",
+            Overflow => "AMM_OVERFLOW",
+            InputTooSmall => "AMM_INPUT_TOO_SMALL",
+            SlippageExceeded { .. } => "AMM_SLIPPAGE_EXCEEDED",
+            InsufficientShares { .. } => "AMM_INSUFFICIENT_SHARES",
+            EmptyPool => "AMM_EMPTY_POOL",
+            FeeTooHigh { .. } => "AMM_FEE_TOO_HIGH",
+            InsufficientLiquidity { .. } => "AMM_INSUFFICIENT_LIQUIDITY",
+            TimestampRegression { .. } => "AMM_TIMESTAMP_REGRESSION",
+            OracleWindowUnavailable { .. } => "AMM_ORACLE_WINDOW_UNAVAILABLE",
+            InvalidAmplification { .. } => "AMM_INVALID_AMPLIFICATION",
+            NoConvergence => "AMM_NO_CONVERGENCE",
+            InvalidWeights => "AMM_INVALID_WEIGHTS",
+            InvalidAssetIndex { .. } => "AMM_INVALID_ASSET_INDEX",
+            InvalidOutcomeCount { .. } => "AMM_INVALID_OUTCOME_COUNT",
+            MarketResolved => "AMM_MARKET_RESOLVED",
+            MarketNotResolved => "AMM_MARKET_NOT_RESOLVED",
+            WrongPhase { .. } => "AMM_WRONG_PHASE",
+            ResolutionUnavailable => "AMM_RESOLUTION_UNAVAILABLE",
+            InvalidPayout { .. } => "AMM_INVALID_PAYOUT",
+            OutstandingClaims { .. } => "AMM_OUTSTANDING_CLAIMS",
+            InvalidTickRange { .. } => "AMM_INVALID_TICK_RANGE",
+            InvalidSqrtPrice => "AMM_INVALID_SQRT_PRICE",
+            NoRoute { .. } => "AMM_NO_ROUTE",
+            BatchClosed { .. } => "AMM_BATCH_CLOSED",
+            BatchOpen { .. } => "AMM_BATCH_OPEN",
+            InvalidEvent { .. } => "AMM_INVALID_EVENT",
+            ShareSupplyMismatch { .. } => "AMM_SHARE_SUPPLY_MISMATCH",
+            InvalidCreditParams => "CREDIT_INVALID_PARAMS",
+            LtvExceeded { .. } => "CREDIT_LTV_EXCEEDED",
+            InsufficientCollateral { .. } => "CREDIT_INSUFFICIENT_COLLATERAL",
+            InvalidRateModel => "CREDIT_INVALID_RATE_MODEL",
+            InsufficientSupply { .. } => "CREDIT_INSUFFICIENT_SUPPLY",
+            InvalidCapacity => "AMM_INVALID_CAPACITY",
+            UnknownAccount { .. } => "AMM_UNKNOWN_ACCOUNT",
+            InvalidTickSpacing { .. } => "AMM_INVALID_TICK_SPACING",
+            InvalidTick { .. } => "AMM_INVALID_TICK",
+            BatchNotOpen { .. } => "AMM_BATCH_NOT_OPEN",
+            InvalidPair { .. } => "AMM_INVALID_PAIR",
+            ProtocolFeeTooHigh { .. } => "AMM_PROTOCOL_FEE_TOO_HIGH",
+        }
+    }
+
+    /// Mensagem localizada (com os valores de contexto).
+    pub fn message(&self, lang: Lang) -> String {
+        use AmmError::*;
+        match (self, lang) {
+            (ZeroAmount, Lang::Pt) => "amount deve ser > 0".into(),
+            (ZeroAmount, Lang::En) => "amount must be > 0".into(),
+            (ZeroReserve, Lang::Pt) => "reserve deve ser > 0".into(),
+            (ZeroReserve, Lang::En) => "reserve must be > 0".into(),
+            (MinReserveBreached, Lang::Pt) => "reserva ficaria abaixo do mínimo".into(),
+            (MinReserveBreached, Lang::En) => "reserve would fall below the minimum".into(),
+            (Overflow, Lang::Pt) => "overflow/underflow numérico".into(),
+            (Overflow, Lang::En) => "numeric overflow/underflow".into(),
+            (InputTooSmall, Lang::Pt) => "input efetivo após taxa é 0".into(),
+            (InputTooSmall, Lang::En) => "effective input after fee is 0".into(),
+            (SlippageExceeded { expected, actual }, Lang::Pt) => format!("slippage excedido: limite {}, obtido {}", expected, actual),
+            (SlippageExceeded { expected, actual }, Lang::En) => format!("slippage exceeded: bound {}, got {}", expected, actual),
+            (InsufficientShares { requested, available }, Lang::Pt) => format!("shares insuficientes: pedido {}, disponível {}", requested, available),
+            (InsufficientShares { requested, available }, Lang::En) => format!("insufficient shares: requested {}, available {}", requested, available),
+            (EmptyPool, Lang::Pt) => "pool sem supply de shares".into(),
+            (EmptyPool, Lang::En) => "pool has no share supply".into(),
+            (FeeTooHigh { fee_ppm }, Lang::Pt) => format!("fee_ppm {} fora de 0..1_000_000", fee_ppm),
+            (FeeTooHigh { fee_ppm }, Lang::En) => format!("fee_ppm {} outside 0..1_000_000", fee_ppm),
+            (InsufficientLiquidity { requested, available }, Lang::Pt) => format!("liquidez insuficiente: pedido {}, disponível {}", requested, available),
+            (InsufficientLiquidity { requested, available }, Lang::En) => format!("insufficient liquidity: requested {}, available {}", requested, available),
+            (TimestampRegression { last, now }, Lang::Pt) => format!("timestamp {} anterior ao último registrado {}", now, last),
+            (TimestampRegression { last, now }, Lang::En) => format!("timestamp {} is before the last recorded {}", now, last),
+            (OracleWindowUnavailable { window, available }, Lang::Pt) => format!("janela de {}s indisponível: histórico cobre {}s", window, available),
+            (OracleWindowUnavailable { window, available }, Lang::En) => format!("{}s window unavailable: history covers {}s", window, available),
+            (InvalidAmplification { amp }, Lang::Pt) => format!("amplificação {} fora da faixa permitida", amp),
+            (InvalidAmplification { amp }, Lang::En) => format!("amplification {} outside the allowed range", amp),
+            (NoConvergence, Lang::Pt) => "iteração numérica não convergiu".into(),
+            (NoConvergence, Lang::En) => "numeric iteration did not converge".into(),
+            (InvalidWeights, Lang::Pt) => "pesos inválidos: cada um ≥ mínimo e soma = 1".into(),
+            (InvalidWeights, Lang::En) => "invalid weights: each ≥ minimum and sum = 1".into(),
+            (InvalidAssetIndex { index, len }, Lang::Pt) => format!("ativo {} inválido para pool com {} ativos", index, len),
+            (InvalidAssetIndex { index, len }, Lang::En) => format!("asset {} invalid for a pool with {} assets", index, len),
+            (InvalidOutcomeCount { count }, Lang::Pt) => format!("quantidade de outcomes {} fora da faixa suportada", count),
+            (InvalidOutcomeCount { count }, Lang::En) => format!("outcome count {} outside the supported range", count),
+            (MarketResolved, Lang::Pt) => "mercado já resolvido".into(),
+            (MarketResolved, Lang::En) => "market already resolved".into(),
+            (MarketNotResolved, Lang::Pt) => "mercado ainda não resolvido".into(),
+            (MarketNotResolved, Lang::En) => "market not resolved yet".into(),
+            (WrongPhase { expected, actual }, Lang::Pt) => format!("operação exige fase {:?}, mercado está em {:?}", expected, actual),
+            (WrongPhase { expected, actual }, Lang::En) => format!("operation requires phase {:?}, market is {:?}", expected, actual),
+            (ResolutionUnavailable, Lang::Pt) => "fonte de resolução ainda sem resultado".into(),
+            (ResolutionUnavailable, Lang::En) => "resolution source has no result yet".into(),
+            (InvalidPayout { yes, no }, Lang::Pt) => format!("payout ({}, {}) não soma 1", yes, no),
+            (InvalidPayout { yes, no }, Lang::En) => format!("payout ({}, {}) does not sum to 1", yes, no),
+            (OutstandingClaims { owed }, Lang::Pt) => format!("claims ainda não pagos: {} de colateral", owed),
+            (OutstandingClaims { owed }, Lang::En) => format!("claims still unpaid: {} of collateral", owed),
+            (InvalidTickRange { lower, upper }, Lang::Pt) => format!("faixa de ticks inválida: [{}, {})", lower, upper),
+            (InvalidTickRange { lower, upper }, Lang::En) => format!("invalid tick range: [{}, {})", lower, upper),
+            (InvalidSqrtPrice, Lang::Pt) => "preço raiz fora da faixa suportada".into(),
+            (InvalidSqrtPrice, Lang::En) => "sqrt price outside the supported range".into(),
+            (NoRoute { token_in, token_out }, Lang::Pt) => format!("nenhuma rota de {} para {}", token_in, token_out),
+            (NoRoute { token_in, token_out }, Lang::En) => format!("no route from {} to {}", token_in, token_out),
+            (BatchClosed { closes_at, now }, Lang::Pt) => format!("lote fechado em {}: intent recebido em {}", closes_at, now),
+            (BatchClosed { closes_at, now }, Lang::En) => format!("batch closed at {}: intent received at {}", closes_at, now),
+            (BatchOpen { closes_at, now }, Lang::Pt) => format!("lote aberto até {}: liquidação pedida em {}", closes_at, now),
+            (BatchOpen { closes_at, now }, Lang::En) => format!("batch open until {}: clearing requested at {}", closes_at, now),
+            (InvalidEvent { seq }, Lang::Pt) => format!("evento {} não reproduz o log", seq),
+            (InvalidEvent { seq }, Lang::En) => format!("event {} does not replay the log", seq),
+            (ShareSupplyMismatch { balances, total_shares }, Lang::Pt) => format!("saldos de LP somam {}, supply do pool é {}", balances, total_shares),
+            (ShareSupplyMismatch { balances, total_shares }, Lang::En) => format!("LP balances sum to {}, pool supply is {}", balances, total_shares),
+            (InvalidCreditParams, Lang::Pt) => "parâmetros de crédito inválidos: haircut < 100% e 0 < LTV ≤ limiar ≤ 100%".into(),
+            (InvalidCreditParams, Lang::En) => "invalid credit params: haircut < 100% and 0 < LTV ≤ threshold ≤ 100%".into(),
+            (LtvExceeded { debt, limit }, Lang::Pt) => format!("dívida {} acima do limite {} do colateral", debt, limit),
+            (LtvExceeded { debt, limit }, Lang::En) => format!("debt {} above the collateral limit {}", debt, limit),
+            (InsufficientCollateral { requested, available }, Lang::Pt) => format!("colateral insuficiente: pedido {}, depositado {}", requested, available),
+            (InsufficientCollateral { requested, available }, Lang::En) => format!("insufficient collateral: requested {}, deposited {}", requested, available),
+            (InvalidRateModel, Lang::Pt) => "modelo de juros inválido: 0 < joelho/alvo < 1 e 0 < mínimo ≤ taxa ≤ máximo".into(),
+            (InvalidRateModel, Lang::En) => "invalid rate model: 0 < kink/target < 1 and 0 < min ≤ rate ≤ max".into(),
+            (InsufficientSupply { requested, available }, Lang::Pt) => format!("oferta insuficiente: pedido {}, saldo {}", requested, available),
+            (InsufficientSupply { requested, available }, Lang::En) => format!("insufficient supply: requested {}, balance {}", requested, available),
+            (InvalidCapacity, Lang::Pt) => "capacidade inválida: precisa de ao menos 1 posição".into(),
+            (InvalidCapacity, Lang::En) => "invalid capacity: at least 1 slot required".into(),
+            (UnknownAccount { account }, Lang::Pt) => format!("conta {} sem posição", account),
+            (UnknownAccount { account }, Lang::En) => format!("account {} has no position", account),
+            (InvalidTickSpacing { spacing }, Lang::Pt) => format!("espaçamento de ticks inválido: {}", spacing),
+            (InvalidTickSpacing { spacing }, Lang::En) => format!("invalid tick spacing: {}", spacing),
+            (InvalidTick { tick }, Lang::Pt) => format!("tick fora dos limites: {}", tick),
+            (InvalidTick { tick }, Lang::En) => format!("tick out of bounds: {}", tick),
+            (BatchNotOpen { opens_at, now }, Lang::Pt) => format!("lote abre em {}: intent recebido em {}", opens_at, now),
+            (BatchNotOpen { opens_at, now }, Lang::En) => format!("batch opens at {}: intent received at {}", opens_at, now),
+            (InvalidPair { token }, Lang::Pt) => format!("par inválido: token {} nos dois lados", token),
+            (InvalidPair { token }, Lang::En) => format!("invalid pair: token {} on both sides", token),
+            (ProtocolFeeTooHigh { protocol_fee_ppm }, Lang::Pt) => format!("protocol_fee_ppm {} fora de 0..=1_000_000", protocol_fee_ppm),
+            (ProtocolFeeTooHigh { protocol_fee_ppm }, Lang::En) => format!("protocol_fee_ppm {} outside 0..=1_000_000", protocol_fee_ppm),
+        }
+    }
 }
 
 impl fmt::Display for AmmError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use AmmError::*;
-        let s = match self {
-            ZeroAmount => "amount deve ser > 0",
-            ZeroReserve => "reserve deve ser > 0",
-            MinReserveBreached => "reserva ficaria abaixo do mínimo",
-            Overflow => "overflow/underflow numérico",
-            InputTooSmall => "input efetivo após taxa é 0",
-        };
-        write!(f, "{}", s)
+        write!(f, "{}", self.message(Lang::Pt))
     }
 }
 
 impl std::error::Error for AmmError {}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_codes_are_stable_and_unique() {
+        let all = [
+            AmmError::ZeroAmount,
+            AmmError::ZeroReserve,
+            AmmError::MinReserveBreached,
+            AmmError::Overflow,
+            AmmError::InputTooSmall,
+            AmmError::SlippageExceeded { expected: 0, actual: 0 },
+            AmmError::InsufficientShares { requested: 0, available: 0 },
+            AmmError::EmptyPool,
+            AmmError::FeeTooHigh { fee_ppm: 0 },
+            AmmError::InsufficientLiquidity { requested: 0, available: 0 },
+            AmmError::TimestampRegression { last: 0, now: 0 },
+            AmmError::OracleWindowUnavailable { window: 0, available: 0 },
+            AmmError::InvalidAmplification { amp: 0 },
+            AmmError::NoConvergence,
+            AmmError::InvalidWeights,
+            AmmError::InvalidAssetIndex { index: 0, len: 0 },
+            AmmError::InvalidOutcomeCount { count: 0 },
+            AmmError::MarketResolved,
+            AmmError::MarketNotResolved,
+            AmmError::WrongPhase { expected: MarketPhase::Open, actual: MarketPhase::Open },
+            AmmError::ResolutionUnavailable,
+            AmmError::InvalidPayout { yes: 0, no: 0 },
+            AmmError::OutstandingClaims { owed: 0 },
+            AmmError::InvalidTickRange { lower: 0, upper: 0 },
+            AmmError::InvalidSqrtPrice,
+            AmmError::NoRoute { token_in: 0, token_out: 0 },
+            AmmError::BatchClosed { closes_at: 0, now: 0 },
+            AmmError::BatchOpen { closes_at: 0, now: 0 },
+            AmmError::InvalidEvent { seq: 0 },
+            AmmError::ShareSupplyMismatch { balances: 0, total_shares: 0 },
+            AmmError::InvalidCreditParams,
+            AmmError::LtvExceeded { debt: 0, limit: 0 },
+            AmmError::InsufficientCollateral { requested: 0, available: 0 },
+            AmmError::InvalidRateModel,
+            AmmError::InsufficientSupply { requested: 0, available: 0 },
+            AmmError::InvalidCapacity,
+            AmmError::UnknownAccount { account: 0 },
+            AmmError::InvalidTickSpacing { spacing: 0 },
+            AmmError::InvalidTick { tick: 0 },
+            AmmError::BatchNotOpen { opens_at: 0, now: 0 },
+            AmmError::InvalidPair { token: 0 },
+            AmmError::ProtocolFeeTooHigh { protocol_fee_ppm: 0 },
+        ];
+        let codes: Vec<u16> = all.iter().map(AmmError::code).collect();
+        assert_eq!(codes, (1001..=1042).collect::<Vec<u16>>());
+        let mut strs: Vec<&str> = all.iter().map(AmmError::code_str).collect();
+        strs.sort();
+        strs.dedup();
+        assert_eq!(strs.len(), all.len());
+        assert!(strs.iter().all(|s| s.starts_with("AMM_") || s.starts_with("CREDIT_")));
+        assert_eq!(AmmError::LtvExceeded { debt: 0, limit: 0 }.code_str(), "CREDIT_LTV_EXCEEDED");
+    }
+
+    #[test]
+    fn t_messages_localized_with_context() {
+        let e = AmmError::InsufficientShares { requested: 5, available: 3 };
+        assert_eq!(e.to_string(), "shares insuficientes: pedido 5, disponível 3");
+        assert_eq!(e.message(Lang::En), "insufficient shares: requested 5, available 3");
+        assert_eq!(AmmError::ZeroAmount.message(Lang::En), "amount must be > 0");
+    }
+}
@@ -0,0 +1,106 @@
+//! Divisão da taxa de swap entre LPs e tesouraria do protocolo.
+//! Políticas (ADR-0001, tabela de arredondamento):
+//! - `fee` total: ceil sobre o input (inalterado, ver `swap`)
+//! - `protocol_fee = floor(fee * protocol_fee_ppm / 1e6)`: o protocolo nunca recebe a mais
+//! - `lp_fee = fee - protocol_fee`: o resto fica com os LPs
+//!
+//! A parte do protocolo **sai** das reservas (fica acumulada até `collect`); a parte dos LPs
+//! permanece no pool, então `k' ≥ k` continua valendo sempre que `lp_fee > 0`. Com `lp_fee = 0`
+//! (taxa zero ou protocolo com 100%) o swap equivale ao sem taxa: `k` só se conserva a menos do
+//! nearest-even de `y*` (até ½ wei vezes a reserva de entrada).
+
+use super::errors::AmmError;
+use super::guardrails::checked_add;
+use super::swap::fee_on_input_ceil;
+use super::types::{Direction, Ppm, U256, Wad, PPM_SCALE};
+
+/// Taxa de um swap separada por destinatário.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub lp_fee: Wad,
+    pub protocol_fee: Wad,
+}
+
+impl FeeSplit {
+    pub fn total(&self) -> Wad { self.lp_fee + self.protocol_fee }
+}
+
+/// Parte do protocolo válida: `0 <= protocol_fee_ppm <= 1_000_000` (em ppm **da taxa**).
+#[inline]
+pub fn ensure_protocol_fee(protocol_fee_ppm: Ppm) -> Result<(), AmmError> {
+    if protocol_fee_ppm > PPM_SCALE { Err(AmmError::ProtocolFeeTooHigh { protocol_fee_ppm }) } else { Ok(()) }
+}
+
+/// Divide uma taxa já cobrada: protocolo = floor, LP = resto.
+pub fn split_fee(fee: Wad, protocol_fee_ppm: Ppm) -> Result<FeeSplit, AmmError> {
+    ensure_protocol_fee(protocol_fee_ppm)?;
+    let protocol = U256::from(fee) * U256::from(protocol_fee_ppm as u64) / U256::from(PPM_SCALE as u64); // floor
+    let protocol_fee = protocol.as_u128(); // ≤ fee
+    Ok(FeeSplit { lp_fee: fee - protocol_fee, protocol_fee })
+}
+
+/// Taxa de um input bruto `amount_in` (ceil) já dividida entre LPs e protocolo.
+pub fn fee_split_on_input(amount_in: Wad, fee_ppm: Ppm, protocol_fee_ppm: Ppm) -> Result<FeeSplit, AmmError> {
+    split_fee(fee_on_input_ceil(amount_in, fee_ppm), protocol_fee_ppm)
+}
+
+/// Taxas do protocolo acumuladas por ativo, resgatáveis via `collect`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolFees {
+    pub x: Wad,
+    pub y: Wad,
+}
+
+impl ProtocolFees {
+    /// Acumula `amount` no ativo de **entrada** de `dir` (a taxa é cobrada sobre o input).
+    pub fn accrue(&mut self, dir: Direction, amount: Wad) -> Result<(), AmmError> {
+        match dir {
+            Direction::XtoY => self.x = checked_add(self.x, amount)?,
+            Direction::YtoX => self.y = checked_add(self.y, amount)?,
+        }
+        Ok(())
+    }
+
+    /// Resgata tudo o que foi acumulado e zera o acumulador. Retorna `(x, y)`.
+    pub fn collect(&mut self) -> (Wad, Wad) {
+        let out = (self.x, self.y);
+        *self = Self::default();
+        out
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::WAD;
+
+    #[test]
+    fn t_split_floor_to_protocol() {
+        // 1/6 da taxa para o protocolo: 7 * 166_667 / 1e6 = 1.16 ⇒ floor 1
+        let s = split_fee(7, 166_667).unwrap();
+        assert_eq!(s, FeeSplit { lp_fee: 6, protocol_fee: 1 });
+        assert_eq!(split_fee(30 * WAD, 0).unwrap(), FeeSplit { lp_fee: 30 * WAD, protocol_fee: 0 });
+        assert_eq!(split_fee(30 * WAD, PPM_SCALE).unwrap(), FeeSplit { lp_fee: 0, protocol_fee: 30 * WAD });
+        assert_eq!(split_fee(1, PPM_SCALE + 1).unwrap_err(), AmmError::ProtocolFeeTooHigh { protocol_fee_ppm: PPM_SCALE + 1 });
+    }
+
+    #[test]
+    fn t_split_on_input_matches_swap_fee() {
+        let s = fee_split_on_input(10_000 * WAD, 3000, 250_000).unwrap();
+        assert_eq!(s.total(), 30 * WAD);
+        assert_eq!(s.protocol_fee, 7_500_000_000_000_000_000);
+    }
+
+    #[test]
+    fn t_accrue_and_collect() {
+        let mut acc = ProtocolFees::default();
+        acc.accrue(Direction::XtoY, 5).unwrap();
+        acc.accrue(Direction::YtoX, 7).unwrap();
+        acc.accrue(Direction::XtoY, 1).unwrap();
+        assert_eq!(acc.collect(), (6, 7));
+        assert_eq!(acc, ProtocolFees::default());
+    }
+}
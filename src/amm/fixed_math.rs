@@ -0,0 +1,199 @@
+//! Matemática de ponto fixo determinística: `ln`, `exp` e `pow` para as curvas com pesos.
+//! Só inteiros (i128/U256) em escala interna 1e36: mesmo resultado bit a bit em qualquer plataforma.
+//!
+//! Cotas de erro (sobre o valor real, antes do arredondamento final):
+//! - `ln_e36(x)`: |erro| ≤ 1e-33 (absoluto);
+//! - `exp_e36(t)`: erro relativo ≤ 1e-33, depois floor na escala 1e36 (≤ 1 unidade);
+//! - `pow_ratio_e36((n/d)^(p/q))`: erro relativo ≤ (1 + 2·p/q)·1e-33, mais 1 unidade de floor.
+//!
+//! `pow_ratio_up`/`pow_ratio_down` aplicam uma folga 100× maior que a cota, então
+//! `down ≤ exato ≤ up`: quem chama escolhe o lado que favorece o pool (ADR-0001).
+//! `ln_wad`/`exp_wad`/`pow_wad` são as mesmas contas com entrada/saída em WAD.
+
+use super::errors::AmmError;
+use super::guardrails::{div_nearest_even_u256, u256_to_u128_checked};
+use super::types::{U256, Wad, WAD};
+
+/// Escala interna (1e36).
+pub const E36: u128 = 1_000_000_000_000_000_000_000_000_000_000_000_000;
+/// ln(2)·1e36, truncado.
+const LN2_E36: i128 = 693_147_180_559_945_309_417_232_121_458_176_568;
+/// Maior expoente de `exp_e36`: e^90·1e36 ≈ 1.2e75 ainda cabe em U256.
+pub const MAX_EXP_E36: i128 = 90 * E36 as i128;
+/// Abaixo disso `e^t·1e36 < 1` ⇒ 0.
+const MIN_EXP_E36: i128 = -84 * E36 as i128;
+
+/// `ln(x / WAD)·1e36`, para `x > 0`.
+pub fn ln_e36(x: Wad) -> Result<i128, AmmError> { ln_scaled_e36(U256::from(x), U256::from(WAD)) }
+
+/// `ln(x / one)·1e36` para `x` e `one` em qualquer escala (até 2^128).
+pub(crate) fn ln_scaled_e36(x: U256, one: U256) -> Result<i128, AmmError> {
+    if x.is_zero() { return Err(AmmError::ZeroAmount); }
+    if one.is_zero() || x.bits() > 128 || one.bits() > 128 { return Err(AmmError::Overflow); }
+    // x/one = m·2^k com m ∈ [1, 2); compara `a = x·2^-k` com `b = one·2^k` sem perder bits
+    let split = |k: i32| if k >= 0 { (x, one << k as usize) } else { (x << (-k) as usize, one) };
+    let mut k: i32 = x.bits() as i32 - one.bits() as i32;
+    let (a, b) = loop {
+        let (a, b) = split(k);
+        if a < b { k -= 1; } else if a >= b << 1 { k += 1; } else { break (a, b); }
+    };
+    // z = (m - 1)/(m + 1) ∈ [0, 1/3);  ln m = 2·atanh(z) = 2·Σ z^(2i+1)/(2i+1)
+    let e36 = U256::from(E36);
+    let z = div_nearest_even_u256((a - b) * e36, a + b)?;
+    let z2 = z * z / e36;
+    let (mut p, mut s, mut i) = (z, U256::zero(), 1u64);
+    while !p.is_zero() {
+        s += p / U256::from(i);
+        p = p * z2 / e36;
+        i += 2;
+    }
+    Ok(k as i128 * LN2_E36 + 2 * s.as_u128() as i128)
+}
+
+/// `e^(t / 1e36)·1e36` (floor); `Overflow` acima de `MAX_EXP_E36`.
+pub fn exp_e36(t: i128) -> Result<U256, AmmError> {
+    if t > MAX_EXP_E36 { return Err(AmmError::Overflow); }
+    if t < MIN_EXP_E36 { return Ok(U256::zero()); }
+    // t = k·ln2 + r com |r| ≤ ln2/2; e^|r| por Taylor, e^-|r| = 1/e^|r|
+    let k = (t + LN2_E36 / 2).div_euclid(LN2_E36);
+    let r = t - k * LN2_E36;
+    let e36 = U256::from(E36);
+    let ra = U256::from(r.unsigned_abs());
+    let (mut term, mut sum, mut n) = (e36, e36, 1u64);
+    loop {
+        term = term * ra / (e36 * U256::from(n));
+        if term.is_zero() { break; }
+        sum += term;
+        n += 1;
+    }
+    let er = if r < 0 { e36 * e36 / sum } else { sum };
+    Ok(if k >= 0 { er << k as usize } else { er >> (-k) as usize })
+}
+
+/// `a·b/c` com sinal de `a` (trunca em direção a 0); `Overflow` se não couber em i128.
+pub(crate) fn mul_div_signed(a: i128, b: u128, c: u128) -> Result<i128, AmmError> {
+    if c == 0 { return Err(AmmError::Overflow); }
+    let m = U256::from(a.unsigned_abs()) * U256::from(b) / U256::from(c);
+    if m > U256::from(i128::MAX as u128) { return Err(AmmError::Overflow); }
+    let m = m.as_u128() as i128;
+    Ok(if a < 0 { -m } else { m })
+}
+
+/// `(num/den)^(p/q)·1e36` (estimativa central, ver cotas no topo).
+pub fn pow_ratio_e36(num: Wad, den: Wad, p: Wad, q: Wad) -> Result<U256, AmmError> {
+    if num == 0 || den == 0 || q == 0 { return Err(AmmError::ZeroAmount); }
+    if p == 0 || num == den { return Ok(U256::from(E36)); }
+    let l = ln_e36(num)? - ln_e36(den)?;
+    let t = U256::from(l.unsigned_abs()) * U256::from(p) / U256::from(q);
+    if t > U256::from(MAX_EXP_E36 as u128) {
+        return if l < 0 { Ok(U256::zero()) } else { Err(AmmError::Overflow) };
+    }
+    let t = t.as_u128() as i128;
+    exp_e36(if l < 0 { -t } else { t })
+}
+
+/// Folga de `pow_ratio_*`: `ceil(r·(1 + 2·p/q)·1e-31) + 1`.
+fn pow_slack(r: U256, p: Wad, q: Wad) -> Result<U256, AmmError> {
+    let m = U256::from(q) + (U256::from(p) << 1);
+    let n = r.checked_mul(m).ok_or(AmmError::Overflow)?;
+    let d = U256::from(q) * U256::from(10u8).pow(U256::from(31u8));
+    Ok((n + d - U256::from(1u8)) / d + U256::from(1u8))
+}
+
+/// Cota superior de `(num/den)^(p/q)·1e36`.
+pub fn pow_ratio_up(num: Wad, den: Wad, p: Wad, q: Wad) -> Result<U256, AmmError> {
+    let r = pow_ratio_e36(num, den, p, q)?;
+    if r == U256::from(E36) { return Ok(r); } // caso exato (expoente 0 ou base 1)
+    r.checked_add(pow_slack(r, p, q)?).ok_or(AmmError::Overflow)
+}
+
+/// Cota inferior de `(num/den)^(p/q)·1e36`.
+pub fn pow_ratio_down(num: Wad, den: Wad, p: Wad, q: Wad) -> Result<U256, AmmError> {
+    let r = pow_ratio_e36(num, den, p, q)?;
+    if r == U256::from(E36) { return Ok(r); }
+    Ok(r.saturating_sub(pow_slack(r, p, q)?))
+}
+
+/// `ln(x / WAD)` em WAD com sinal (nearest).
+pub fn ln_wad(x: Wad) -> Result<i128, AmmError> {
+    let half = (WAD / 2) as i128;
+    Ok((ln_e36(x)? + half).div_euclid(WAD as i128))
+}
+
+/// `e^(t / WAD)` em WAD (floor).
+pub fn exp_wad(t: i128) -> Result<Wad, AmmError> {
+    let t36 = match t.checked_mul(WAD as i128) {
+        Some(v) => v,
+        None if t < 0 => return Ok(0),
+        None => return Err(AmmError::Overflow),
+    };
+    u256_to_u128_checked(exp_e36(t36)? / U256::from(WAD))
+}
+
+/// `(x / WAD)^(y / WAD)` em WAD (floor da estimativa central); `0^y = 0` e `x^0 = 1`.
+pub fn pow_wad(x: Wad, y: Wad) -> Result<Wad, AmmError> {
+    if y == 0 { return Ok(WAD); }
+    if x == 0 { return Ok(0); }
+    u256_to_u128_checked(pow_ratio_e36(x, WAD, y, WAD)? / U256::from(WAD))
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // constantes com 36 casas (truncadas)
+    const LN10_E36: i128 = 2_302_585_092_994_045_684_017_991_454_684_364_207;
+    const E_E36: u128 = 2_718_281_828_459_045_235_360_287_471_352_662_497;
+
+    #[test]
+    fn t_ln_known_values() {
+        assert_eq!(ln_e36(WAD).unwrap(), 0);
+        assert!((ln_e36(2 * WAD).unwrap() - LN2_E36).abs() <= 1_000);
+        assert!((ln_e36(10 * WAD).unwrap() - LN10_E36).abs() <= 1_000);
+        assert!((ln_e36(WAD / 10).unwrap() + LN10_E36).abs() <= 1_000);
+        assert_eq!(ln_wad(10 * WAD).unwrap(), 2_302_585_092_994_045_684);
+        assert_eq!(ln_e36(0).unwrap_err(), AmmError::ZeroAmount);
+        // extremos do domínio
+        assert!(ln_e36(1).unwrap() < 0);
+        assert!(ln_e36(u128::MAX).unwrap() > 0);
+    }
+
+    #[test]
+    fn t_exp_known_values() {
+        assert_eq!(exp_e36(0).unwrap(), U256::from(E36));
+        let e = exp_e36(E36 as i128).unwrap();
+        assert!(e.as_u128().abs_diff(E_E36) <= 1_000);
+        assert_eq!(exp_wad(WAD as i128).unwrap(), 2_718_281_828_459_045_235);
+        assert_eq!(exp_wad(-100 * WAD as i128).unwrap(), 0);
+        assert_eq!(exp_wad(i128::MIN).unwrap(), 0);
+        assert_eq!(exp_wad(100 * WAD as i128).unwrap_err(), AmmError::Overflow);
+        assert_eq!(exp_e36(MAX_EXP_E36 + 1).unwrap_err(), AmmError::Overflow);
+    }
+
+    #[test]
+    fn t_exp_ln_round_trip() {
+        for x in [1u128, 7, WAD / 3, WAD, 3 * WAD / 2, 12_345 * WAD, u128::MAX / 2] {
+            let back = exp_e36(ln_e36(x).unwrap()).unwrap() / U256::from(WAD);
+            let err = back.as_u128().abs_diff(x);
+            assert!(err <= 1 + x / 1_000_000_000_000_000_000_000_000_000_000, "x={} back={}", x, back);
+        }
+    }
+
+    #[test]
+    fn t_pow_integer_and_root() {
+        assert!(pow_wad(2 * WAD, 10 * WAD).unwrap().abs_diff(1024 * WAD) <= 1);
+        assert!(pow_wad(9 * WAD, WAD / 2).unwrap().abs_diff(3 * WAD) <= 1);
+        assert_eq!(pow_wad(0, WAD).unwrap(), 0);
+        assert_eq!(pow_wad(5 * WAD, 0).unwrap(), WAD);
+        // (1/2)^4 com cotas direcionadas
+        let (lo, hi) = (pow_ratio_down(1, 2, 4, 1).unwrap(), pow_ratio_up(1, 2, 4, 1).unwrap());
+        let exact = U256::from(E36 / 16);
+        assert!(lo <= exact && exact <= hi && hi - lo < U256::from(1_000_000u64));
+        assert_eq!(pow_ratio_up(3, 3, 7, 2).unwrap(), U256::from(E36));
+        assert_eq!(pow_ratio_e36(u128::MAX, 1, 10, 1).unwrap_err(), AmmError::Overflow);
+        assert_eq!(pow_ratio_e36(1, u128::MAX, 10, 1).unwrap(), U256::zero());
+    }
+}
@@ -2,7 +2,7 @@
 //! Objetivo: entradas seguras e divisões/multiplicações sem estouro.
 
 use super::errors::AmmError;
-use super::types::{U256, Wad, MIN_RESERVE};
+use super::types::{U256, U512, Ppm, Wad, MIN_RESERVE, PPM_SCALE};
 
 #[inline]
 pub fn ensure_nonzero(amount: Wad) -> Result<(), AmmError> {
@@ -16,6 +16,12 @@ pub fn ensure_reserves(x: Wad, y: Wad) -> Result<(), AmmError> {
     Ok(())
 }
 
+/// Taxa válida: `0 <= fee_ppm < 1_000_000` (ADR-0002 §4: 100% é rejeitado).
+#[inline]
+pub fn ensure_fee(fee_ppm: Ppm) -> Result<(), AmmError> {
+    if fee_ppm >= PPM_SCALE { Err(AmmError::FeeTooHigh { fee_ppm }) } else { Ok(()) }
+}
+
 #[inline]
 pub fn checked_add(a: Wad, b: Wad) -> Result<Wad, AmmError> {
     a.checked_add(b).ok_or(AmmError::Overflow)
@@ -54,6 +60,33 @@ pub fn div_nearest_even_u256_to_u128(n: U256, d: U256) -> Result<Wad, AmmError>
     u256_to_u128_checked(q)
 }
 
+#[inline]
+pub(crate) fn widen(v: U256) -> U512 {
+    let mut limbs = [0u64; 8];
+    limbs[..4].copy_from_slice(&v.0);
+    U512(limbs)
+}
+
+pub(crate) fn narrow(v: U512) -> Result<U256, AmmError> {
+    if v.0[4..].iter().any(|&l| l != 0) { return Err(AmmError::Overflow); }
+    let mut limbs = [0u64; 4];
+    limbs.copy_from_slice(&v.0[..4]);
+    Ok(U256(limbs))
+}
+
+/// `floor(a·b/d)` com produto em 512 bits (o resultado ainda precisa caber em U256).
+pub fn mul_div_floor_u256(a: U256, b: U256, d: U256) -> Result<U256, AmmError> {
+    if d.is_zero() { return Err(AmmError::Overflow); }
+    narrow(widen(a) * widen(b) / widen(d))
+}
+
+/// `ceil(a·b/d)` com produto em 512 bits.
+pub fn mul_div_ceil_u256(a: U256, b: U256, d: U256) -> Result<U256, AmmError> {
+    if d.is_zero() { return Err(AmmError::Overflow); }
+    let (q, r) = (widen(a) * widen(b)).div_mod(widen(d));
+    narrow(if r.is_zero() { q } else { q + U512::from(1u8) })
+}
+
 // -------------------------
 // TESTES
 // -------------------------
@@ -79,9 +112,17 @@ mod tests {
         assert_eq!(ensure_reserves(MIN_RESERVE, MIN_RESERVE - 1).unwrap_err(), AmmError::MinReserveBreached);
     }
 
+    #[test]
+    fn t_ensure_fee_edges() {
+        assert!(ensure_fee(0).is_ok());
+        assert!(ensure_fee(999_999).is_ok());
+        assert_eq!(ensure_fee(1_000_000).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: 1_000_000 });
+        assert_eq!(ensure_fee(u32::MAX).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: u32::MAX });
+    }
+
     #[test]
     fn t_checked_add_sub_over_under_flow() {
-        use core::u128::MAX as UMAX;
+        const UMAX: u128 = u128::MAX;
         // add ok
         assert_eq!(checked_add(1, 2).unwrap(), 3);
         // add overflow
@@ -105,4 +146,16 @@ mod tests {
         let q = div_nearest_even_u256(three, two).unwrap();
         assert_eq!(q, U256::from(2u8));
     }
+
+    #[test]
+    fn t_mul_div_u256_512_bit_product() {
+        let max = U256::MAX;
+        // max·max/max não cabe no produto de 256 bits, mas o resultado sim
+        assert_eq!(mul_div_floor_u256(max, max, max).unwrap(), max);
+        assert_eq!(mul_div_ceil_u256(max, max, max).unwrap(), max);
+        assert_eq!(mul_div_floor_u256(U256::from(7u8), U256::from(3u8), U256::from(2u8)).unwrap(), U256::from(10u8));
+        assert_eq!(mul_div_ceil_u256(U256::from(7u8), U256::from(3u8), U256::from(2u8)).unwrap(), U256::from(11u8));
+        assert_eq!(mul_div_floor_u256(max, U256::from(2u8), U256::one()).unwrap_err(), AmmError::Overflow);
+        assert_eq!(mul_div_ceil_u256(U256::one(), U256::one(), U256::zero()).unwrap_err(), AmmError::Overflow);
+    }
 }
@@ -0,0 +1,223 @@
+//! Ledger de eventos de um `Pool`: cada operação bem-sucedida vira um `PoolEvent` com número de
+//! sequência, inputs (`PoolOp`), outputs (`OpOutcome`) e o hash do estado resultante (`PoolState::hash`).
+//! Operações com erro não geram evento (o pool fica inalterado, ver `pool`).
+//!
+//! `replay` reexecuta o log a partir do evento `Create` (seq 0) e confere, evento a evento, sequência,
+//! outputs e hash: como toda a aritmética é inteira e determinística, o estado final é idêntico bit a
+//! bit ao original — uma disputa ou auditoria se resolve rodando o log de novo.
+
+use super::errors::AmmError;
+use super::fees::ProtocolFees;
+use super::pool::{LiquidityReceipt, Pool, PoolState, SwapReceipt};
+use super::types::{Direction, Ppm, Wad};
+
+/// Inputs de uma operação sobre o pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolOp {
+    /// Gênese: só como primeiro evento do log
+    Create { x: Wad, y: Wad, fee_ppm: Ppm, min_reserve: Wad },
+    SwapExactIn { direction: Direction, amount_in: Wad, min_out: Wad },
+    SwapExactOut { direction: Direction, amount_out: Wad, max_in: Wad },
+    AddLiquidity { amount_x: Wad, amount_y: Wad },
+    RemoveLiquidity { shares: Wad },
+    CollectProtocolFees,
+    SetFee { fee_ppm: Ppm },
+    SetProtocolFee { protocol_fee_ppm: Ppm },
+}
+
+/// Outputs de uma operação.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpOutcome {
+    Created { shares: Wad },
+    Swap(SwapReceipt),
+    Liquidity(LiquidityReceipt),
+    Collected(ProtocolFees),
+    ParamsChanged,
+}
+
+/// Entrada do log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolEvent {
+    /// Posição no log (0 = `Create`)
+    pub seq: u64,
+    pub op: PoolOp,
+    pub outcome: OpOutcome,
+    /// `PoolState::hash` após a operação
+    pub state_hash: [u8; 32],
+}
+
+/// Aplica `op` (exceto `Create`) a `pool`; em erro o pool fica inalterado.
+fn execute(pool: &mut Pool, op: PoolOp) -> Result<OpOutcome, AmmError> {
+    Ok(match op {
+        PoolOp::Create { .. } => return Err(AmmError::InvalidEvent { seq: 0 }),
+        PoolOp::SwapExactIn { direction, amount_in, min_out } => OpOutcome::Swap(pool.swap_exact_in(direction, amount_in, min_out)?),
+        PoolOp::SwapExactOut { direction, amount_out, max_in } => OpOutcome::Swap(pool.swap_exact_out(direction, amount_out, max_in)?),
+        PoolOp::AddLiquidity { amount_x, amount_y } => OpOutcome::Liquidity(pool.add_liquidity(amount_x, amount_y)?),
+        PoolOp::RemoveLiquidity { shares } => OpOutcome::Liquidity(pool.remove_liquidity(shares)?),
+        PoolOp::CollectProtocolFees => {
+            let (x, y) = pool.collect_protocol_fees();
+            OpOutcome::Collected(ProtocolFees { x, y })
+        }
+        PoolOp::SetFee { fee_ppm } => { pool.set_fee_ppm(fee_ppm)?; OpOutcome::ParamsChanged }
+        PoolOp::SetProtocolFee { protocol_fee_ppm } => { pool.set_protocol_fee_ppm(protocol_fee_ppm)?; OpOutcome::ParamsChanged }
+    })
+}
+
+/// Pool com log de eventos: toda mudança de estado passa por `apply`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolLedger {
+    pool: Pool,
+    events: Vec<PoolEvent>,
+}
+
+impl PoolLedger {
+    /// Cria o pool (como `Pool::with_min_reserve`) e registra o evento de gênese.
+    pub fn create(x: Wad, y: Wad, fee_ppm: Ppm, min_reserve: Wad) -> Result<Self, AmmError> {
+        let pool = Pool::with_min_reserve(x, y, fee_ppm, min_reserve)?;
+        let event = PoolEvent {
+            seq: 0,
+            op: PoolOp::Create { x, y, fee_ppm, min_reserve },
+            outcome: OpOutcome::Created { shares: pool.total_shares() },
+            state_hash: pool.state().hash(),
+        };
+        Ok(Self { pool, events: vec![event] })
+    }
+
+    pub fn pool(&self) -> &Pool { &self.pool }
+    pub fn events(&self) -> &[PoolEvent] { &self.events }
+
+    /// Executa `op` e registra o evento; em erro nada é registrado.
+    pub fn apply(&mut self, op: PoolOp) -> Result<OpOutcome, AmmError> {
+        let (pool, event) = self.prepare(op)?;
+        self.commit(pool, event);
+        Ok(event.outcome)
+    }
+
+    /// Calcula o pool e o evento de `op` sem gravar (para persistir antes de `commit`).
+    pub(crate) fn prepare(&self, op: PoolOp) -> Result<(Pool, PoolEvent), AmmError> {
+        let seq = self.events.len() as u64;
+        if matches!(op, PoolOp::Create { .. }) { return Err(AmmError::InvalidEvent { seq }); }
+        let mut pool = self.pool.clone();
+        let outcome = execute(&mut pool, op)?;
+        let state_hash = pool.state().hash();
+        Ok((pool, PoolEvent { seq, op, outcome, state_hash }))
+    }
+
+    pub(crate) fn commit(&mut self, pool: Pool, event: PoolEvent) {
+        self.pool = pool;
+        self.events.push(event);
+    }
+
+    /// Reconstrói o ledger do log completo. Com `snapshot = (seq, estado após seq)` só a cauda é
+    /// reexecutada: o snapshot precisa bater com o `state_hash` registrado no evento `seq`.
+    pub fn restore(events: Vec<PoolEvent>, snapshot: Option<(u64, PoolState)>) -> Result<Self, AmmError> {
+        let Some((seq, state)) = snapshot else {
+            let pool = Pool::restore(replay(&events)?).map_err(|_| AmmError::InvalidEvent { seq: 0 })?;
+            return Ok(Self { pool, events });
+        };
+        let at = usize::try_from(seq).ok().filter(|&i| i < events.len()).ok_or(AmmError::InvalidEvent { seq })?;
+        if events[at].seq != seq || events[at].state_hash != state.hash() { return Err(AmmError::InvalidEvent { seq }); }
+        let mut pool = Pool::restore(state).map_err(|_| AmmError::InvalidEvent { seq })?;
+        replay_tail(&mut pool, seq + 1, &events[at + 1..])?;
+        Ok(Self { pool, events })
+    }
+}
+
+/// Reexecuta `events` sobre `pool`, exigindo `seq` contíguo a partir de `start`.
+fn replay_tail(pool: &mut Pool, start: u64, events: &[PoolEvent]) -> Result<(), AmmError> {
+    for (i, e) in events.iter().enumerate() {
+        let seq = start + i as u64;
+        let mismatch = AmmError::InvalidEvent { seq };
+        if e.seq != seq || matches!(e.op, PoolOp::Create { .. }) { return Err(mismatch); }
+        let outcome = execute(pool, e.op).map_err(|_| mismatch.clone())?;
+        if outcome != e.outcome || pool.state().hash() != e.state_hash { return Err(mismatch); }
+    }
+    Ok(())
+}
+
+/// Reexecuta o log e devolve o estado final; qualquer divergência (sequência, output ou hash)
+/// vira `InvalidEvent` com o `seq` do primeiro evento divergente.
+pub fn replay(events: &[PoolEvent]) -> Result<PoolState, AmmError> {
+    let Some((genesis, rest)) = events.split_first() else { return Err(AmmError::InvalidEvent { seq: 0 }) };
+    let PoolOp::Create { x, y, fee_ppm, min_reserve } = genesis.op else { return Err(AmmError::InvalidEvent { seq: 0 }) };
+    let ledger = PoolLedger::create(x, y, fee_ppm, min_reserve).map_err(|_| AmmError::InvalidEvent { seq: 0 })?;
+    if ledger.events[0] != *genesis { return Err(AmmError::InvalidEvent { seq: 0 }); }
+    let mut pool = ledger.pool;
+    replay_tail(&mut pool, 1, rest)?;
+    Ok(pool.state())
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::{MIN_RESERVE, WAD};
+
+    fn ledger() -> PoolLedger {
+        let mut l = PoolLedger::create(1_000_000 * WAD, 1_000_000 * WAD, 3_000, MIN_RESERVE).unwrap();
+        l.apply(PoolOp::SetProtocolFee { protocol_fee_ppm: 250_000 }).unwrap();
+        l.apply(PoolOp::SwapExactIn { direction: Direction::XtoY, amount_in: 10_000 * WAD, min_out: 0 }).unwrap();
+        l.apply(PoolOp::AddLiquidity { amount_x: 50_000 * WAD, amount_y: 40_000 * WAD }).unwrap();
+        l.apply(PoolOp::SwapExactOut { direction: Direction::YtoX, amount_out: 5_000 * WAD, max_in: Wad::MAX }).unwrap();
+        l.apply(PoolOp::SetFee { fee_ppm: 10_000 }).unwrap();
+        l.apply(PoolOp::RemoveLiquidity { shares: 20_000 * WAD }).unwrap();
+        l.apply(PoolOp::CollectProtocolFees).unwrap();
+        l
+    }
+
+    #[test]
+    fn t_replay_reproduces_state() {
+        let l = ledger();
+        assert_eq!(l.events().len(), 8);
+        assert!(l.events().iter().enumerate().all(|(i, e)| e.seq == i as u64));
+        assert_eq!(replay(l.events()).unwrap(), l.pool().state());
+        // prefixo do log = estado naquele ponto
+        let prefix = &l.events()[..3];
+        assert_eq!(replay(prefix).unwrap().hash(), prefix[2].state_hash);
+    }
+
+    #[test]
+    fn t_restore_from_snapshot_replays_tail() {
+        let l = ledger();
+        let snap = replay(&l.events()[..4]).unwrap();
+        assert_eq!(PoolLedger::restore(l.events().to_vec(), Some((3, snap))).unwrap(), l);
+        assert_eq!(PoolLedger::restore(l.events().to_vec(), None).unwrap(), l);
+        // snapshot que não bate com o hash do evento
+        assert_eq!(PoolLedger::restore(l.events().to_vec(), Some((4, snap))).unwrap_err(), AmmError::InvalidEvent { seq: 4 });
+        assert_eq!(PoolLedger::restore(l.events().to_vec(), Some((99, snap))).unwrap_err(), AmmError::InvalidEvent { seq: 99 });
+    }
+
+    #[test]
+    fn t_failed_op_is_not_logged() {
+        let mut l = ledger();
+        let before = l.clone();
+        assert_eq!(l.apply(PoolOp::SetFee { fee_ppm: 1_000_000 }).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: 1_000_000 });
+        assert!(matches!(l.apply(PoolOp::SwapExactIn { direction: Direction::XtoY, amount_in: WAD, min_out: WAD }), Err(AmmError::SlippageExceeded { .. })));
+        assert_eq!(l.apply(PoolOp::Create { x: WAD, y: WAD, fee_ppm: 0, min_reserve: 0 }).unwrap_err(), AmmError::InvalidEvent { seq: 8 });
+        assert_eq!(l, before);
+    }
+
+    #[test]
+    fn t_tampered_log_is_rejected() {
+        let l = ledger();
+        let mut events = l.events().to_vec();
+        if let OpOutcome::Swap(r) = &mut events[2].outcome { r.amount_out += 1; }
+        assert_eq!(replay(&events).unwrap_err(), AmmError::InvalidEvent { seq: 2 });
+
+        let mut events = l.events().to_vec();
+        events[4].state_hash[0] ^= 1;
+        assert_eq!(replay(&events).unwrap_err(), AmmError::InvalidEvent { seq: 4 });
+
+        let mut events = l.events().to_vec();
+        events.remove(3);
+        assert_eq!(replay(&events).unwrap_err(), AmmError::InvalidEvent { seq: 3 });
+
+        assert_eq!(replay(&l.events()[1..]).unwrap_err(), AmmError::InvalidEvent { seq: 0 });
+        let mut events = l.events().to_vec();
+        events[5].seq = 9;
+        assert_eq!(replay(&events).unwrap_err(), AmmError::InvalidEvent { seq: 5 });
+        assert_eq!(replay(&[]).unwrap_err(), AmmError::InvalidEvent { seq: 0 });
+    }
+}
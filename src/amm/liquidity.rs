@@ -12,7 +12,7 @@ use super::guardrails::{
 use super::types::{U256, Wad, MIN_RESERVE};
 
 #[inline]
-fn isqrt_u256(n: U256) -> U256 {
+pub(crate) fn isqrt_u256(n: U256) -> U256 {
     if n.is_zero() { return U256::from(0u8); }
     let mut low = U256::from(0u8);
     let mut high = n;
@@ -40,7 +40,7 @@ pub fn add_liquidity(x: Wad, y: Wad, dx: Wad, dy: Wad, total_shares: Wad) -> Res
     ensure_reserves(x, y)?;
     ensure_nonzero(dx)?;
     ensure_nonzero(dy)?;
-    if total_shares == 0 { return Err(AmmError::Overflow); }
+    if total_shares == 0 { return Err(AmmError::EmptyPool); }
 
     let s = U256::from(total_shares);
     let sx = (U256::from(dx) * s) / U256::from(x); // floor
@@ -61,8 +61,10 @@ pub fn add_liquidity(x: Wad, y: Wad, dx: Wad, dy: Wad, total_shares: Wad) -> Res
 pub fn remove_liquidity(x: Wad, y: Wad, burn_shares: Wad, total_shares: Wad) -> Result<(Wad, Wad), AmmError> {
     ensure_reserves(x, y)?;
     ensure_nonzero(burn_shares)?;
-    if total_shares == 0 { return Err(AmmError::Overflow); }
-    if burn_shares > total_shares { return Err(AmmError::Overflow); }
+    if total_shares == 0 { return Err(AmmError::EmptyPool); }
+    if burn_shares > total_shares {
+        return Err(AmmError::InsufficientShares { requested: burn_shares, available: total_shares });
+    }
 
     let bx = (U256::from(x) * U256::from(burn_shares)) / U256::from(total_shares);
     let by = (U256::from(y) * U256::from(burn_shares)) / U256::from(total_shares);
@@ -139,7 +141,7 @@ mod tests {
     fn t_remove_liquidity_burn_too_big() {
         let (x, y, s) = (2_000_000u128*WAD, 2_000_000u128*WAD, 1_000_000u128*WAD);
         let err = remove_liquidity(x, y, s + 1, s).unwrap_err();
-        assert_eq!(err, AmmError::Overflow);
+        assert_eq!(err, AmmError::InsufficientShares { requested: s + 1, available: s });
     }
 
     #[test]
@@ -154,4 +156,11 @@ mod tests {
         let err = remove_liquidity(x, y, 999_999u128*WAD, s).unwrap_err();
         assert_eq!(err, AmmError::MinReserveBreached);
     }
+
+    #[test]
+    fn t_empty_pool_rejected() {
+        let (x, y) = (1_000_000u128*WAD, 1_000_000u128*WAD);
+        assert_eq!(add_liquidity(x, y, WAD, WAD, 0).unwrap_err(), AmmError::EmptyPool);
+        assert_eq!(remove_liquidity(x, y, WAD, 0).unwrap_err(), AmmError::EmptyPool);
+    }
 }
@@ -0,0 +1,254 @@
+//! Market maker LMSR (logarithmic market scoring rule) para mercados de previsão com N outcomes.
+//! Custo `C(q) = b·ln(Σ e^(q_i/b))`, onde `q_i` são as shares de cada outcome já vendidas pelo maker
+//! e `b` o parâmetro de liquidez (em WAD, mesma unidade do colateral).
+//! - preço do outcome i: `p_i = e^(q_i/b) / Σ e^(q_j/b)`, em WAD, somando exatamente `WAD`;
+//! - perda máxima do maker (subsídio): `C(0) = b·ln N`, independente do estado.
+//!
+//! `C` é calculado em escala 1e36 via `fixed_math` (forma estável: `m + b·ln Σ e^((q_i - m)/b)`,
+//! `m = max q_i`), com erro ≤ `b·3e-33`. Política (ADR-0001), sempre a favor do maker:
+//! - compra (colateral in): `cost_up(q') - cost_down(q)`, com folga `b·1e-32 + 1 wei` em cada lado;
+//! - venda (colateral out): `cost_down(q) - cost_up(q')`;
+//! - preços: floor e o resto distribuído pelos maiores restos (soma exata, determinística);
+//! - `worst_case_loss`: por cima.
+
+use super::errors::AmmError;
+use super::fixed_math::{exp_e36, ln_scaled_e36, E36};
+use super::guardrails::{checked_add, checked_sub, ensure_nonzero, u256_to_u128_checked};
+use super::types::{U256, Wad, WAD};
+
+/// Máximo de outcomes num mercado.
+pub const MAX_OUTCOMES: usize = 64;
+/// Abaixo de `e^-84` o termo some na escala 1e36.
+const MIN_EXPONENT_E36: u128 = 84 * E36;
+
+/// Mercado válido: `b > 0` e `2..=MAX_OUTCOMES` outcomes.
+pub fn ensure_market(b: Wad, outcomes: usize) -> Result<(), AmmError> {
+    if !(2..=MAX_OUTCOMES).contains(&outcomes) { return Err(AmmError::InvalidOutcomeCount { count: outcomes }); }
+    ensure_nonzero(b)
+}
+
+#[inline]
+fn ensure_outcome(outcome: usize, len: usize) -> Result<(), AmmError> {
+    if outcome >= len { Err(AmmError::InvalidAssetIndex { index: outcome, len }) } else { Ok(()) }
+}
+
+/// Termos `e^((q_i - m)/b)·1e36` e a soma (≥ 1e36, o termo do máximo vale 1).
+fn terms_e36(b: Wad, q: &[Wad]) -> Result<(Wad, Vec<U256>, U256), AmmError> {
+    ensure_market(b, q.len())?;
+    let m = q.iter().copied().max().unwrap_or(0);
+    let mut sum = U256::zero();
+    let mut terms = Vec::with_capacity(q.len());
+    for &qi in q {
+        let d = U256::from(m - qi) * U256::from(E36) / U256::from(b);
+        let t = if d > U256::from(MIN_EXPONENT_E36) { U256::zero() } else { exp_e36(-(d.as_u128() as i128))? };
+        sum += t;
+        terms.push(t);
+    }
+    Ok((m, terms, sum))
+}
+
+/// `C(q)·1e36` (estimativa central).
+fn cost_e36(b: Wad, q: &[Wad]) -> Result<U256, AmmError> {
+    let (m, _, sum) = terms_e36(b, q)?;
+    let l = ln_scaled_e36(sum, U256::from(E36))?; // ≥ 0: sum ≥ 1e36
+    Ok(U256::from(m) * U256::from(E36) + U256::from(b) * U256::from(l as u128))
+}
+
+/// Folga que cobre o erro de `C`: `b·1e-32 + 1 wei`.
+#[inline]
+fn cost_slack(b: Wad) -> Wad { b / 100_000_000_000_000_000_000_000_000_000_000 + 1 }
+
+/// Cota superior de `C(q)` em WAD.
+pub fn cost_up(b: Wad, q: &[Wad]) -> Result<Wad, AmmError> {
+    let c = cost_e36(b, q)?;
+    let d = U256::from(E36);
+    checked_add(u256_to_u128_checked((c + d - U256::from(1u8)) / d)?, cost_slack(b))
+}
+
+/// Cota inferior de `C(q)` em WAD.
+pub fn cost_down(b: Wad, q: &[Wad]) -> Result<Wad, AmmError> {
+    let c = cost_e36(b, q)?;
+    Ok(u256_to_u128_checked(c / U256::from(E36))?.saturating_sub(cost_slack(b)))
+}
+
+/// Preços dos outcomes em WAD (somam exatamente `WAD`).
+pub fn prices(b: Wad, q: &[Wad]) -> Result<Vec<Wad>, AmmError> {
+    let (_, terms, sum) = terms_e36(b, q)?;
+    let wad = U256::from(WAD);
+    let mut out = Vec::with_capacity(q.len());
+    let mut rems = Vec::with_capacity(q.len());
+    for (i, t) in terms.iter().enumerate() {
+        let n = *t * wad;
+        out.push((n / sum).as_u128());
+        rems.push((n % sum, i));
+    }
+    // maiores restos primeiro; empate pelo menor índice
+    let missing = WAD - out.iter().sum::<Wad>();
+    rems.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, i) in rems.iter().take(missing as usize) { out[i] += 1; }
+    Ok(out)
+}
+
+/// Colateral a pagar para comprar `shares` do `outcome` (ceil + folga).
+pub fn quote_buy(b: Wad, q: &[Wad], outcome: usize, shares: Wad) -> Result<Wad, AmmError> {
+    ensure_outcome(outcome, q.len())?;
+    ensure_nonzero(shares)?;
+    let mut q1 = q.to_vec();
+    q1[outcome] = checked_add(q1[outcome], shares)?;
+    checked_sub(cost_up(b, &q1)?, cost_down(b, q)?)
+}
+
+/// Colateral recebido ao vender `shares` do `outcome` de volta ao maker (floor - folga).
+pub fn quote_sell(b: Wad, q: &[Wad], outcome: usize, shares: Wad) -> Result<Wad, AmmError> {
+    ensure_outcome(outcome, q.len())?;
+    ensure_nonzero(shares)?;
+    if shares > q[outcome] {
+        return Err(AmmError::InsufficientShares { requested: shares, available: q[outcome] });
+    }
+    let mut q1 = q.to_vec();
+    q1[outcome] -= shares;
+    let proceeds = cost_down(b, q)?.saturating_sub(cost_up(b, &q1)?);
+    if proceeds == 0 { return Err(AmmError::InputTooSmall); }
+    Ok(proceeds)
+}
+
+/// Perda máxima do maker, `b·ln N` (por cima).
+pub fn worst_case_loss(b: Wad, outcomes: usize) -> Result<Wad, AmmError> {
+    ensure_market(b, outcomes)?;
+    cost_up(b, &vec![0; outcomes])
+}
+
+/// Resultado de uma compra/venda no LMSR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LmsrTrade {
+    pub outcome: usize,
+    pub shares: Wad,
+    /// Colateral pago (compra) ou recebido (venda)
+    pub collateral: Wad,
+}
+
+/// Mercado LMSR com estado: shares vendidas por outcome e colateral líquido recebido.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lmsr {
+    b: Wad,
+    quantities: Vec<Wad>,
+    collected: Wad,
+}
+
+impl Lmsr {
+    /// Mercado novo com `outcomes` outcomes e nenhuma share vendida (preços uniformes).
+    pub fn new(b: Wad, outcomes: usize) -> Result<Self, AmmError> {
+        ensure_market(b, outcomes)?;
+        Ok(Self { b, quantities: vec![0; outcomes], collected: 0 })
+    }
+
+    /// Reconstrói o mercado a partir de estado já existente (ex.: carregado de storage).
+    pub fn from_state(b: Wad, quantities: Vec<Wad>, collected: Wad) -> Result<Self, AmmError> {
+        ensure_market(b, quantities.len())?;
+        Ok(Self { b, quantities, collected })
+    }
+
+    pub fn b(&self) -> Wad { self.b }
+    pub fn quantities(&self) -> &[Wad] { &self.quantities }
+    /// Colateral líquido recebido pelo maker (compras - vendas).
+    pub fn collected(&self) -> Wad { self.collected }
+
+    pub fn prices(&self) -> Result<Vec<Wad>, AmmError> { prices(self.b, &self.quantities) }
+    pub fn quote_buy(&self, outcome: usize, shares: Wad) -> Result<Wad, AmmError> { quote_buy(self.b, &self.quantities, outcome, shares) }
+    pub fn quote_sell(&self, outcome: usize, shares: Wad) -> Result<Wad, AmmError> { quote_sell(self.b, &self.quantities, outcome, shares) }
+    pub fn worst_case_loss(&self) -> Result<Wad, AmmError> { worst_case_loss(self.b, self.quantities.len()) }
+
+    /// Perda do maker no pior outcome com o estado atual: `max(q_i) - collected` (0 se no lucro).
+    /// Nunca passa de `worst_case_loss`.
+    pub fn exposure(&self) -> Wad {
+        self.quantities.iter().copied().max().unwrap_or(0).saturating_sub(self.collected)
+    }
+
+    /// Compra `shares` do `outcome`; exige custo ≤ `max_cost`.
+    pub fn buy(&mut self, outcome: usize, shares: Wad, max_cost: Wad) -> Result<LmsrTrade, AmmError> {
+        let cost = self.quote_buy(outcome, shares)?;
+        if cost > max_cost { return Err(AmmError::SlippageExceeded { expected: max_cost, actual: cost }); }
+        let collected = checked_add(self.collected, cost)?;
+        self.quantities[outcome] += shares; // checado em quote_buy
+        self.collected = collected;
+        Ok(LmsrTrade { outcome, shares, collateral: cost })
+    }
+
+    /// Vende `shares` do `outcome` de volta; exige recebimento ≥ `min_proceeds`.
+    pub fn sell(&mut self, outcome: usize, shares: Wad, min_proceeds: Wad) -> Result<LmsrTrade, AmmError> {
+        let proceeds = self.quote_sell(outcome, shares)?;
+        if proceeds < min_proceeds { return Err(AmmError::SlippageExceeded { expected: min_proceeds, actual: proceeds }); }
+        let collected = checked_sub(self.collected, proceeds)?;
+        self.quantities[outcome] -= shares;
+        self.collected = collected;
+        Ok(LmsrTrade { outcome, shares, collateral: proceeds })
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const B: Wad = 1_000u128 * WAD;
+
+    #[test]
+    fn t_initial_prices_uniform_and_loss_b_ln_n() {
+        let m = Lmsr::new(B, 2).unwrap();
+        assert_eq!(m.prices().unwrap(), vec![WAD / 2, WAD / 2]);
+        // b·ln 2 = 693.147180559945309417…
+        let loss = m.worst_case_loss().unwrap();
+        assert!(loss >= 693_147_180_559_945_309_417 && loss - 693_147_180_559_945_309_417 <= 2, "loss={}", loss);
+        let p3 = Lmsr::new(B, 3).unwrap().prices().unwrap();
+        assert_eq!(p3.iter().sum::<Wad>(), WAD);
+        assert_eq!(p3, vec![WAD / 3 + 1, WAD / 3, WAD / 3]);
+    }
+
+    #[test]
+    fn t_buy_moves_price_and_costs_between_bounds() {
+        let mut m = Lmsr::new(B, 2).unwrap();
+        let t = m.buy(0, 100u128 * WAD, u128::MAX).unwrap();
+        // custo entre o preço inicial (0.5) e o final por share
+        let p = m.prices().unwrap();
+        assert!(p[0] > WAD / 2 && p[0] + p[1] == WAD);
+        assert!(t.collateral > 50u128 * WAD && t.collateral < 100u128 * p[0]);
+        assert_eq!(m.quantities(), &[100u128 * WAD, 0]);
+        assert_eq!(m.collected(), t.collateral);
+    }
+
+    #[test]
+    fn t_round_trip_never_profits_trader() {
+        let mut m = Lmsr::new(B, 4).unwrap();
+        let paid = m.buy(2, 250u128 * WAD, u128::MAX).unwrap().collateral;
+        let got = m.sell(2, 250u128 * WAD, 0).unwrap().collateral;
+        // spread máximo: 2 folgas + 1 arredondamento por lado
+        assert!(got < paid && paid - got <= 6, "paid={} got={}", paid, got);
+        assert_eq!(m.quantities(), &[0, 0, 0, 0]);
+        assert!(m.exposure() <= m.worst_case_loss().unwrap());
+    }
+
+    #[test]
+    fn t_extreme_state_prices_saturate() {
+        let m = Lmsr::from_state(WAD, vec![1_000u128 * WAD, 0, 0], 0).unwrap();
+        assert_eq!(m.prices().unwrap(), vec![WAD, 0, 0]);
+        // comprar mais do favorito custa ~1 por share
+        let c = m.quote_buy(0, WAD).unwrap();
+        assert!(c >= WAD && c - WAD <= 2, "c={}", c);
+    }
+
+    #[test]
+    fn t_guards() {
+        assert_eq!(Lmsr::new(B, 1).unwrap_err(), AmmError::InvalidOutcomeCount { count: 1 });
+        assert_eq!(Lmsr::new(B, MAX_OUTCOMES + 1).unwrap_err(), AmmError::InvalidOutcomeCount { count: MAX_OUTCOMES + 1 });
+        assert_eq!(Lmsr::new(0, 2).unwrap_err(), AmmError::ZeroAmount);
+        let mut m = Lmsr::new(B, 2).unwrap();
+        let before = m.clone();
+        assert_eq!(m.buy(2, WAD, u128::MAX).unwrap_err(), AmmError::InvalidAssetIndex { index: 2, len: 2 });
+        assert_eq!(m.buy(0, 0, u128::MAX).unwrap_err(), AmmError::ZeroAmount);
+        assert!(matches!(m.buy(0, WAD, 1), Err(AmmError::SlippageExceeded { .. })));
+        assert_eq!(m.sell(0, WAD, 0).unwrap_err(), AmmError::InsufficientShares { requested: WAD, available: 0 });
+        assert_eq!(m, before);
+    }
+}
@@ -4,4 +4,21 @@ pub mod guardrails;    // CRD-7-03
 pub mod swap;          // CRD-7-04
 pub mod liquidity;     // CRD-7-05
 pub mod pricing;       // CRD-7-06
+pub mod fees;          // split LP/protocolo
+pub mod pool;          // Pool com estado
+pub mod oracle;        // TWAP
+pub mod stableswap;    // curva StableSwap
+pub mod fixed_math;    // ln/exp/pow em ponto fixo
+pub mod weighted;      // pools com pesos (Balancer)
+pub mod lmsr;          // market maker LMSR (outcomes)
+pub mod outcome;       // outcome tokens (FPMM binário)
+pub mod settlement;    // ciclo de vida e liquidação de mercados
+pub mod clmm_math;     // ticks e preço raiz Q64.96 (liquidez concentrada)
+pub mod concentrated;  // pool de liquidez concentrada (ticks)
+pub mod router;        // roteamento multi-hop
+pub mod split;         // split de ordens entre rotas
+pub mod batch;         // leilão em lote a preço uniforme
+pub mod ledger;        // log de eventos do pool + replay
+pub mod positions;     // saldos de shares de LP por dono
+pub mod analytics;     // IL, taxas, PnL e APR de LPs
 pub mod ref_bigdecimal; // CRD-7-08 (só testes)
@@ -0,0 +1,204 @@
+//! Oráculo TWAP (preço médio ponderado no tempo) para o CPMM.
+//! - acumuladores `cum += spot * dt` em U256 com aritmética **modular** (wraparound seguro:
+//!   só diferenças entre acumuladores importam, e `wrapping_sub` as recupera exatamente);
+//! - ring buffer de observações, uma por mudança de estado (timestamp fornecido pelo chamador);
+//! - `consult(dir, now, window)`: TWAP em WAD com divisão nearest (ties-to-even), ADR-0001.
+//!
+//! Uso: após cada operação que altera as reservas do pool, chame `update(now, reservas_novas)`.
+//! O preço novo passa a valer a partir de `now`; o intervalo anterior acumula o preço antigo.
+
+use super::errors::AmmError;
+use super::guardrails::div_nearest_even_u256_to_u128;
+use super::pricing::{spot_price_x_in_y, spot_price_y_in_x};
+use super::types::{Direction, Reserves, U256, Wad};
+
+/// Observação: acumuladores no instante `timestamp` e o spot que vale a partir dele.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Observation {
+    pub timestamp: u64,
+    pub cum_x_in_y: U256,
+    pub cum_y_in_x: U256,
+    pub price_x_in_y: Wad,
+    pub price_y_in_x: Wad,
+}
+
+impl Observation {
+    #[inline]
+    fn price(&self, dir: Direction) -> Wad {
+        match dir { Direction::XtoY => self.price_x_in_y, Direction::YtoX => self.price_y_in_x }
+    }
+
+    #[inline]
+    fn cum(&self, dir: Direction) -> U256 {
+        match dir { Direction::XtoY => self.cum_x_in_y, Direction::YtoX => self.cum_y_in_x }
+    }
+
+    /// Acumulador extrapolado para `t >= timestamp` com o preço vigente (modular).
+    #[inline]
+    fn cum_at(&self, dir: Direction, t: u64) -> U256 {
+        let dt = U256::from(t - self.timestamp);
+        self.cum(dir).overflowing_add(U256::from(self.price(dir)) * dt).0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Oracle {
+    ring: Vec<Observation>,
+    capacity: usize,
+    /// posição da observação mais recente em `ring`
+    head: usize,
+}
+
+impl Oracle {
+    /// Cria o oráculo com a primeira observação em `now`. `capacity` ≥ 1 observações.
+    pub fn new(capacity: usize, now: u64, reserves: Reserves) -> Result<Self, AmmError> {
+        if capacity == 0 { return Err(AmmError::InvalidCapacity); }
+        let first = Observation {
+            timestamp: now,
+            cum_x_in_y: U256::zero(),
+            cum_y_in_x: U256::zero(),
+            price_x_in_y: spot_price_x_in_y(reserves.x, reserves.y)?,
+            price_y_in_x: spot_price_y_in_x(reserves.x, reserves.y)?,
+        };
+        let mut ring = Vec::with_capacity(capacity);
+        ring.push(first);
+        Ok(Self { ring, capacity, head: 0 })
+    }
+
+    /// Observação mais recente.
+    pub fn latest(&self) -> Observation { self.ring[self.head] }
+
+    /// Observações retidas, da mais antiga para a mais recente.
+    pub fn observations(&self) -> Vec<Observation> {
+        let n = self.ring.len();
+        (1..=n).map(|i| self.ring[(self.head + i) % n]).collect()
+    }
+
+    /// Registra uma mudança de estado em `now` com as reservas **após** a operação.
+    /// Várias mudanças no mesmo timestamp só atualizam o preço vigente (dt = 0).
+    pub fn update(&mut self, now: u64, reserves: Reserves) -> Result<(), AmmError> {
+        let last = self.latest();
+        if now < last.timestamp { return Err(AmmError::TimestampRegression { last: last.timestamp, now }); }
+        let next = Observation {
+            timestamp: now,
+            cum_x_in_y: last.cum_at(Direction::XtoY, now),
+            cum_y_in_x: last.cum_at(Direction::YtoX, now),
+            price_x_in_y: spot_price_x_in_y(reserves.x, reserves.y)?,
+            price_y_in_x: spot_price_y_in_x(reserves.x, reserves.y)?,
+        };
+        if now == last.timestamp {
+            self.ring[self.head] = next;
+        } else if self.ring.len() < self.capacity {
+            self.ring.push(next);
+            self.head = self.ring.len() - 1;
+        } else {
+            self.head = (self.head + 1) % self.capacity;
+            self.ring[self.head] = next;
+        }
+        Ok(())
+    }
+
+    /// TWAP de 1 unidade do ativo de entrada de `dir` (em WAD) sobre `[now - window, now]`.
+    /// `window = 0` devolve o spot vigente.
+    pub fn consult(&self, dir: Direction, now: u64, window: u64) -> Result<Wad, AmmError> {
+        let latest = self.latest();
+        if now < latest.timestamp { return Err(AmmError::TimestampRegression { last: latest.timestamp, now }); }
+        if window == 0 { return Ok(latest.price(dir)); }
+
+        let observations = self.observations();
+        let oldest = observations[0].timestamp;
+        let unavailable = AmmError::OracleWindowUnavailable { window, available: now - oldest };
+        let target = now.checked_sub(window).ok_or(unavailable.clone())?;
+        // observação mais recente com timestamp ≤ alvo: seu preço vale até a próxima
+        let base = observations.iter().rev().find(|o| o.timestamp <= target).ok_or(unavailable)?;
+
+        let cum_now = latest.cum_at(dir, now);
+        let cum_target = base.cum_at(dir, target);
+        let delta = cum_now.overflowing_sub(cum_target).0;
+        div_nearest_even_u256_to_u128(delta, U256::from(window))
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::pool::Pool;
+    use crate::amm::types::WAD;
+
+    fn r(x: u128, y: u128) -> Reserves { Reserves::new(x * WAD, y * WAD) }
+
+    #[test]
+    fn t_constant_price_twap_equals_spot() {
+        let mut o = Oracle::new(8, 1_000, r(1_000, 2_000)).unwrap();
+        o.update(1_060, r(1_000, 2_000)).unwrap();
+        assert_eq!(o.consult(Direction::XtoY, 1_100, 100).unwrap(), 2 * WAD);
+        assert_eq!(o.consult(Direction::YtoX, 1_100, 50).unwrap(), WAD / 2);
+    }
+
+    #[test]
+    fn t_step_change_time_weighted() {
+        // p=2 por 10s, depois p=4 por 30s ⇒ TWAP(40s) = (2*10 + 4*30)/40 = 3.5
+        let mut o = Oracle::new(8, 0, r(1_000, 2_000)).unwrap();
+        o.update(10, r(1_000, 4_000)).unwrap();
+        assert_eq!(o.consult(Direction::XtoY, 40, 40).unwrap(), 3 * WAD + WAD / 2);
+        // janela que começa no meio do primeiro intervalo: (2*5 + 4*30)/35
+        let expected = (2 * 5 + 4 * 30) * WAD / 35;
+        assert!(o.consult(Direction::XtoY, 40, 35).unwrap().abs_diff(expected) <= 1);
+        assert_eq!(o.consult(Direction::XtoY, 40, 0).unwrap(), 4 * WAD);
+    }
+
+    #[test]
+    fn t_same_timestamp_only_updates_price() {
+        let mut o = Oracle::new(4, 0, r(1_000, 1_000)).unwrap();
+        o.update(5, r(1_000, 3_000)).unwrap();
+        o.update(5, r(1_000, 2_000)).unwrap(); // segunda mudança no mesmo bloco
+        assert_eq!(o.observations().len(), 2);
+        assert_eq!(o.consult(Direction::XtoY, 10, 10).unwrap(), (WAD + 2 * WAD) / 2);
+    }
+
+    #[test]
+    fn t_ring_buffer_evicts_oldest() {
+        let mut o = Oracle::new(3, 0, r(1_000, 1_000)).unwrap();
+        for t in 1..=5 { o.update(t * 10, r(1_000, 1_000)).unwrap(); }
+        let obs = o.observations();
+        assert_eq!(obs.iter().map(|o| o.timestamp).collect::<Vec<_>>(), vec![30, 40, 50]);
+        assert_eq!(o.consult(Direction::XtoY, 50, 20).unwrap(), WAD);
+        let err = o.consult(Direction::XtoY, 50, 21).unwrap_err();
+        assert_eq!(err, AmmError::OracleWindowUnavailable { window: 21, available: 20 });
+    }
+
+    #[test]
+    fn t_timestamp_regression_rejected() {
+        let mut o = Oracle::new(2, 100, r(1_000, 1_000)).unwrap();
+        let before = o.clone();
+        assert_eq!(o.update(99, r(1_000, 1_000)).unwrap_err(), AmmError::TimestampRegression { last: 100, now: 99 });
+        assert_eq!(o, before);
+        assert_eq!(Oracle::new(0, 100, r(1_000, 1_000)).unwrap_err(), AmmError::InvalidCapacity);
+    }
+
+    #[test]
+    fn t_wraparound_is_transparent() {
+        let mut o = Oracle::new(4, 0, r(1_000, 3_000)).unwrap();
+        // acumulador logo abaixo de 2^256: a próxima soma dá a volta
+        o.ring[0].cum_x_in_y = U256::MAX - U256::from(5u8);
+        o.update(100, r(1_000, 3_000)).unwrap();
+        assert!(o.latest().cum_x_in_y < U256::from(3 * WAD * 100));
+        assert_eq!(o.consult(Direction::XtoY, 100, 100).unwrap(), 3 * WAD);
+    }
+
+    #[test]
+    fn t_tracks_pool_state_changes() {
+        let mut pool = Pool::new(1_000_000u128 * WAD, 1_000_000u128 * WAD, 3000).unwrap();
+        let mut o = Oracle::new(16, 0, pool.reserves()).unwrap();
+        // manipulação de um único bloco quase não move o TWAP longo
+        pool.swap_exact_in(Direction::XtoY, 500_000u128 * WAD, 0).unwrap();
+        o.update(3_600, pool.reserves()).unwrap();
+        pool.swap_exact_in(Direction::YtoX, 330_000u128 * WAD, 0).unwrap();
+        o.update(3_601, pool.reserves()).unwrap();
+        let twap = o.consult(Direction::XtoY, 3_601, 3_601).unwrap();
+        assert!(twap.abs_diff(WAD) < WAD / 1_000, "twap={}", twap);
+    }
+}
@@ -0,0 +1,310 @@
+//! Mercado binário de outcome tokens (FPMM): pool CPMM de YES (reserva X) / NO (reserva Y)
+//! sobre `Pool`, mais o colateral que lastreia os tokens.
+//! - `mint_sets`: 1 de colateral ⇒ 1 YES + 1 NO (complete set); `burn_sets` desfaz;
+//! - `buy(YES, c)`: minta `c` sets e troca os `c` NO por YES no pool (trader fica com `c + out` YES);
+//! - `sell(YES, r)`: troca YES por `r` NO no pool e queima `r` sets, liberando `r` de colateral;
+//! - após `resolve`, cada token vencedor resgata 1 de colateral; perdedores valem 0.
+//!
+//! Invariante (conservação): para cada outcome, `em mãos de traders + reserva do pool = colateral`.
+//! Logo o colateral sempre cobre os tokens vencedores em circulação. Arredondamentos vêm do `swap`
+//! (out floor, in ceil), sempre a favor do pool.
+
+use super::errors::AmmError;
+use super::guardrails::{checked_add, checked_sub, ensure_nonzero, div_nearest_even_u256_to_u128};
+use super::pool::{LiquidityReceipt, Pool, SwapReceipt};
+use super::types::{Direction, Ppm, Reserves, U256, Wad, WAD};
+
+/// Outcome de um mercado binário.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome { Yes, No }
+impl Outcome {
+    /// Outcome oposto.
+    #[inline]
+    pub fn other(self) -> Self {
+        match self { Outcome::Yes => Outcome::No, Outcome::No => Outcome::Yes }
+    }
+    /// Sentido do swap que entrega este outcome (YES = X, NO = Y).
+    #[inline]
+    fn buy_direction(self) -> Direction {
+        match self { Outcome::Yes => Direction::YtoX, Outcome::No => Direction::XtoY }
+    }
+}
+
+/// Resultado de uma compra/venda de outcome tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutcomeTrade {
+    pub outcome: Outcome,
+    /// Colateral pago (compra) ou liberado (venda)
+    pub collateral: Wad,
+    /// Tokens do outcome recebidos (compra) ou entregues (venda)
+    pub tokens: Wad,
+    /// Perna de swap no pool
+    pub swap: SwapReceipt,
+}
+
+/// Resultado de `add_funding`: liquidez adicionada e sobra de tokens devolvida ao LP.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FundingReceipt {
+    pub liquidity: LiquidityReceipt,
+    /// Sobra de YES (sets mintados − YES que entrou no pool)
+    pub leftover_yes: Wad,
+    /// Sobra de NO (sets mintados − NO que entrou no pool)
+    pub leftover_no: Wad,
+}
+
+impl FundingReceipt {
+    /// Sobra do outcome.
+    pub fn leftover(&self, outcome: Outcome) -> Wad {
+        match outcome { Outcome::Yes => self.leftover_yes, Outcome::No => self.leftover_no }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutcomeMarket {
+    pool: Pool,
+    collateral: Wad,
+    held_yes: Wad,
+    held_no: Wad,
+    resolved: Option<Outcome>,
+}
+
+impl OutcomeMarket {
+    /// Abre o mercado com `funding` de colateral: minta `funding` sets e deposita tudo no pool (50/50).
+    pub fn new(funding: Wad, fee_ppm: Ppm) -> Result<Self, AmmError> {
+        let pool = Pool::new(funding, funding, fee_ppm)?;
+        Ok(Self { pool, collateral: funding, held_yes: 0, held_no: 0, resolved: None })
+    }
+
+    pub fn pool(&self) -> &Pool { &self.pool }
+    /// Colateral total travado (= sets em circulação).
+    pub fn collateral(&self) -> Wad { self.collateral }
+    /// Tokens do outcome fora do pool (em mãos de traders).
+    pub fn held(&self, outcome: Outcome) -> Wad {
+        match outcome { Outcome::Yes => self.held_yes, Outcome::No => self.held_no }
+    }
+    pub fn resolved(&self) -> Option<Outcome> { self.resolved }
+
+    #[inline]
+    fn held_mut(&mut self, outcome: Outcome) -> &mut Wad {
+        match outcome { Outcome::Yes => &mut self.held_yes, Outcome::No => &mut self.held_no }
+    }
+
+    #[inline]
+    fn ensure_open(&self) -> Result<(), AmmError> {
+        if self.resolved.is_some() { Err(AmmError::MarketResolved) } else { Ok(()) }
+    }
+
+    /// Preço (probabilidade implícita) de cada outcome em WAD: `P(YES) = no / (yes + no)`; somam `WAD`.
+    pub fn price(&self, outcome: Outcome) -> Result<Wad, AmmError> {
+        let Reserves { x, y } = self.pool.reserves();
+        let p_yes = div_nearest_even_u256_to_u128(U256::from(y) * U256::from(WAD), U256::from(x) + U256::from(y))?;
+        Ok(match outcome { Outcome::Yes => p_yes, Outcome::No => WAD - p_yes })
+    }
+
+    /// Deposita `amount` de colateral e recebe `amount` YES + `amount` NO.
+    pub fn mint_sets(&mut self, amount: Wad) -> Result<(), AmmError> {
+        self.ensure_open()?;
+        ensure_nonzero(amount)?;
+        let (c, y, n) = (checked_add(self.collateral, amount)?, checked_add(self.held_yes, amount)?, checked_add(self.held_no, amount)?);
+        self.collateral = c;
+        self.held_yes = y;
+        self.held_no = n;
+        Ok(())
+    }
+
+    /// Devolve `amount` YES + `amount` NO e recebe `amount` de colateral.
+    pub fn burn_sets(&mut self, amount: Wad) -> Result<(), AmmError> {
+        ensure_nonzero(amount)?;
+        let available = self.held_yes.min(self.held_no);
+        if amount > available { return Err(AmmError::InsufficientShares { requested: amount, available }); }
+        self.collateral -= amount;
+        self.held_yes -= amount;
+        self.held_no -= amount;
+        Ok(())
+    }
+
+    /// Adiciona `amount` de colateral como liquidez: minta sets e oferece tudo ao pool, que só aceita
+    /// a parte na proporção das reservas; a sobra de cada lado fica com o LP como tokens em mãos.
+    pub fn add_funding(&mut self, amount: Wad) -> Result<FundingReceipt, AmmError> {
+        self.ensure_open()?;
+        ensure_nonzero(amount)?;
+        let total = checked_add(self.collateral, amount)?;
+        let mut pool = self.pool.clone();
+        let liquidity = pool.add_liquidity(amount, amount)?;
+        let (leftover_yes, leftover_no) = (amount - liquidity.amount_x, amount - liquidity.amount_y);
+        let (held_yes, held_no) = (checked_add(self.held_yes, leftover_yes)?, checked_add(self.held_no, leftover_no)?);
+        self.pool = pool;
+        self.collateral = total;
+        self.held_yes = held_yes;
+        self.held_no = held_no;
+        Ok(FundingReceipt { liquidity, leftover_yes, leftover_no })
+    }
+
+    /// Compra `outcome` com `collateral`: minta sets e troca o outro lado no pool; exige tokens ≥ `min_tokens`.
+    pub fn buy(&mut self, outcome: Outcome, collateral: Wad, min_tokens: Wad) -> Result<OutcomeTrade, AmmError> {
+        self.ensure_open()?;
+        ensure_nonzero(collateral)?;
+        let mut pool = self.pool.clone();
+        let swap = pool.swap_exact_in(outcome.buy_direction(), collateral, 0)?;
+        let tokens = checked_add(collateral, swap.amount_out)?;
+        if tokens < min_tokens { return Err(AmmError::SlippageExceeded { expected: min_tokens, actual: tokens }); }
+        let total = checked_add(self.collateral, collateral)?;
+        let held = checked_add(self.held(outcome), tokens)?;
+        self.pool = pool;
+        self.collateral = total;
+        *self.held_mut(outcome) = held;
+        Ok(OutcomeTrade { outcome, collateral, tokens, swap })
+    }
+
+    /// Vende `outcome` para liberar exatamente `collateral`: troca tokens pelo outro lado no pool e
+    /// queima os sets; exige tokens entregues ≤ `max_tokens`.
+    pub fn sell(&mut self, outcome: Outcome, collateral: Wad, max_tokens: Wad) -> Result<OutcomeTrade, AmmError> {
+        self.ensure_open()?;
+        ensure_nonzero(collateral)?;
+        let mut pool = self.pool.clone();
+        let swap = pool.swap_exact_out(outcome.buy_direction().reverse(), collateral, u128::MAX)?;
+        let tokens = checked_add(collateral, swap.amount_in)?;
+        if tokens > max_tokens { return Err(AmmError::SlippageExceeded { expected: max_tokens, actual: tokens }); }
+        let available = self.held(outcome);
+        if tokens > available { return Err(AmmError::InsufficientShares { requested: tokens, available }); }
+        let total = checked_sub(self.collateral, collateral)?;
+        self.pool = pool;
+        self.collateral = total;
+        *self.held_mut(outcome) -= tokens;
+        Ok(OutcomeTrade { outcome, collateral, tokens, swap })
+    }
+
+    /// Compra YES (ver `buy`).
+    pub fn buy_yes(&mut self, collateral: Wad, min_tokens: Wad) -> Result<OutcomeTrade, AmmError> {
+        self.buy(Outcome::Yes, collateral, min_tokens)
+    }
+
+    /// Vende YES (ver `sell`).
+    pub fn sell_yes(&mut self, collateral: Wad, max_tokens: Wad) -> Result<OutcomeTrade, AmmError> {
+        self.sell(Outcome::Yes, collateral, max_tokens)
+    }
+
+    /// Fixa o outcome vencedor; a partir daqui só resgates.
+    pub fn resolve(&mut self, winner: Outcome) -> Result<(), AmmError> {
+        self.ensure_open()?;
+        self.resolved = Some(winner);
+        Ok(())
+    }
+
+    /// Resgata `amount` tokens vencedores por `amount` de colateral.
+    pub fn redeem(&mut self, amount: Wad) -> Result<Wad, AmmError> {
+        let winner = self.resolved.ok_or(AmmError::MarketNotResolved)?;
+        ensure_nonzero(amount)?;
+        let available = self.held(winner);
+        if amount > available { return Err(AmmError::InsufficientShares { requested: amount, available }); }
+        self.collateral -= amount;
+        *self.held_mut(winner) -= amount;
+        Ok(amount)
+    }
+
+    /// Conservação: para cada outcome, tokens em mãos + reserva do pool = colateral.
+    pub fn is_balanced(&self) -> bool {
+        let Reserves { x, y } = self.pool.reserves();
+        let (yes, no) = (self.held_yes.checked_add(x), self.held_no.checked_add(y));
+        match self.resolved {
+            // após resolver, só os vencedores precisam de lastro (perdedores não são resgatados)
+            Some(Outcome::Yes) => yes == Some(self.collateral),
+            Some(Outcome::No) => no == Some(self.collateral),
+            None => yes == Some(self.collateral) && no == Some(self.collateral),
+        }
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::swap::get_amount_out_dir;
+
+    const FEE2: Ppm = 20_000; // 2%
+
+    fn market() -> OutcomeMarket { OutcomeMarket::new(10_000u128 * WAD, FEE2).unwrap() }
+
+    #[test]
+    fn t_new_market_is_fifty_fifty() {
+        let m = market();
+        assert_eq!(m.price(Outcome::Yes).unwrap(), WAD / 2);
+        assert_eq!(m.price(Outcome::No).unwrap(), WAD / 2);
+        assert_eq!(m.collateral(), 10_000u128 * WAD);
+        assert!(m.is_balanced());
+    }
+
+    #[test]
+    fn t_buy_yes_mints_and_swaps() {
+        let mut m = market();
+        let c = 1_000u128 * WAD;
+        let out = get_amount_out_dir(Direction::YtoX, 10_000u128 * WAD, 10_000u128 * WAD, c, FEE2).unwrap();
+        let t = m.buy_yes(c, 0).unwrap();
+        assert_eq!(t.tokens, c + out);
+        assert_eq!(m.held(Outcome::Yes), c + out);
+        assert_eq!(m.held(Outcome::No), 0);
+        assert!(m.price(Outcome::Yes).unwrap() > WAD / 2);
+        assert_eq!(m.price(Outcome::Yes).unwrap() + m.price(Outcome::No).unwrap(), WAD);
+        assert!(m.is_balanced());
+    }
+
+    #[test]
+    fn t_sell_yes_round_trip_costs_fees() {
+        let mut m = market();
+        let bought = m.buy_yes(1_000u128 * WAD, 0).unwrap().tokens;
+        let sold = m.sell_yes(900u128 * WAD, u128::MAX).unwrap();
+        assert!(sold.tokens <= bought);
+        assert_eq!(m.held(Outcome::Yes), bought - sold.tokens);
+        assert_eq!(m.collateral(), 10_100u128 * WAD);
+        assert!(m.is_balanced());
+        // vender tudo de volta não libera o colateral inteiro pago (taxa fica no pool)
+        assert!(matches!(m.sell_yes(1_000u128 * WAD, u128::MAX), Err(AmmError::InsufficientShares { .. })));
+    }
+
+    #[test]
+    fn t_resolve_and_redeem() {
+        let mut m = market();
+        let yes = m.buy_yes(2_000u128 * WAD, 0).unwrap().tokens;
+        m.mint_sets(500u128 * WAD).unwrap();
+        assert_eq!(m.redeem(1).unwrap_err(), AmmError::MarketNotResolved);
+        m.resolve(Outcome::Yes).unwrap();
+        assert_eq!(m.buy_yes(WAD, 0).unwrap_err(), AmmError::MarketResolved);
+        assert_eq!(m.resolve(Outcome::No).unwrap_err(), AmmError::MarketResolved);
+        let held = m.held(Outcome::Yes);
+        assert_eq!(held, yes + 500u128 * WAD);
+        assert_eq!(m.redeem(held).unwrap(), held);
+        // o que sobra cobre exatamente os YES do pool
+        assert_eq!(m.collateral(), m.pool().reserves().x);
+        assert!(m.is_balanced());
+    }
+
+    #[test]
+    fn t_guards_and_atomicity() {
+        let mut m = market();
+        let before = m.clone();
+        assert!(matches!(m.buy_yes(WAD, u128::MAX), Err(AmmError::SlippageExceeded { .. })));
+        assert!(matches!(m.sell_yes(WAD, u128::MAX), Err(AmmError::InsufficientShares { .. })));
+        assert!(matches!(m.burn_sets(WAD), Err(AmmError::InsufficientShares { .. })));
+        assert_eq!(m.buy(Outcome::No, 0, 0).unwrap_err(), AmmError::ZeroAmount);
+        assert_eq!(m, before);
+        m.mint_sets(WAD).unwrap();
+        m.burn_sets(WAD).unwrap();
+        assert_eq!(m, before);
+    }
+
+    #[test]
+    fn t_add_funding_keeps_price_and_returns_leftover() {
+        let mut m = market();
+        m.buy_yes(3_000u128 * WAD, 0).unwrap();
+        let p0 = m.price(Outcome::Yes).unwrap();
+        let f = m.add_funding(1_000u128 * WAD).unwrap();
+        // YES ficou escasso no pool ⇒ entra menos YES e a sobra (o lado mais caro) fica com o LP
+        assert_eq!(f.leftover_no, 0);
+        assert!(f.leftover_yes > 0 && f.liquidity.shares > 0);
+        assert_eq!(f.leftover(Outcome::Yes) + f.liquidity.amount_x, 1_000u128 * WAD);
+        assert!(m.price(Outcome::Yes).unwrap().abs_diff(p0) <= 1);
+        assert!(m.is_balanced());
+    }
+}
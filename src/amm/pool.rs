@@ -0,0 +1,370 @@
+//! Pool CPMM com estado: reservas, supply de shares, taxa e política de mínimo.
+//! Cada operação calcula o novo estado inteiro antes de gravar: em erro, o pool
+//! fica **inalterado**. As contas delegam para as funções puras de `swap`/`liquidity`.
+//! A parte da taxa destinada ao protocolo (`fees`) sai das reservas e fica acumulada até `collect`.
+
+use sha2::{Digest, Sha256};
+
+use super::errors::AmmError;
+use super::fees::{ensure_protocol_fee, fee_split_on_input, ProtocolFees};
+use super::guardrails::{checked_add, checked_sub, ensure_fee, ensure_reserves, mul_div_ceil_u256, u256_to_u128_checked};
+use super::liquidity;
+use super::swap::{swap_exact_in_dir, swap_exact_out_dir};
+use super::types::{Direction, Ppm, Reserves, U256, Wad, MIN_RESERVE};
+
+/// Resultado de um swap aplicado ao pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapReceipt {
+    pub direction: Direction,
+    /// Input bruto (inclui a taxa)
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+    /// Parte do input retida como taxa (ceil, ADR-0001) = `lp_fee + protocol_fee`
+    pub fee: Wad,
+    /// Parte da taxa que fica nas reservas (LPs)
+    pub lp_fee: Wad,
+    /// Parte da taxa destinada à tesouraria (floor), fora das reservas
+    pub protocol_fee: Wad,
+    /// `k = x*y` após a operação
+    pub k: U256,
+}
+
+/// Resultado de um add/remove de liquidez aplicado ao pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityReceipt {
+    /// Montantes que entraram (add) ou saíram (remove) das reservas
+    pub amount_x: Wad,
+    pub amount_y: Wad,
+    /// Shares mintados (add) ou queimados (remove)
+    pub shares: Wad,
+    /// `k = x*y` após a operação
+    pub k: U256,
+}
+
+/// Estado completo do pool, campo a campo: `Pool::restore(p.state()) == p`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    pub reserves: Reserves,
+    pub total_shares: Wad,
+    pub fee_ppm: Ppm,
+    pub min_reserve: Wad,
+    pub protocol_fee_ppm: Ppm,
+    pub protocol_fees: ProtocolFees,
+}
+
+impl PoolState {
+    /// sha256 dos campos em ordem de declaração (big-endian).
+    pub fn hash(&self) -> [u8; 32] {
+        let mut h = Sha256::new();
+        for v in [self.reserves.x, self.reserves.y, self.total_shares] { h.update(v.to_be_bytes()); }
+        h.update(self.fee_ppm.to_be_bytes());
+        h.update(self.min_reserve.to_be_bytes());
+        h.update(self.protocol_fee_ppm.to_be_bytes());
+        for v in [self.protocol_fees.x, self.protocol_fees.y] { h.update(v.to_be_bytes()); }
+        h.finalize().into()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pool {
+    reserves: Reserves,
+    total_shares: Wad,
+    fee_ppm: Ppm,
+    min_reserve: Wad,
+    /// Fração da taxa (ppm da taxa) destinada ao protocolo
+    protocol_fee_ppm: Ppm,
+    protocol_fees: ProtocolFees,
+}
+
+impl Pool {
+    /// Cria o pool com o depósito inicial `(x, y)`; o supply inicial é `initial_mint(x, y)`.
+    pub fn new(x: Wad, y: Wad, fee_ppm: Ppm) -> Result<Self, AmmError> {
+        Self::with_min_reserve(x, y, fee_ppm, MIN_RESERVE)
+    }
+
+    /// Como `new`, com um mínimo de reserva mais restritivo (nunca abaixo de `MIN_RESERVE`).
+    pub fn with_min_reserve(x: Wad, y: Wad, fee_ppm: Ppm, min_reserve: Wad) -> Result<Self, AmmError> {
+        let total_shares = liquidity::initial_mint(x, y)?;
+        Self::from_state(Reserves::new(x, y), total_shares, fee_ppm, min_reserve)
+    }
+
+    /// Reconstrói um pool a partir de estado já existente (ex.: carregado de storage).
+    pub fn from_state(reserves: Reserves, total_shares: Wad, fee_ppm: Ppm, min_reserve: Wad) -> Result<Self, AmmError> {
+        ensure_fee(fee_ppm)?;
+        if total_shares == 0 { return Err(AmmError::EmptyPool); }
+        let pool = Self {
+            reserves,
+            total_shares,
+            fee_ppm,
+            min_reserve: min_reserve.max(MIN_RESERVE),
+            protocol_fee_ppm: 0,
+            protocol_fees: ProtocolFees::default(),
+        };
+        pool.check_reserves(reserves.x, reserves.y)?;
+        Ok(pool)
+    }
+
+    /// Reconstrói o pool exatamente como em `state`, inclusive a parte e o acumulado do protocolo.
+    pub fn restore(state: PoolState) -> Result<Self, AmmError> {
+        ensure_protocol_fee(state.protocol_fee_ppm)?;
+        let mut pool = Self::from_state(state.reserves, state.total_shares, state.fee_ppm, state.min_reserve)?;
+        pool.protocol_fee_ppm = state.protocol_fee_ppm;
+        pool.protocol_fees = state.protocol_fees;
+        Ok(pool)
+    }
+
+    pub fn state(&self) -> PoolState {
+        PoolState {
+            reserves: self.reserves,
+            total_shares: self.total_shares,
+            fee_ppm: self.fee_ppm,
+            min_reserve: self.min_reserve,
+            protocol_fee_ppm: self.protocol_fee_ppm,
+            protocol_fees: self.protocol_fees,
+        }
+    }
+
+    pub fn reserves(&self) -> Reserves { self.reserves }
+    pub fn total_shares(&self) -> Wad { self.total_shares }
+    pub fn fee_ppm(&self) -> Ppm { self.fee_ppm }
+    pub fn min_reserve(&self) -> Wad { self.min_reserve }
+    pub fn protocol_fee_ppm(&self) -> Ppm { self.protocol_fee_ppm }
+    /// Taxas do protocolo acumuladas e ainda não coletadas.
+    pub fn protocol_fees(&self) -> ProtocolFees { self.protocol_fees }
+
+    /// Define a taxa de swap (`0..1_000_000` ppm) dos próximos swaps.
+    pub fn set_fee_ppm(&mut self, fee_ppm: Ppm) -> Result<(), AmmError> {
+        ensure_fee(fee_ppm)?;
+        self.fee_ppm = fee_ppm;
+        Ok(())
+    }
+
+    /// Define a fração da taxa (ppm **da taxa**, 0..=1e6) que vai para o protocolo nos próximos swaps.
+    pub fn set_protocol_fee_ppm(&mut self, protocol_fee_ppm: Ppm) -> Result<(), AmmError> {
+        ensure_protocol_fee(protocol_fee_ppm)?;
+        self.protocol_fee_ppm = protocol_fee_ppm;
+        Ok(())
+    }
+
+    /// Resgata as taxas do protocolo acumuladas; retorna `(x, y)` e zera o acumulador.
+    pub fn collect_protocol_fees(&mut self) -> (Wad, Wad) { self.protocol_fees.collect() }
+
+    /// `k = x*y` atual (U256).
+    pub fn k(&self) -> U256 { U256::from(self.reserves.x) * U256::from(self.reserves.y) }
+
+    #[inline]
+    fn check_reserves(&self, x: Wad, y: Wad) -> Result<(), AmmError> {
+        ensure_reserves(x, y)?;
+        if x < self.min_reserve || y < self.min_reserve { return Err(AmmError::MinReserveBreached); }
+        Ok(())
+    }
+
+    /// Aplica `amount_in`/`amount_out` às reservas no sentido `dir` e devolve o novo par (sem gravar).
+    fn reserves_after_swap(&self, dir: Direction, amount_in: Wad, amount_out: Wad) -> Result<Reserves, AmmError> {
+        let Reserves { x, y } = self.reserves;
+        let (x1, y1) = match dir {
+            Direction::XtoY => (checked_add(x, amount_in)?, checked_sub(y, amount_out)?),
+            Direction::YtoX => (checked_sub(x, amount_out)?, checked_add(y, amount_in)?),
+        };
+        self.check_reserves(x1, y1)?;
+        Ok(Reserves::new(x1, y1))
+    }
+
+    fn commit_swap(&mut self, dir: Direction, amount_in: Wad, amount_out: Wad) -> Result<SwapReceipt, AmmError> {
+        let split = fee_split_on_input(amount_in, self.fee_ppm, self.protocol_fee_ppm)?;
+        // a parte do protocolo não entra nas reservas
+        let to_reserves = checked_sub(amount_in, split.protocol_fee)?;
+        let next = self.reserves_after_swap(dir, to_reserves, amount_out)?;
+        let mut fees = self.protocol_fees;
+        fees.accrue(dir, split.protocol_fee)?;
+        self.reserves = next;
+        self.protocol_fees = fees;
+        Ok(SwapReceipt {
+            direction: dir,
+            amount_in,
+            amount_out,
+            fee: split.total(),
+            lp_fee: split.lp_fee,
+            protocol_fee: split.protocol_fee,
+            k: self.k(),
+        })
+    }
+
+    /// Swap com input exato: envia `amount_in` (bruto) no sentido `dir`; exige output ≥ `min_out`.
+    pub fn swap_exact_in(&mut self, dir: Direction, amount_in: Wad, min_out: Wad) -> Result<SwapReceipt, AmmError> {
+        let Reserves { x, y } = self.reserves;
+        let amount_out = swap_exact_in_dir(dir, x, y, amount_in, min_out, self.fee_ppm)?;
+        self.commit_swap(dir, amount_in, amount_out)
+    }
+
+    /// Swap com output exato: recebe `amount_out` pagando o menor input bruto; exige input ≤ `max_in`.
+    pub fn swap_exact_out(&mut self, dir: Direction, amount_out: Wad, max_in: Wad) -> Result<SwapReceipt, AmmError> {
+        let Reserves { x, y } = self.reserves;
+        let amount_in = swap_exact_out_dir(dir, x, y, amount_out, max_in, self.fee_ppm)?;
+        self.commit_swap(dir, amount_in, amount_out)
+    }
+
+    /// Deposita até `(dx, dy)` e minta shares proporcionais (floor). Só entra a parte na razão
+    /// das reservas, `ceil(shares * r / S)` de cada lado (a favor do pool, ≤ o oferecido): o recibo
+    /// traz os montantes usados e a sobra de um depósito desbalanceado fica com o depositante.
+    pub fn add_liquidity(&mut self, dx: Wad, dy: Wad) -> Result<LiquidityReceipt, AmmError> {
+        let Reserves { x, y } = self.reserves;
+        let shares = liquidity::add_liquidity(x, y, dx, dy, self.total_shares)?;
+        let (s, minted) = (U256::from(self.total_shares), U256::from(shares));
+        let amount_x = u256_to_u128_checked(mul_div_ceil_u256(minted, U256::from(x), s)?)?;
+        let amount_y = u256_to_u128_checked(mul_div_ceil_u256(minted, U256::from(y), s)?)?;
+        let (x1, y1) = (checked_add(x, amount_x)?, checked_add(y, amount_y)?);
+        let s1 = checked_add(self.total_shares, shares)?;
+        self.reserves = Reserves::new(x1, y1);
+        self.total_shares = s1;
+        Ok(LiquidityReceipt { amount_x, amount_y, shares, k: self.k() })
+    }
+
+    /// Queima `shares` e devolve a parte proporcional das reservas (floor).
+    pub fn remove_liquidity(&mut self, shares: Wad) -> Result<LiquidityReceipt, AmmError> {
+        let Reserves { x, y } = self.reserves;
+        let (amount_x, amount_y) = liquidity::remove_liquidity(x, y, shares, self.total_shares)?;
+        let (x1, y1) = (checked_sub(x, amount_x)?, checked_sub(y, amount_y)?);
+        self.check_reserves(x1, y1)?;
+        let s1 = checked_sub(self.total_shares, shares)?;
+        self.reserves = Reserves::new(x1, y1);
+        self.total_shares = s1;
+        Ok(LiquidityReceipt { amount_x, amount_y, shares, k: self.k() })
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::swap::get_amount_out;
+    use crate::amm::types::WAD;
+
+    const FEE3: Ppm = 3000; // 0,30%
+
+    fn pool_1m() -> Pool { Pool::new(1_000_000u128 * WAD, 1_000_000u128 * WAD, FEE3).unwrap() }
+
+    #[test]
+    fn t_new_mints_sqrt_xy() {
+        let p = pool_1m();
+        assert_eq!(p.total_shares(), 1_000_000u128 * WAD);
+        assert_eq!(p.reserves(), Reserves::new(1_000_000u128 * WAD, 1_000_000u128 * WAD));
+    }
+
+    #[test]
+    fn t_swap_exact_in_updates_state() {
+        let mut p = pool_1m();
+        let k0 = p.k();
+        let dx = 10_000u128 * WAD;
+        let r = p.swap_exact_in(Direction::XtoY, dx, 0).unwrap();
+        assert_eq!(r.amount_out, get_amount_out(1_000_000u128 * WAD, 1_000_000u128 * WAD, dx, FEE3).unwrap());
+        assert_eq!(r.fee, 30u128 * WAD);
+        assert_eq!(p.reserves(), Reserves::new(1_010_000u128 * WAD, 1_000_000u128 * WAD - r.amount_out));
+        assert_eq!(r.k, p.k());
+        assert!(r.k >= k0);
+    }
+
+    #[test]
+    fn t_swap_exact_out_y_to_x() {
+        let mut p = pool_1m();
+        let r = p.swap_exact_out(Direction::YtoX, 9_870u128 * WAD, Wad::MAX).unwrap();
+        assert_eq!(r.amount_out, 9_870u128 * WAD);
+        assert_eq!(p.reserves().x, 1_000_000u128 * WAD - 9_870u128 * WAD);
+        assert_eq!(p.reserves().y, 1_000_000u128 * WAD + r.amount_in);
+    }
+
+    #[test]
+    fn t_failure_leaves_state_unchanged() {
+        let mut p = pool_1m();
+        let before = p.clone();
+        assert_eq!(p.swap_exact_in(Direction::XtoY, 0, 0).unwrap_err(), AmmError::ZeroAmount);
+        assert_eq!(p.swap_exact_out(Direction::XtoY, 999_999u128 * WAD, Wad::MAX).unwrap_err(), AmmError::MinReserveBreached);
+        assert!(matches!(p.swap_exact_out(Direction::XtoY, 1_000_000u128 * WAD, Wad::MAX), Err(AmmError::InsufficientLiquidity { .. })));
+        let s = p.total_shares();
+        assert_eq!(p.remove_liquidity(s + 1).unwrap_err(), AmmError::InsufficientShares { requested: s + 1, available: s });
+        let err = p.swap_exact_in(Direction::YtoX, 10_000u128 * WAD, 10_000u128 * WAD).unwrap_err();
+        assert!(matches!(err, AmmError::SlippageExceeded { expected, .. } if expected == 10_000u128 * WAD));
+        assert_eq!(p, before);
+    }
+
+    #[test]
+    fn t_pool_min_reserve_policy() {
+        let mut p = Pool::with_min_reserve(1_000u128 * WAD, 1_000u128 * WAD, 0, 500u128 * WAD).unwrap();
+        let before = p.clone();
+        // 60% sairia do pool: y' = 400 < 500 (mínimo do pool), mesmo acima do MIN_RESERVE global
+        let err = p.swap_exact_out(Direction::XtoY, 600u128 * WAD, Wad::MAX).unwrap_err();
+        assert_eq!(err, AmmError::MinReserveBreached);
+        assert_eq!(p, before);
+    }
+
+    #[test]
+    fn t_invalid_fee_rejected_at_construction() {
+        let err = Pool::new(1_000u128 * WAD, 1_000u128 * WAD, 1_000_000).unwrap_err();
+        assert_eq!(err, AmmError::FeeTooHigh { fee_ppm: 1_000_000 });
+        assert!(Pool::new(1_000u128 * WAD, 1_000u128 * WAD, 999_999).is_ok());
+    }
+
+    #[test]
+    fn t_protocol_fee_leaves_reserves() {
+        let mut p = pool_1m();
+        p.set_protocol_fee_ppm(250_000).unwrap(); // 25% da taxa
+        let k0 = p.k();
+        let dx = 10_000u128 * WAD;
+        let r = p.swap_exact_in(Direction::XtoY, dx, 0).unwrap();
+        assert_eq!(r.fee, 30u128 * WAD);
+        assert_eq!((r.lp_fee, r.protocol_fee), (22_500_000_000_000_000_000, 7_500_000_000_000_000_000));
+        // output igual ao de um pool sem split (a curva só vê dx_net)
+        assert_eq!(r.amount_out, get_amount_out(1_000_000u128 * WAD, 1_000_000u128 * WAD, dx, FEE3).unwrap());
+        assert_eq!(p.reserves().x, 1_000_000u128 * WAD + dx - r.protocol_fee);
+        assert!(p.k() >= k0);
+        assert_eq!(p.protocol_fees(), ProtocolFees { x: r.protocol_fee, y: 0 });
+        assert_eq!(p.collect_protocol_fees(), (r.protocol_fee, 0));
+        assert_eq!(p.protocol_fees(), ProtocolFees::default());
+        assert_eq!(p.set_protocol_fee_ppm(1_000_001).unwrap_err(), AmmError::ProtocolFeeTooHigh { protocol_fee_ppm: 1_000_001 });
+    }
+
+    #[test]
+    fn t_state_roundtrip() {
+        let mut p = pool_1m();
+        p.set_protocol_fee_ppm(500_000).unwrap();
+        p.swap_exact_in(Direction::YtoX, 1_000u128 * WAD, 0).unwrap();
+        p.set_fee_ppm(10_000).unwrap();
+        let s = p.state();
+        assert_eq!(Pool::restore(s).unwrap(), p);
+        assert_eq!(s.hash(), Pool::restore(s).unwrap().state().hash());
+        assert_ne!(s.hash(), pool_1m().state().hash());
+        assert_eq!(p.set_fee_ppm(1_000_000).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: 1_000_000 });
+        assert_eq!(p.fee_ppm(), 10_000);
+    }
+
+    #[test]
+    fn t_add_then_remove_liquidity() {
+        let mut p = pool_1m();
+        let add = p.add_liquidity(100_000u128 * WAD, 100_000u128 * WAD).unwrap();
+        assert_eq!(add.shares, 100_000u128 * WAD);
+        assert_eq!(p.total_shares(), 1_100_000u128 * WAD);
+        let rem = p.remove_liquidity(add.shares).unwrap();
+        assert_eq!((rem.amount_x, rem.amount_y), (100_000u128 * WAD, 100_000u128 * WAD));
+        assert_eq!(p.total_shares(), 1_000_000u128 * WAD);
+        assert_eq!(rem.k, p.k());
+    }
+
+    #[test]
+    fn t_add_liquidity_uses_only_the_balanced_part() {
+        let mut p = Pool::new(1_000u128 * WAD, 4_000u128 * WAD, FEE3).unwrap();
+        let s0 = p.total_shares();
+        // oferece Y de sobra: entra só o Y na razão 1:4, o resto fica com o depositante
+        let add = p.add_liquidity(100u128 * WAD, 1_000u128 * WAD).unwrap();
+        assert_eq!(add.shares, s0 / 10);
+        assert_eq!((add.amount_x, add.amount_y), (100u128 * WAD, 400u128 * WAD));
+        assert_eq!(p.reserves(), Reserves::new(1_100u128 * WAD, 4_400u128 * WAD));
+        // razão que não fecha: o lado usado é ceil (a favor do pool) e nunca passa do oferecido
+        let (x, y, s) = (p.reserves().x, p.reserves().y, p.total_shares());
+        let add = p.add_liquidity(7, 1_000).unwrap();
+        assert!(add.amount_x <= 7 && add.amount_y <= 1_000);
+        assert!(U256::from(add.amount_x) * U256::from(s) >= U256::from(add.shares) * U256::from(x));
+        assert!(U256::from(add.amount_y) * U256::from(s) >= U256::from(add.shares) * U256::from(y));
+    }
+}
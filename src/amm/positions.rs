@@ -0,0 +1,225 @@
+//! Ledger de posições de LP: saldo de shares por dono, por pool.
+//!
+//! Cada `LpBook` envolve um `Pool` e é o único caminho para mintar/queimar shares, então a soma dos
+//! saldos é sempre `total_shares` (`check_supply` confere). Operações: mint (`add_liquidity`), burn
+//! (`remove_liquidity`) e `transfer` entre donos; swaps passam direto para o pool e não mexem em saldos.
+//! Como no `Pool`, cada operação calcula tudo antes de gravar: em erro, nada muda.
+//!
+//! Valor subjacente de um saldo (ADR-0001, mesma política do burn): `x·shares/total` e
+//! `y·shares/total`, ambos **floor** — a soma dos valores de todos os donos nunca passa das reservas.
+
+use std::collections::BTreeMap;
+
+use super::errors::AmmError;
+use super::guardrails::{checked_add, ensure_nonzero, mul_u128_to_u256, u256_to_u128_checked};
+use super::pool::{LiquidityReceipt, Pool, SwapReceipt};
+use super::types::{AccountId, Direction, U256, Wad};
+
+/// Identificador de pool no ledger.
+pub type PoolId = u32;
+
+/// Saldo de um dono e sua parte das reservas (floor).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionValue {
+    pub shares: Wad,
+    pub amount_x: Wad,
+    pub amount_y: Wad,
+}
+
+/// Pool com os saldos de shares de cada dono.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LpBook {
+    pool: Pool,
+    /// Só saldos > 0
+    balances: BTreeMap<AccountId, Wad>,
+}
+
+impl LpBook {
+    /// Registra `pool` com todo o supply atual em nome de `owner`.
+    pub fn new(owner: AccountId, pool: Pool) -> Self {
+        let balances = BTreeMap::from([(owner, pool.total_shares())]);
+        Self { pool, balances }
+    }
+
+    pub fn pool(&self) -> &Pool { &self.pool }
+    pub fn balance(&self, owner: AccountId) -> Wad { self.balances.get(&owner).copied().unwrap_or(0) }
+    /// Donos com saldo > 0, em ordem de id.
+    pub fn owners(&self) -> impl Iterator<Item = (AccountId, Wad)> + '_ { self.balances.iter().map(|(&o, &s)| (o, s)) }
+
+    fn set_balance(balances: &mut BTreeMap<AccountId, Wad>, owner: AccountId, shares: Wad) {
+        if shares == 0 { balances.remove(&owner); } else { balances.insert(owner, shares); }
+    }
+
+    fn debit(&self, owner: AccountId, shares: Wad) -> Result<Wad, AmmError> {
+        let available = self.balance(owner);
+        if shares > available { return Err(AmmError::InsufficientShares { requested: shares, available }); }
+        Ok(available - shares)
+    }
+
+    /// Deposita `(dx, dy)` no pool e credita as shares mintadas a `owner`.
+    pub fn add_liquidity(&mut self, owner: AccountId, dx: Wad, dy: Wad) -> Result<LiquidityReceipt, AmmError> {
+        let mut pool = self.pool.clone();
+        let r = pool.add_liquidity(dx, dy)?;
+        let balance = checked_add(self.balance(owner), r.shares)?;
+        self.pool = pool;
+        Self::set_balance(&mut self.balances, owner, balance);
+        Ok(r)
+    }
+
+    /// Queima `shares` de `owner` e devolve a parte das reservas (floor).
+    pub fn remove_liquidity(&mut self, owner: AccountId, shares: Wad) -> Result<LiquidityReceipt, AmmError> {
+        let balance = self.debit(owner, shares)?;
+        let mut pool = self.pool.clone();
+        let r = pool.remove_liquidity(shares)?;
+        self.pool = pool;
+        Self::set_balance(&mut self.balances, owner, balance);
+        Ok(r)
+    }
+
+    /// Move `shares` de `from` para `to`.
+    pub fn transfer(&mut self, from: AccountId, to: AccountId, shares: Wad) -> Result<(), AmmError> {
+        ensure_nonzero(shares)?;
+        let from_balance = self.debit(from, shares)?;
+        if from == to { return Ok(()); }
+        let to_balance = checked_add(self.balance(to), shares)?;
+        Self::set_balance(&mut self.balances, from, from_balance);
+        Self::set_balance(&mut self.balances, to, to_balance);
+        Ok(())
+    }
+
+    pub fn swap_exact_in(&mut self, dir: Direction, amount_in: Wad, min_out: Wad) -> Result<SwapReceipt, AmmError> {
+        self.pool.swap_exact_in(dir, amount_in, min_out)
+    }
+
+    pub fn swap_exact_out(&mut self, dir: Direction, amount_out: Wad, max_in: Wad) -> Result<SwapReceipt, AmmError> {
+        self.pool.swap_exact_out(dir, amount_out, max_in)
+    }
+
+    /// Saldo de `owner` e sua parte das reservas atuais (floor).
+    pub fn value_of(&self, owner: AccountId) -> Result<PositionValue, AmmError> {
+        let shares = self.balance(owner);
+        let total = U256::from(self.pool.total_shares());
+        let reserves = self.pool.reserves();
+        Ok(PositionValue {
+            shares,
+            amount_x: u256_to_u128_checked(mul_u128_to_u256(reserves.x, shares) / total)?,
+            amount_y: u256_to_u128_checked(mul_u128_to_u256(reserves.y, shares) / total)?,
+        })
+    }
+
+    /// Confere que a soma dos saldos é o `total_shares` do pool.
+    pub fn check_supply(&self) -> Result<(), AmmError> {
+        let balances = self.balances.values().try_fold(0, |acc, &s| checked_add(acc, s))?;
+        let total_shares = self.pool.total_shares();
+        if balances != total_shares { return Err(AmmError::ShareSupplyMismatch { balances, total_shares }); }
+        Ok(())
+    }
+}
+
+/// Livros de LP de vários pools.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LpLedger {
+    books: BTreeMap<PoolId, LpBook>,
+}
+
+impl LpLedger {
+    pub fn new() -> Self { Self::default() }
+
+    /// Registra `pool` sob `id` com todo o supply em nome de `owner`; devolve o livro anterior do `id`.
+    pub fn insert_pool(&mut self, id: PoolId, owner: AccountId, pool: Pool) -> Option<LpBook> {
+        self.books.insert(id, LpBook::new(owner, pool))
+    }
+
+    pub fn book(&self, id: PoolId) -> Option<&LpBook> { self.books.get(&id) }
+    pub fn book_mut(&mut self, id: PoolId) -> Option<&mut LpBook> { self.books.get_mut(&id) }
+    pub fn books(&self) -> impl Iterator<Item = (PoolId, &LpBook)> { self.books.iter().map(|(&id, b)| (id, b)) }
+
+    /// Posições de `owner` em todos os pools onde tem saldo, em ordem de id.
+    pub fn positions(&self, owner: AccountId) -> Result<Vec<(PoolId, PositionValue)>, AmmError> {
+        self.books
+            .iter()
+            .filter(|(_, b)| b.balance(owner) > 0)
+            .map(|(&id, b)| Ok((id, b.value_of(owner)?)))
+            .collect()
+    }
+
+    /// `check_supply` em todos os pools.
+    pub fn check_supply(&self) -> Result<(), AmmError> {
+        self.books.values().try_for_each(LpBook::check_supply)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::WAD;
+
+    const ALICE: AccountId = 1;
+    const BOB: AccountId = 2;
+
+    fn book() -> LpBook { LpBook::new(ALICE, Pool::new(1_000 * WAD, 4_000 * WAD, 3_000).unwrap()) }
+
+    #[test]
+    fn t_mint_transfer_burn_keep_supply() {
+        let mut b = book();
+        assert_eq!(b.balance(ALICE), 2_000 * WAD);
+        let r = b.add_liquidity(BOB, 100 * WAD, 400 * WAD).unwrap();
+        assert_eq!(b.balance(BOB), r.shares);
+        b.transfer(ALICE, BOB, 500 * WAD).unwrap();
+        assert_eq!(b.balance(ALICE), 1_500 * WAD);
+        b.remove_liquidity(BOB, 500 * WAD).unwrap();
+        b.transfer(ALICE, ALICE, WAD).unwrap();
+        b.check_supply().unwrap();
+        // saldo zerado some do livro
+        b.transfer(BOB, ALICE, b.balance(BOB)).unwrap();
+        assert_eq!(b.owners().collect::<Vec<_>>(), vec![(ALICE, b.pool().total_shares())]);
+        b.check_supply().unwrap();
+    }
+
+    #[test]
+    fn t_failures_leave_book_unchanged() {
+        let mut b = book();
+        let before = b.clone();
+        assert_eq!(b.transfer(BOB, ALICE, WAD).unwrap_err(), AmmError::InsufficientShares { requested: WAD, available: 0 });
+        assert_eq!(b.transfer(ALICE, BOB, 0).unwrap_err(), AmmError::ZeroAmount);
+        assert_eq!(b.remove_liquidity(BOB, WAD).unwrap_err(), AmmError::InsufficientShares { requested: WAD, available: 0 });
+        // saldo existe, mas o pool recusa (reserva mínima)
+        assert_eq!(b.remove_liquidity(ALICE, 2_000 * WAD).unwrap_err(), AmmError::MinReserveBreached);
+        assert_eq!(b.add_liquidity(BOB, 0, WAD).unwrap_err(), AmmError::ZeroAmount);
+        assert_eq!(b, before);
+    }
+
+    #[test]
+    fn t_value_is_floor_and_bounded_by_reserves() {
+        let mut b = book();
+        b.transfer(ALICE, BOB, 2_000 * WAD / 3).unwrap();
+        b.swap_exact_in(Direction::XtoY, 10 * WAD, 0).unwrap();
+        let (va, vb) = (b.value_of(ALICE).unwrap(), b.value_of(BOB).unwrap());
+        let r = b.pool().reserves();
+        let total = b.pool().total_shares();
+        assert_eq!(vb.amount_x, (U256::from(r.x) * U256::from(vb.shares) / U256::from(total)).as_u128());
+        assert!(va.amount_x + vb.amount_x <= r.x && r.x - (va.amount_x + vb.amount_x) <= 1);
+        assert!(va.amount_y + vb.amount_y <= r.y && r.y - (va.amount_y + vb.amount_y) <= 1);
+        // valor = o que o burn devolveria
+        let burn = b.clone().remove_liquidity(BOB, vb.shares).unwrap();
+        assert_eq!((burn.amount_x, burn.amount_y), (vb.amount_x, vb.amount_y));
+        assert_eq!(b.value_of(99).unwrap(), PositionValue::default());
+    }
+
+    #[test]
+    fn t_ledger_multiple_pools() {
+        let mut l = LpLedger::new();
+        assert!(l.insert_pool(7, ALICE, Pool::new(1_000 * WAD, 1_000 * WAD, 0).unwrap()).is_none());
+        l.insert_pool(3, BOB, Pool::new(100 * WAD, 400 * WAD, 0).unwrap());
+        l.book_mut(7).unwrap().add_liquidity(BOB, 10 * WAD, 10 * WAD).unwrap();
+        let pos = l.positions(BOB).unwrap();
+        assert_eq!(pos.iter().map(|p| p.0).collect::<Vec<_>>(), vec![3, 7]);
+        assert_eq!(pos[0].1, PositionValue { shares: 200 * WAD, amount_x: 100 * WAD, amount_y: 400 * WAD });
+        assert_eq!(pos[1].1.shares, 10 * WAD);
+        assert_eq!(l.positions(ALICE).unwrap().len(), 1);
+        l.check_supply().unwrap();
+    }
+}
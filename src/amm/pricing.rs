@@ -3,10 +3,10 @@
 
 use super::errors::AmmError;
 use super::guardrails::{
-    div_nearest_even_u256, div_nearest_even_u256_to_u128, ensure_nonzero, ensure_reserves, u256_to_u128_checked,
+    div_nearest_even_u256, div_nearest_even_u256_to_u128, ensure_fee, ensure_nonzero, ensure_reserves, u256_to_u128_checked,
 };
-use super::swap::{get_amount_in, get_amount_out};
-use super::types::{U256, Ppm, Wad, PPM_SCALE, WAD};
+use super::swap::{get_amount_in_dir, get_amount_out_dir};
+use super::types::{Direction, U256, Ppm, Wad, PPM_SCALE, WAD};
 
 #[inline]
 fn ceil_div_u256(n: U256, d: U256) -> U256 { (n + (d - U256::from(1u8))) / d }
@@ -26,21 +26,47 @@ pub fn spot_price_y_in_x(x: Wad, y: Wad) -> Result<Wad, AmmError> {
     div_nearest_even_u256_to_u128(n, U256::from(y))
 }
 
+/// Preço à vista de 1 unidade do ativo de entrada de `dir`, cotado no ativo de saída (em WAD)
+pub fn spot_price_dir(dir: Direction, x: Wad, y: Wad) -> Result<Wad, AmmError> {
+    match dir {
+        Direction::XtoY => spot_price_x_in_y(x, y),
+        Direction::YtoX => spot_price_y_in_x(x, y),
+    }
+}
+
 // --------- Execução e slippage ---------
-/// Preço efetivo (execução) da troca X→Y para um `dx` **bruto** (inclui taxa): **p_exec = out/dx** (em WAD)
-pub fn execution_price_x_to_y(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+/// Preço efetivo (execução) no sentido `dir` para um input **bruto** (inclui taxa): **p_exec = out/in** (em WAD)
+pub fn execution_price_dir(dir: Direction, x: Wad, y: Wad, amount_in: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
     ensure_reserves(x, y)?;
-    ensure_nonzero(dx)?;
-    let out = get_amount_out(x, y, dx, fee_ppm)?;
+    ensure_nonzero(amount_in)?;
+    let out = get_amount_out_dir(dir, x, y, amount_in, fee_ppm)?;
     let n = U256::from(out) * U256::from(WAD);
-    div_nearest_even_u256_to_u128(n, U256::from(dx))
+    div_nearest_even_u256_to_u128(n, U256::from(amount_in))
 }
 
-/// Slippage relativo em **PPM** comparando `p_exec` vs `spot` (sempre ≥0):
+/// Preço efetivo (execução) da troca X→Y para um `dx` **bruto** (inclui taxa): **p_exec = out/dx** (em WAD)
+pub fn execution_price_x_to_y(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    execution_price_dir(Direction::XtoY, x, y, dx, fee_ppm)
+}
+
+/// Preço efetivo (execução) da troca Y→X para um `dy` **bruto** (inclui taxa): **p_exec = out/dy** (em WAD)
+pub fn execution_price_y_to_x(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    execution_price_dir(Direction::YtoX, x, y, dy, fee_ppm)
+}
+
+/// Slippage relativo em **PPM** no sentido `dir`, comparando `p_exec` vs `spot` (sempre ≥0):
 /// slippage_ppm = ((spot - p_exec) / spot) * 1e6
-pub fn slippage_ppm_x_to_y(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Ppm, AmmError> {
-    let spot = spot_price_x_in_y(x, y)?;           // WAD
-    let exec = execution_price_x_to_y(x, y, dx, fee_ppm)?; // WAD
+pub fn slippage_ppm_dir(dir: Direction, x: Wad, y: Wad, amount_in: Wad, fee_ppm: Ppm) -> Result<Ppm, AmmError> {
+    ensure_fee(fee_ppm)?;
+    let spot = spot_price_dir(dir, x, y)?;                         // WAD
+    let exec = execution_price_dir(dir, x, y, amount_in, fee_ppm)?; // WAD
+    slippage_ppm_from_prices(spot, exec)
+}
+
+/// Slippage em **PPM** entre um `spot` e um `p_exec` já calculados (WAD), mesma regra de `slippage_ppm_dir`
+/// (nearest-even, 0 se `p_exec ≥ spot`, saturado em 1e6). Útil para rotas com vários pools.
+pub fn slippage_ppm_from_prices(spot: Wad, exec: Wad) -> Result<Ppm, AmmError> {
     if exec >= spot { return Ok(0); }
     let num = (U256::from(spot) - U256::from(exec)) * U256::from(PPM_SCALE as u64);
     let den = U256::from(spot);
@@ -51,13 +77,23 @@ pub fn slippage_ppm_x_to_y(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Ppm,
     Ok(ppm)
 }
 
+/// Slippage relativo em **PPM** da troca X→Y (ver `slippage_ppm_dir`)
+pub fn slippage_ppm_x_to_y(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Ppm, AmmError> {
+    slippage_ppm_dir(Direction::XtoY, x, y, dx, fee_ppm)
+}
+
+/// Slippage relativo em **PPM** da troca Y→X (ver `slippage_ppm_dir`)
+pub fn slippage_ppm_y_to_x(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Ppm, AmmError> {
+    slippage_ppm_dir(Direction::YtoX, x, y, dy, fee_ppm)
+}
+
 // --------- Cotas com tolerância de slippage ---------
-/// Retorna **min_out** aceito pela UI para X→Y considerando `slippage_tolerance_ppm` (0..1e6)
+/// Retorna **min_out** aceito pela UI no sentido `dir` considerando `slippage_tolerance_ppm` (0..1e6)
 /// min_out = floor( out * (1 - tol) )
-pub fn min_out_with_tolerance(
-    x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm, slippage_tolerance_ppm: Ppm,
+pub fn min_out_with_tolerance_dir(
+    dir: Direction, x: Wad, y: Wad, amount_in: Wad, fee_ppm: Ppm, slippage_tolerance_ppm: Ppm,
 ) -> Result<Wad, AmmError> {
-    let out = get_amount_out(x, y, dx, fee_ppm)?;
+    let out = get_amount_out_dir(dir, x, y, amount_in, fee_ppm)?;
     let tol = if slippage_tolerance_ppm > PPM_SCALE { PPM_SCALE } else { slippage_tolerance_ppm } as u64;
     let factor = (PPM_SCALE as u64) - tol; // (1 - tol)
     let n = U256::from(out) * U256::from(factor);
@@ -65,17 +101,33 @@ pub fn min_out_with_tolerance(
     Ok(q.as_u128())
 }
 
+/// Retorna **max_in** aceito pela UI no sentido `dir` para receber `amount_out` com tolerância (0..1e6)
+/// max_in = ceil( in * (1 + tol) )
+pub fn max_in_with_tolerance_dir(
+    dir: Direction, x: Wad, y: Wad, amount_out: Wad, fee_ppm: Ppm, slippage_tolerance_ppm: Ppm,
+) -> Result<Wad, AmmError> {
+    let amount_in = get_amount_in_dir(dir, x, y, amount_out, fee_ppm)?;
+    let tol = if slippage_tolerance_ppm > PPM_SCALE { PPM_SCALE } else { slippage_tolerance_ppm } as u64;
+    let factor = (PPM_SCALE as u64) + tol; // (1 + tol)
+    let n = U256::from(amount_in) * U256::from(factor);
+    let q = ceil_div_u256(n, U256::from(PPM_SCALE as u64)); // ceil
+    u256_to_u128_checked(q)
+}
+
+/// Retorna **min_out** aceito pela UI para X→Y considerando `slippage_tolerance_ppm` (0..1e6)
+/// min_out = floor( out * (1 - tol) )
+pub fn min_out_with_tolerance(
+    x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm, slippage_tolerance_ppm: Ppm,
+) -> Result<Wad, AmmError> {
+    min_out_with_tolerance_dir(Direction::XtoY, x, y, dx, fee_ppm, slippage_tolerance_ppm)
+}
+
 /// Retorna **max_in** aceito pela UI para atingir `dy` com tolerância `slippage_tolerance_ppm` (0..1e6)
 /// max_in = ceil( dx * (1 + tol) )
 pub fn max_in_with_tolerance(
     x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm, slippage_tolerance_ppm: Ppm,
 ) -> Result<Wad, AmmError> {
-    let dx = get_amount_in(x, y, dy, fee_ppm)?;
-    let tol = if slippage_tolerance_ppm > PPM_SCALE { PPM_SCALE } else { slippage_tolerance_ppm } as u64;
-    let factor = (PPM_SCALE as u64) + tol; // (1 + tol)
-    let n = U256::from(dx) * U256::from(factor);
-    let q = ceil_div_u256(n, U256::from(PPM_SCALE as u64)); // ceil
-    Ok(q.as_u128())
+    max_in_with_tolerance_dir(Direction::XtoY, x, y, dy, fee_ppm, slippage_tolerance_ppm)
 }
 
 // -------------------------
@@ -84,6 +136,7 @@ pub fn max_in_with_tolerance(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::amm::swap::{get_amount_in, get_amount_out, get_amount_in_y_to_x};
     use crate::amm::types::MIN_RESERVE;
 
     const FEE0: Ppm = 0;
@@ -143,7 +196,7 @@ mod tests {
         let out = get_amount_out(x, y, dx, FEE0).unwrap();
         let p_exec = execution_price_x_to_y(x, y, dx, FEE0).unwrap();
         let p_exec_check = (U256::from(out) * U256::from(WAD)) / U256::from(dx);
-        assert_eq!(p_exec as u128, p_exec_check.as_u128());
+        assert_eq!(p_exec, p_exec_check.as_u128());
     }
 
     #[test]
@@ -153,4 +206,41 @@ mod tests {
         // dx zero na execução
         assert!(execution_price_x_to_y(MIN_RESERVE, MIN_RESERVE, 0, FEE0).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn t_y_to_x_pricing_uses_inverse_spot() {
+        let (x, y, dy) = (1_000_000u128*WAD, 2_000_000u128*WAD, 20_000u128*WAD);
+        assert_eq!(spot_price_dir(Direction::YtoX, x, y).unwrap(), WAD / 2);
+        // p_exec Y→X fica abaixo do spot 0.5 e o slippage espelha o caso X→Y simétrico
+        let p = execution_price_y_to_x(x, y, dy, FEE0).unwrap();
+        assert!(p < WAD / 2);
+        let s_yx = slippage_ppm_y_to_x(x, y, dy, FEE0).unwrap();
+        let s_xy = slippage_ppm_x_to_y(y, x, dy, FEE0).unwrap();
+        assert_eq!(s_yx, s_xy);
+    }
+
+    #[test]
+    fn t_tolerance_helpers_y_to_x() {
+        let (x, y, dx) = (1_000_000u128*WAD, 2_000_000u128*WAD, 9_870u128*WAD);
+        let dy = get_amount_in_y_to_x(x, y, dx, FEE3).unwrap();
+        let max_in = max_in_with_tolerance_dir(Direction::YtoX, x, y, dx, FEE3, 0).unwrap();
+        assert_eq!(max_in, dy);
+        let min_out = min_out_with_tolerance_dir(Direction::YtoX, x, y, dy, FEE3, 0).unwrap();
+        assert!(min_out >= dx);
+    }
+
+    #[test]
+    fn t_fee_too_high_rejected_everywhere() {
+        let (x, y, d) = (1_000_000u128*WAD, 1_000_000u128*WAD, 10_000u128*WAD);
+        for fee in [1_000_000, u32::MAX] {
+            let e = AmmError::FeeTooHigh { fee_ppm: fee };
+            assert_eq!(execution_price_y_to_x(x, y, d, fee).unwrap_err(), e);
+            assert_eq!(slippage_ppm_x_to_y(x, y, d, fee).unwrap_err(), e);
+            // mesmo com reservas inválidas a taxa é checada primeiro
+            assert_eq!(slippage_ppm_x_to_y(0, y, d, fee).unwrap_err(), e);
+            assert_eq!(min_out_with_tolerance(x, y, d, fee, 0).unwrap_err(), e);
+            assert_eq!(max_in_with_tolerance(x, y, d, fee, 0).unwrap_err(), e);
+        }
+        assert!(slippage_ppm_x_to_y(x, y, d, 999_999).is_ok());
+    }
+}
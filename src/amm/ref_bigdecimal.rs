@@ -0,0 +1,714 @@
+//! Modelo de referência em precisão arbitrária (racionais exatos) para testes diferenciais.
+//! Nada aqui é usado em produção: serve de "verdade" contra a qual as contas U256 do core
+//! são comparadas, e de base para gerar goldens.
+//!
+//! Convenções:
+//! - funções `*_exact` devolvem o valor **contínuo** (sem arredondamento) como `BigRational`;
+//! - funções `ref_*` aplicam a política do ADR-0001 no valor exato e devolvem `Wad`,
+//!   com as mesmas guardas de erro do core;
+//! - funções `spec_*` seguem **literalmente** a especificação do ADR-0002 (arredondamento
+//!   nearest-even nos intermediários, floor/ceil na fronteira) sobre racionais exatos: devem
+//!   bater bit a bit com o core e são a fonte dos goldens gerados.
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use super::clmm_math::{MAX_TICK, MIN_TICK, TICK_RATIOS};
+use super::concentrated::MAX_TICK_SPACING;
+use super::errors::AmmError;
+use super::types::{AccountId, Direction, Ppm, Wad, MIN_RESERVE, PPM_SCALE, WAD};
+
+pub type Q = BigRational;
+
+#[inline]
+pub fn int(v: u128) -> BigInt { BigInt::from(v) }
+
+#[inline]
+pub fn q(v: u128) -> Q { Q::from_integer(int(v)) }
+
+#[inline]
+fn ratio(n: BigInt, d: BigInt) -> Q { Q::new(n, d) }
+
+// --------- Arredondamentos ---------
+/// floor(v) em u128 (None se negativo ou fora da faixa)
+pub fn floor_u128(v: &Q) -> Option<Wad> { v.floor().to_integer().to_u128() }
+
+/// ceil(v) em u128 (None se negativo ou fora da faixa)
+pub fn ceil_u128(v: &Q) -> Option<Wad> { v.ceil().to_integer().to_u128() }
+
+/// nearest (ties-to-even) em u128
+pub fn round_half_even_u128(v: &Q) -> Option<Wad> {
+    let fl = v.floor();
+    let frac = v - &fl;
+    let half = ratio(BigInt::one(), BigInt::from(2u8));
+    let fl = fl.to_integer();
+    let r = if frac > half || (frac == half && fl.is_odd()) { fl + BigInt::one() } else { fl };
+    r.to_u128()
+}
+
+/// |a - b| como racional
+pub fn abs_diff(a: &Q, b: &Q) -> Q { (a - b).abs() }
+
+fn isqrt(n: &BigInt) -> BigInt { n.sqrt() }
+
+// --------- Swap ---------
+/// Taxa sobre o input (fronteira ⇒ **ceil**, inteira): `ceil(dx * fee / 1e6)`
+pub fn fee_exact(dx: Wad, fee_ppm: Ppm) -> BigInt {
+    let n = int(dx) * BigInt::from(fee_ppm);
+    let d = BigInt::from(PPM_SCALE);
+    n.div_ceil(&d)
+}
+
+/// Output contínuo X→Y: `y * dx_net / (x + dx_net)`. None se `dx_net <= 0`.
+pub fn amount_out_exact(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Option<Q> {
+    let dx_net = int(dx) - fee_exact(dx, fee_ppm);
+    if !dx_net.is_positive() { return None; }
+    Some(ratio(int(y) * &dx_net, int(x) + dx_net))
+}
+
+/// Input **líquido** contínuo para receber `dy`: `x * dy / (y - dy)`
+pub fn amount_in_net_exact(x: Wad, y: Wad, dy: Wad) -> Q {
+    ratio(int(x) * int(dy), int(y) - int(dy))
+}
+
+/// Input bruto líquido de taxa para um `dx` inteiro
+fn net_of(dx: Wad, fee_ppm: Ppm) -> BigInt { int(dx) - fee_exact(dx, fee_ppm) }
+
+/// Referência X→Y com a política do ADR-0001: `floor(out_exact)` e mesmas guardas do core.
+pub fn ref_amount_out(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    check_fee(fee_ppm)?;
+    check_reserves(x, y)?;
+    if dx == 0 { return Err(AmmError::ZeroAmount); }
+    let out = amount_out_exact(x, y, dx, fee_ppm).ok_or(AmmError::InputTooSmall)?;
+    let out = floor_u128(&out).ok_or(AmmError::Overflow)?;
+    if y - out < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    Ok(out)
+}
+
+/// Referência do **menor dx bruto** com `floor(out_exact(dx)) ≥ dy` (fronteira ⇒ ceil).
+pub fn ref_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    check_fee(fee_ppm)?;
+    check_reserves(x, y)?;
+    if dy == 0 { return Err(AmmError::ZeroAmount); }
+    if dy >= y { return Err(AmmError::InsufficientLiquidity { requested: dy, available: y - MIN_RESERVE }); }
+    if dy >= y - MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    // líquido mínimo: out_exact(n) ≥ dy ⇔ n ≥ x*dy/(y-dy)
+    let need = amount_in_net_exact(x, y, dy).ceil().to_integer();
+    gross_of(&need, fee_ppm)
+}
+
+/// Menor `dx` bruto com `net_of(dx) ≥ need`: chute fechado e ajuste local (net(dx) é monótono).
+fn gross_of(need: &BigInt, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let gross = ratio(need * BigInt::from(PPM_SCALE), BigInt::from(PPM_SCALE - fee_ppm));
+    let mut g = ceil_u128(&gross).ok_or(AmmError::Overflow)?.max(1);
+    while g > 1 && net_of(g - 1, fee_ppm) >= *need { g -= 1; }
+    while net_of(g, fee_ppm) < *need { g = g.checked_add(1).ok_or(AmmError::Overflow)?; }
+    Ok(g)
+}
+
+// --------- Pools com pesos ---------
+/// Razão de pesos `w_a / w_b` reduzida a `(p, q)`. O modelo exato só trata razões com
+/// denominador pequeno (ex.: pesos múltiplos de 10%), onde `b^q` ainda é barato em BigInt.
+pub fn weight_ratio(w_a: Wad, w_b: Wad) -> Option<(u32, u32)> {
+    let g = w_a.gcd(&w_b);
+    let (p, q) = (w_a / g, w_b / g);
+    if p > 64 || q > 64 { return None; }
+    Some((p as u32, q as u32))
+}
+
+/// Menor inteiro `n` com `n^k · den ≥ num` (ceil da raiz k-ésima de `num/den`).
+fn ceil_root(num: &BigInt, den: &BigInt, k: u32) -> BigInt {
+    let mut n = (num / den).nth_root(k);
+    while &n.pow(k) * den < *num { n += BigInt::one(); }
+    n
+}
+
+fn check_weighted(b_in: Wad, w_in: Wad, b_out: Wad, w_out: Wad, fee_ppm: Ppm) -> Result<(u32, u32), AmmError> {
+    check_fee(fee_ppm)?;
+    check_reserves(b_in, b_out)?;
+    if w_in == 0 || w_out == 0 { return Err(AmmError::InvalidWeights); }
+    // fora do alcance do modelo exato (ex.: 1/3 : 2/3 em WAD): recusa em vez de entrar em pânico
+    weight_ratio(w_in, w_out).ok_or(AmmError::InvalidWeights)
+}
+
+/// Referência do swap com pesos: `floor(b_o·(1 - (b_i/(b_i + net))^(w_i/w_o)))`, exato via raiz inteira:
+/// `floor(out) = b_o - ceil((b_o^q · b_i^p / (b_i + net)^p)^(1/q))`.
+pub fn ref_weighted_amount_out(b_in: Wad, w_in: Wad, b_out: Wad, w_out: Wad, amount_in: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let (p, q) = check_weighted(b_in, w_in, b_out, w_out, fee_ppm)?;
+    if amount_in == 0 { return Err(AmmError::ZeroAmount); }
+    let net = net_of(amount_in, fee_ppm);
+    if !net.is_positive() { return Err(AmmError::InputTooSmall); }
+    let kept = ceil_root(&(int(b_out).pow(q) * int(b_in).pow(p)), &(int(b_in) + net).pow(p), q);
+    let out = to_wad(int(b_out) - kept)?;
+    if out == 0 { return Err(AmmError::InputTooSmall); }
+    if b_out - out < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    Ok(out)
+}
+
+/// Referência do **menor input bruto** com `ref_weighted_amount_out ≥ amount_out`:
+/// `b_i + net ≥ ceil((b_o^q · b_i^p / (b_o - a_o)^q)^(1/p))`.
+pub fn ref_weighted_amount_in(b_in: Wad, w_in: Wad, b_out: Wad, w_out: Wad, amount_out: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let (p, q) = check_weighted(b_in, w_in, b_out, w_out, fee_ppm)?;
+    if amount_out == 0 { return Err(AmmError::ZeroAmount); }
+    let available = b_out - MIN_RESERVE;
+    if amount_out >= b_out { return Err(AmmError::InsufficientLiquidity { requested: amount_out, available }); }
+    if amount_out >= available { return Err(AmmError::MinReserveBreached); }
+    let total = ceil_root(&(int(b_out).pow(q) * int(b_in).pow(p)), &int(b_out - amount_out).pow(q), p);
+    let need = (total - int(b_in)).max(BigInt::one());
+    gross_of(&need, fee_ppm)
+}
+
+// --------- Liquidez ---------
+/// Mint inicial exato: `floor(sqrt(x*y))` (raiz inteira de BigInt)
+pub fn initial_mint_exact(x: Wad, y: Wad) -> BigInt { isqrt(&(int(x) * int(y))) }
+
+/// Shares contínuos do add: `min(dx*S/x, dy*S/y)`
+pub fn add_liquidity_exact(x: Wad, y: Wad, dx: Wad, dy: Wad, total_shares: Wad) -> Q {
+    let sx = ratio(int(dx) * int(total_shares), int(x));
+    let sy = ratio(int(dy) * int(total_shares), int(y));
+    if sx < sy { sx } else { sy }
+}
+
+/// Saídas contínuas do remove: `(x*burn/S, y*burn/S)`
+pub fn remove_liquidity_exact(x: Wad, y: Wad, burn: Wad, total_shares: Wad) -> (Q, Q) {
+    (
+        ratio(int(x) * int(burn), int(total_shares)),
+        ratio(int(y) * int(burn), int(total_shares)),
+    )
+}
+
+// --------- Preços ---------
+/// Spot exato de 1 X em Y, em WAD: `y * WAD / x`
+pub fn spot_price_x_in_y_exact(x: Wad, y: Wad) -> Q { ratio(int(y) * int(WAD), int(x)) }
+
+/// Spot exato de 1 Y em X, em WAD: `x * WAD / y`
+pub fn spot_price_y_in_x_exact(x: Wad, y: Wad) -> Q { ratio(int(x) * int(WAD), int(y)) }
+
+/// Preço de execução exato em WAD para um par (out, in): `out * WAD / in`
+pub fn execution_price_exact(amount_out: Wad, amount_in: Wad) -> Q {
+    ratio(int(amount_out) * int(WAD), int(amount_in))
+}
+
+/// Slippage exato em ppm: `(spot - exec) / spot * 1e6`, saturado em 0 se exec ≥ spot
+pub fn slippage_ppm_exact(spot: &Q, exec: &Q) -> Q {
+    if exec >= spot { return Q::zero(); }
+    (spot - exec) / spot * q(PPM_SCALE as u128)
+}
+
+// --------- Especificação (bit a bit com o core) ---------
+fn to_wad(v: BigInt) -> Result<Wad, AmmError> { v.to_u128().ok_or(AmmError::Overflow) }
+
+/// ADR-0002 §2: `out = y - nearest_even(x*y / (x + dx_net))`, com as guardas do core.
+pub fn spec_amount_out(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    check_fee(fee_ppm)?;
+    check_reserves(x, y)?;
+    if dx == 0 { return Err(AmmError::ZeroAmount); }
+    let dx_net = to_wad(net_of(dx, fee_ppm)).map_err(|_| AmmError::Overflow)?;
+    if dx_net == 0 { return Err(AmmError::InputTooSmall); }
+    let x1 = x.checked_add(dx_net).ok_or(AmmError::Overflow)?;
+    let y_star = round_half_even_u128(&ratio(int(x) * int(y), int(x1))).ok_or(AmmError::Overflow)?;
+    let out = y.checked_sub(y_star).ok_or(AmmError::Overflow)?;
+    if y - out < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    Ok(out)
+}
+
+/// ADR-0002 §3: menor `dx` bruto com `spec_amount_out(dx) ≥ dy` (busca sobre a spec, limitada por `ref_amount_in`).
+pub fn spec_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let hi = ref_amount_in(x, y, dy, fee_ppm)?;
+    // o core não tem como pagar o próprio limite superior (ex.: x + dx estoura u128) ⇒ mesmo erro
+    spec_amount_out(x, y, hi, fee_ppm)?;
+    let ok = |dx: Wad| spec_amount_out(x, y, dx, fee_ppm).map(|o| o >= dy).unwrap_or(false);
+    let (mut lo, mut hi) = (1u128, hi);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if ok(mid) { hi = mid; } else { lo = mid + 1; }
+    }
+    Ok(hi)
+}
+
+/// Spot de 1 X em Y em WAD, nearest-even.
+pub fn spec_spot_price_x_in_y(x: Wad, y: Wad) -> Result<Wad, AmmError> {
+    check_reserves(x, y)?;
+    round_half_even_u128(&spot_price_x_in_y_exact(x, y)).ok_or(AmmError::Overflow)
+}
+
+/// Slippage em ppm X→Y: nearest-even sobre spot e p_exec já arredondados (como no core), saturado em 1e6.
+pub fn spec_slippage_ppm_x_to_y(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Ppm, AmmError> {
+    check_fee(fee_ppm)?;
+    let spot = spec_spot_price_x_in_y(x, y)?;
+    let out = spec_amount_out(x, y, dx, fee_ppm)?;
+    let exec = round_half_even_u128(&execution_price_exact(out, dx)).ok_or(AmmError::Overflow)?;
+    if exec >= spot { return Ok(0); }
+    let ppm = round_half_even_u128(&slippage_ppm_exact(&q(spot), &q(exec))).ok_or(AmmError::Overflow)?;
+    Ok(ppm.min(PPM_SCALE as u128) as Ppm)
+}
+
+/// Mint inicial com guardas: `floor(sqrt(x*y))`.
+pub fn spec_initial_mint(x: Wad, y: Wad) -> Result<Wad, AmmError> {
+    check_reserves(x, y)?;
+    let s = to_wad(initial_mint_exact(x, y))?;
+    if s == 0 { return Err(AmmError::InputTooSmall); }
+    Ok(s)
+}
+
+/// Add proporcional com guardas: `floor(min(dx*S/x, dy*S/y))`.
+pub fn spec_add_liquidity(x: Wad, y: Wad, dx: Wad, dy: Wad, total_shares: Wad) -> Result<Wad, AmmError> {
+    check_reserves(x, y)?;
+    if dx == 0 || dy == 0 { return Err(AmmError::ZeroAmount); }
+    if total_shares == 0 { return Err(AmmError::EmptyPool); }
+    let shares = floor_u128(&add_liquidity_exact(x, y, dx, dy, total_shares)).ok_or(AmmError::Overflow)?;
+    if shares == 0 { return Err(AmmError::InputTooSmall); }
+    x.checked_add(dx).ok_or(AmmError::Overflow)?;
+    y.checked_add(dy).ok_or(AmmError::Overflow)?;
+    Ok(shares)
+}
+
+/// Remove proporcional com guardas: `(floor(x*burn/S), floor(y*burn/S))`.
+pub fn spec_remove_liquidity(x: Wad, y: Wad, burn: Wad, total_shares: Wad) -> Result<(Wad, Wad), AmmError> {
+    check_reserves(x, y)?;
+    if burn == 0 { return Err(AmmError::ZeroAmount); }
+    if total_shares == 0 { return Err(AmmError::EmptyPool); }
+    if burn > total_shares { return Err(AmmError::InsufficientShares { requested: burn, available: total_shares }); }
+    let (ex, ey) = remove_liquidity_exact(x, y, burn, total_shares);
+    let (xo, yo) = (floor_u128(&ex).ok_or(AmmError::Overflow)?, floor_u128(&ey).ok_or(AmmError::Overflow)?);
+    if x - xo < MIN_RESERVE || y - yo < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    if xo == 0 && yo == 0 { return Err(AmmError::InputTooSmall); }
+    Ok((xo, yo))
+}
+
+// --------- Liquidez concentrada (Q64.96) ---------
+// Preços raiz e resultados como `BigInt`; as frações são racionais exatos com floor/ceil na
+// fronteira, sem os intermediários de 256/512 bits do core.
+
+fn q96() -> BigInt { BigInt::one() << 96 }
+
+fn to_u256_range(v: BigInt) -> Result<BigInt, AmmError> {
+    if v >= BigInt::one() << 256 { Err(AmmError::Overflow) } else { Ok(v) }
+}
+
+/// `sqrt_ratio_at_tick` com as mesmas constantes da v3, em BigInt.
+pub fn spec_sqrt_ratio_at_tick(tick: i32) -> Result<BigInt, AmmError> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) { return Err(AmmError::InvalidTick { tick }); }
+    let abs = tick.unsigned_abs();
+    let mut r = if abs & 1 != 0 { BigInt::from(TICK_RATIOS[0]) } else { BigInt::one() << 128 };
+    for (k, c) in TICK_RATIOS.iter().enumerate().skip(1) {
+        if abs & (1 << k) != 0 { r = (r * BigInt::from(*c)) >> 128; }
+    }
+    if tick > 0 { r = ((BigInt::one() << 256) - BigInt::one()) / r; }
+    Ok(r.div_ceil(&(BigInt::one() << 32)))
+}
+
+/// `floor(sqrt(1.0001^tick)·2^96)` calculado direto da definição, em ponto fixo de 512 bits:
+/// não usa as constantes da v3 e serve para medir o erro de `spec_sqrt_ratio_at_tick`.
+pub fn sqrt_ratio_at_tick_exact(tick: i32) -> BigInt {
+    const S: u32 = 512;
+    let (mut acc, mut base) = (BigInt::one() << S, (BigInt::from(10_001) << S) / BigInt::from(10_000));
+    let mut e = tick.unsigned_abs();
+    while e > 0 {
+        if e & 1 != 0 { acc = (acc * &base) >> S; }
+        base = (&base * &base) >> S;
+        e >>= 1;
+    }
+    if tick < 0 { acc = (BigInt::one() << (2 * S)) / acc; }
+    isqrt(&((acc << 192) >> S))
+}
+
+/// Maior tick com `spec_sqrt_ratio_at_tick(tick) <= sqrt_p`.
+pub fn spec_tick_at_sqrt_ratio(sqrt_p: &BigInt) -> Result<i32, AmmError> {
+    if *sqrt_p < spec_sqrt_ratio_at_tick(MIN_TICK)? || *sqrt_p >= spec_sqrt_ratio_at_tick(MAX_TICK)? {
+        return Err(AmmError::InvalidSqrtPrice);
+    }
+    let (mut lo, mut hi) = (MIN_TICK, MAX_TICK);
+    while lo < hi {
+        let mid = lo + (hi - lo + 1) / 2;
+        if spec_sqrt_ratio_at_tick(mid)? <= *sqrt_p { lo = mid; } else { hi = mid - 1; }
+    }
+    Ok(lo)
+}
+
+fn round_q(v: &Q, up: bool) -> BigInt { if up { v.ceil().to_integer() } else { v.floor().to_integer() } }
+
+/// `Δx = L·2^96·(b - a)/(a·b)`, floor ou ceil.
+pub fn spec_amount0_delta(a: &BigInt, b: &BigInt, liquidity: u128, round_up: bool) -> Result<Wad, AmmError> {
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    if a.is_zero() { return Err(AmmError::InvalidSqrtPrice); }
+    to_wad(round_q(&ratio(int(liquidity) * q96() * (b - a), a * b), round_up))
+}
+
+/// `Δy = L·(b - a)/2^96`, floor ou ceil.
+pub fn spec_amount1_delta(a: &BigInt, b: &BigInt, liquidity: u128, round_up: bool) -> Result<Wad, AmmError> {
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    to_wad(round_q(&ratio(int(liquidity) * (b - a), q96()), round_up))
+}
+
+/// Próximo preço raiz após `amount` de input líquido (`zero_for_one`: token0 entra).
+pub fn spec_next_sqrt_from_input(p: &BigInt, liquidity: u128, amount: Wad, zero_for_one: bool) -> Result<BigInt, AmmError> {
+    if p.is_zero() || liquidity == 0 { return Err(AmmError::InsufficientLiquidity { requested: amount, available: 0 }); }
+    let lq = int(liquidity) * q96();
+    if zero_for_one {
+        if amount == 0 { return Ok(p.clone()); }
+        to_u256_range(ratio(&lq * p, &lq + int(amount) * p).ceil().to_integer())
+    } else {
+        to_u256_range(p + ratio(int(amount) * q96(), int(liquidity)).floor().to_integer())
+    }
+}
+
+/// Próximo preço raiz após `amount` de output (`zero_for_one`: token1 sai).
+pub fn spec_next_sqrt_from_output(p: &BigInt, liquidity: u128, amount: Wad, zero_for_one: bool) -> Result<BigInt, AmmError> {
+    if p.is_zero() || liquidity == 0 { return Err(AmmError::InsufficientLiquidity { requested: amount, available: 0 }); }
+    let lq = int(liquidity) * q96();
+    let drained = AmmError::InsufficientLiquidity { requested: amount, available: 0 };
+    if zero_for_one {
+        let d = ratio(int(amount) * q96(), int(liquidity)).ceil().to_integer();
+        if d >= *p { return Err(drained); }
+        Ok(p - d)
+    } else {
+        if amount == 0 { return Ok(p.clone()); }
+        let product = int(amount) * p;
+        if product >= lq { return Err(drained); }
+        to_u256_range(ratio(&lq * p, lq - product).ceil().to_integer())
+    }
+}
+
+/// Um passo de swap (`compute_swap_step`): `(preço final, input líquido, output, taxa)`.
+pub fn spec_swap_step(
+    current: &BigInt,
+    target: &BigInt,
+    liquidity: u128,
+    remaining: Wad,
+    exact_in: bool,
+    fee_ppm: Ppm,
+) -> Result<(BigInt, Wad, Wad, Wad), AmmError> {
+    check_fee(fee_ppm)?;
+    let zero_for_one = current >= target;
+    let keep = BigInt::from(PPM_SCALE - fee_ppm);
+    let delta_in = |a: &BigInt, b: &BigInt| if zero_for_one { spec_amount0_delta(a, b, liquidity, true) } else { spec_amount1_delta(a, b, liquidity, true) };
+    let delta_out = |a: &BigInt, b: &BigInt| if zero_for_one { spec_amount1_delta(a, b, liquidity, false) } else { spec_amount0_delta(a, b, liquidity, false) };
+
+    let (next, full_in, full_out) = if exact_in {
+        let less_fee = to_wad(int(remaining) * &keep / BigInt::from(PPM_SCALE))?;
+        let to_target = delta_in(target, current)?;
+        if less_fee >= to_target { (target.clone(), Some(to_target), None) } else { (spec_next_sqrt_from_input(current, liquidity, less_fee, zero_for_one)?, None, None) }
+    } else {
+        let to_target = delta_out(target, current)?;
+        if remaining >= to_target { (target.clone(), None, Some(to_target)) } else { (spec_next_sqrt_from_output(current, liquidity, remaining, zero_for_one)?, None, None) }
+    };
+    let amount_in = match full_in { Some(v) => v, None => delta_in(&next, current)? };
+    let mut amount_out = match full_out { Some(v) => v, None => delta_out(&next, current)? };
+    if !exact_in { amount_out = amount_out.min(remaining); }
+    let fee = if exact_in && next != *target {
+        remaining.checked_sub(amount_in).ok_or(AmmError::Overflow)?
+    } else {
+        to_wad((int(amount_in) * BigInt::from(fee_ppm)).div_ceil(&keep))?
+    };
+    Ok((next, amount_in, amount_out, fee))
+}
+
+// --------- Pool concentrado (sequências mint/swap/collect) ---------
+// Estado mínimo: preço raiz, lista de posições e saldos. A liquidez ativa e o fee growth de cada
+// posição saem direto das faixas a cada passo (sem `liquidity_net`, `fee_growth_outside` nem ticks
+// inicializados), então a contabilidade por ticks do `ConcentratedPool` é checada por outro caminho.
+// Convenção de borda: descendo, a faixa está ativa em `(sqrtP(lower), sqrtP(upper)]`; subindo, em
+// `[sqrtP(lower), sqrtP(upper))` — passos de largura zero na borda não movem tokens nem taxas.
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct RefClPosition {
+    owner: AccountId,
+    lower: i32,
+    upper: i32,
+    liquidity: u128,
+    /// Soma exata dos `floor(taxa·2^128 / L_ativa)` dos passos com a faixa ativa, desde a última atualização
+    growth_x: BigInt,
+    growth_y: BigInt,
+    owed_x: Wad,
+    owed_y: Wad,
+}
+
+/// Modelo de referência do pool concentrado; mesmas guardas e erros do core.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefClPool {
+    fee_ppm: Ppm,
+    tick_spacing: i32,
+    sqrt_p: BigInt,
+    positions: Vec<RefClPosition>,
+    balance_x: Wad,
+    balance_y: Wad,
+}
+
+/// Resultado de um swap no modelo: `(input bruto, output, taxa, preço raiz final)`.
+pub type RefClSwap = (Wad, Wad, Wad, BigInt);
+
+impl RefClPool {
+    pub fn new(sqrt_p: BigInt, fee_ppm: Ppm, tick_spacing: i32) -> Result<Self, AmmError> {
+        check_fee(fee_ppm)?;
+        if !(1..=MAX_TICK_SPACING).contains(&tick_spacing) { return Err(AmmError::InvalidTickSpacing { spacing: tick_spacing }); }
+        spec_tick_at_sqrt_ratio(&sqrt_p)?;
+        Ok(Self { fee_ppm, tick_spacing, sqrt_p, positions: Vec::new(), balance_x: 0, balance_y: 0 })
+    }
+
+    pub fn sqrt_price(&self) -> &BigInt { &self.sqrt_p }
+    pub fn tick(&self) -> Result<i32, AmmError> { spec_tick_at_sqrt_ratio(&self.sqrt_p) }
+    /// Tokens no pool `(x, y)`.
+    pub fn balances(&self) -> (Wad, Wad) { (self.balance_x, self.balance_y) }
+
+    fn find(&self, owner: AccountId, lower: i32, upper: i32) -> Option<usize> {
+        self.positions.iter().position(|p| (p.owner, p.lower, p.upper) == (owner, lower, upper))
+    }
+
+    fn check_range(&self, lower: i32, upper: i32) -> Result<(), AmmError> {
+        let aligned = lower % self.tick_spacing == 0 && upper % self.tick_spacing == 0;
+        if lower >= upper || lower < MIN_TICK || upper > MAX_TICK || !aligned { return Err(AmmError::InvalidTickRange { lower, upper }); }
+        Ok(())
+    }
+
+    /// Liquidez bruta de cada borda ≤ `u128::MAX / nº de ticks utilizáveis`.
+    fn check_gross(&self, ticks: [i32; 2]) -> Result<(), AmmError> {
+        let s = self.tick_spacing;
+        let max = u128::MAX / (((MAX_TICK / s) * s - (MIN_TICK / s) * s) / s + 1) as u128;
+        for t in ticks {
+            let gross = self.positions.iter().filter(|p| p.lower == t || p.upper == t)
+                .try_fold(0u128, |acc, p| acc.checked_add(p.liquidity)).ok_or(AmmError::Overflow)?;
+            if gross > max { return Err(AmmError::Overflow); }
+        }
+        Ok(())
+    }
+
+    /// Converte o growth pendente da posição em `tokens_owed` (floor sobre a liquidez antiga).
+    fn accrue(pos: &mut RefClPosition) -> Result<(), AmmError> {
+        let q128 = BigInt::one() << 128;
+        for (growth, owed) in [(&mut pos.growth_x, &mut pos.owed_x), (&mut pos.growth_y, &mut pos.owed_y)] {
+            let fees = to_wad(&*growth * int(pos.liquidity) / &q128)?;
+            *owed = owed.checked_add(fees).ok_or(AmmError::Overflow)?;
+            *growth = BigInt::zero();
+        }
+        Ok(())
+    }
+
+    /// Tokens da faixa para `liquidity`, pelo preço atual: abaixo só x, acima só y, dentro os dois.
+    fn range_amounts(&self, lower: i32, upper: i32, liquidity: u128, round_up: bool) -> Result<(Wad, Wad), AmmError> {
+        let (a, b) = (spec_sqrt_ratio_at_tick(lower)?, spec_sqrt_ratio_at_tick(upper)?);
+        let p = &self.sqrt_p;
+        if *p <= a {
+            Ok((spec_amount0_delta(&a, &b, liquidity, round_up)?, 0))
+        } else if *p < b {
+            Ok((spec_amount0_delta(p, &b, liquidity, round_up)?, spec_amount1_delta(&a, p, liquidity, round_up)?))
+        } else {
+            Ok((0, spec_amount1_delta(&a, &b, liquidity, round_up)?))
+        }
+    }
+
+    fn modify(&mut self, owner: AccountId, lower: i32, upper: i32, delta: i128) -> Result<(Wad, Wad), AmmError> {
+        self.check_range(lower, upper)?;
+        let i = match self.find(owner, lower, upper) {
+            Some(i) => i,
+            None => {
+                self.positions.push(RefClPosition { owner, lower, upper, ..Default::default() });
+                self.positions.len() - 1
+            }
+        };
+        let liquidity = self.positions[i].liquidity;
+        if delta == 0 && liquidity == 0 { return Err(AmmError::InsufficientShares { requested: 0, available: 0 }); }
+        if delta < 0 && delta.unsigned_abs() > liquidity {
+            return Err(AmmError::InsufficientShares { requested: delta.unsigned_abs(), available: liquidity });
+        }
+        Self::accrue(&mut self.positions[i])?;
+        self.positions[i].liquidity = liquidity.checked_add_signed(delta).ok_or(AmmError::Overflow)?;
+        if delta != 0 { self.check_gross([lower, upper])?; }
+        self.range_amounts(lower, upper, delta.unsigned_abs(), delta > 0)
+    }
+
+    /// Mint: `(x, y)` depositados (ceil).
+    pub fn mint(&mut self, owner: AccountId, lower: i32, upper: i32, liquidity: u128) -> Result<(Wad, Wad), AmmError> {
+        if liquidity == 0 { return Err(AmmError::ZeroAmount); }
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        let mut next = self.clone();
+        let (x, y) = next.modify(owner, lower, upper, delta)?;
+        next.balance_x = next.balance_x.checked_add(x).ok_or(AmmError::Overflow)?;
+        next.balance_y = next.balance_y.checked_add(y).ok_or(AmmError::Overflow)?;
+        *self = next;
+        Ok((x, y))
+    }
+
+    /// Burn: `(x, y)` creditados (floor) em `tokens_owed`.
+    pub fn burn(&mut self, owner: AccountId, lower: i32, upper: i32, liquidity: u128) -> Result<(Wad, Wad), AmmError> {
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::Overflow)?;
+        let mut next = self.clone();
+        let (x, y) = next.modify(owner, lower, upper, -delta)?;
+        let i = next.find(owner, lower, upper).ok_or(AmmError::Overflow)?;
+        let pos = &mut next.positions[i];
+        pos.owed_x = pos.owed_x.checked_add(x).ok_or(AmmError::Overflow)?;
+        pos.owed_y = pos.owed_y.checked_add(y).ok_or(AmmError::Overflow)?;
+        *self = next;
+        Ok((x, y))
+    }
+
+    /// Collect: paga tudo o que a posição tem a receber; posição vazia sai da lista.
+    pub fn collect(&mut self, owner: AccountId, lower: i32, upper: i32) -> Result<(Wad, Wad), AmmError> {
+        let mut next = self.clone();
+        let i = next.find(owner, lower, upper).ok_or(AmmError::InsufficientShares { requested: 0, available: 0 })?;
+        Self::accrue(&mut next.positions[i])?;
+        let (x, y) = (next.positions[i].owed_x, next.positions[i].owed_y);
+        next.balance_x = next.balance_x.checked_sub(x).ok_or(AmmError::Overflow)?;
+        next.balance_y = next.balance_y.checked_sub(y).ok_or(AmmError::Overflow)?;
+        if next.positions[i].liquidity == 0 {
+            next.positions.remove(i);
+        } else {
+            next.positions[i].owed_x = 0;
+            next.positions[i].owed_y = 0;
+        }
+        *self = next;
+        Ok((x, y))
+    }
+
+    /// A faixa da posição conta para um passo que parte de `p` no sentido indicado?
+    fn active(pos: &RefClPosition, p: &BigInt, zero_for_one: bool) -> Result<bool, AmmError> {
+        if pos.liquidity == 0 { return Ok(false); }
+        let (a, b) = (spec_sqrt_ratio_at_tick(pos.lower)?, spec_sqrt_ratio_at_tick(pos.upper)?);
+        Ok(if zero_for_one { a < *p && *p <= b } else { a <= *p && *p < b })
+    }
+
+    /// Próxima borda de faixa estritamente além de `p` no sentido do swap, limitada por `limit`.
+    fn next_target(&self, zero_for_one: bool, limit: &BigInt) -> Result<BigInt, AmmError> {
+        let mut target = limit.clone();
+        for pos in self.positions.iter().filter(|p| p.liquidity > 0) {
+            for t in [pos.lower, pos.upper] {
+                let s = spec_sqrt_ratio_at_tick(t)?;
+                let beyond = if zero_for_one { s < self.sqrt_p && s > target } else { s > self.sqrt_p && s < target };
+                if beyond { target = s; }
+            }
+        }
+        Ok(target)
+    }
+
+    fn swap(&mut self, dir: Direction, amount: Wad, exact_in: bool) -> Result<RefClSwap, AmmError> {
+        if amount == 0 { return Err(AmmError::ZeroAmount); }
+        let zero_for_one = dir == Direction::XtoY;
+        let limit = if zero_for_one {
+            spec_sqrt_ratio_at_tick(MIN_TICK)? + BigInt::one()
+        } else {
+            spec_sqrt_ratio_at_tick(MAX_TICK)? - BigInt::one()
+        };
+        let (mut remaining, mut amount_in, mut amount_out, mut fee) = (amount, 0u128, 0u128, 0u128);
+        let add = |a: Wad, b: Wad| a.checked_add(b).ok_or(AmmError::Overflow);
+
+        while remaining > 0 && self.sqrt_p != limit {
+            let target = self.next_target(zero_for_one, &limit)?;
+            let mut liquidity = 0u128;
+            let mut active = Vec::new();
+            for (i, pos) in self.positions.iter().enumerate() {
+                if Self::active(pos, &self.sqrt_p, zero_for_one)? {
+                    liquidity = add(liquidity, pos.liquidity)?;
+                    active.push(i);
+                }
+            }
+            let (next, step_in, step_out, step_fee) = spec_swap_step(&self.sqrt_p, &target, liquidity, remaining, exact_in, self.fee_ppm)?;
+            let gross = add(step_in, step_fee)?;
+            remaining = remaining.checked_sub(if exact_in { gross } else { step_out }).ok_or(AmmError::Overflow)?;
+            amount_in = add(amount_in, gross)?;
+            amount_out = add(amount_out, step_out)?;
+            fee = add(fee, step_fee)?;
+            if liquidity > 0 {
+                let growth = (int(step_fee) << 128) / int(liquidity);
+                for i in active {
+                    let pos = &mut self.positions[i];
+                    if zero_for_one { pos.growth_x += &growth; } else { pos.growth_y += &growth; }
+                }
+            }
+            self.sqrt_p = next;
+        }
+
+        if remaining > 0 {
+            let available = if exact_in { amount_in } else { amount_out };
+            return Err(AmmError::InsufficientLiquidity { requested: amount, available });
+        }
+        let (bx, by) = (self.balance_x, self.balance_y);
+        let (bx, by) = match dir {
+            Direction::XtoY => (bx.checked_add(amount_in), by.checked_sub(amount_out)),
+            Direction::YtoX => (bx.checked_sub(amount_out), by.checked_add(amount_in)),
+        };
+        self.balance_x = bx.ok_or(AmmError::Overflow)?;
+        self.balance_y = by.ok_or(AmmError::Overflow)?;
+        Ok((amount_in, amount_out, fee, self.sqrt_p.clone()))
+    }
+
+    /// Swap com input exato (bruto); output zero é `InputTooSmall`.
+    pub fn swap_exact_in(&mut self, dir: Direction, amount_in: Wad) -> Result<RefClSwap, AmmError> {
+        let mut next = self.clone();
+        let r = next.swap(dir, amount_in, true)?;
+        if r.1 == 0 { return Err(AmmError::InputTooSmall); }
+        *self = next;
+        Ok(r)
+    }
+
+    /// Swap com output exato.
+    pub fn swap_exact_out(&mut self, dir: Direction, amount_out: Wad) -> Result<RefClSwap, AmmError> {
+        let mut next = self.clone();
+        let r = next.swap(dir, amount_out, false)?;
+        *self = next;
+        Ok(r)
+    }
+}
+
+fn check_fee(fee_ppm: Ppm) -> Result<(), AmmError> {
+    if fee_ppm >= PPM_SCALE { Err(AmmError::FeeTooHigh { fee_ppm }) } else { Ok(()) }
+}
+
+fn check_reserves(x: Wad, y: Wad) -> Result<(), AmmError> {
+    if x == 0 || y == 0 { return Err(AmmError::ZeroReserve); }
+    if x < MIN_RESERVE || y < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    Ok(())
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_sqrt_ratio_at_tick_close_to_definition() {
+        // as constantes da v3 aproximam sqrt(1.0001^t)·2^96 com erro relativo < 2^-63 (+1 do ceil)
+        for t in (MIN_TICK..=MAX_TICK).step_by(997).chain([MIN_TICK, -1, 0, 1, MAX_TICK]) {
+            let (spec, exact) = (spec_sqrt_ratio_at_tick(t).unwrap(), sqrt_ratio_at_tick_exact(t));
+            assert!((&spec - &exact).abs() <= BigInt::one() + (&exact >> 63), "t={} spec={} exact={}", t, spec, exact);
+        }
+    }
+
+    #[test]
+    fn t_round_half_even() {
+        assert_eq!(round_half_even_u128(&ratio(int(5), int(2))), Some(2));
+        assert_eq!(round_half_even_u128(&ratio(int(3), int(2))), Some(2));
+        assert_eq!(round_half_even_u128(&ratio(int(7), int(3))), Some(2));
+        assert_eq!(floor_u128(&ratio(int(7), int(3))), Some(2));
+        assert_eq!(ceil_u128(&ratio(int(7), int(3))), Some(3));
+    }
+
+    #[test]
+    fn t_weighted_ratio_out_of_reach_is_an_error() {
+        // 1/3 : 2/3 em WAD não reduz a denominador pequeno: erro, não pânico
+        let (w_in, w_out) = (333_333_333_333_333_333, 666_666_666_666_666_667);
+        assert_eq!(weight_ratio(w_in, w_out), None);
+        assert_eq!(ref_weighted_amount_out(1000 * WAD, w_in, 1000 * WAD, w_out, WAD, 3000), Err(AmmError::InvalidWeights));
+        assert_eq!(ref_weighted_amount_in(1000 * WAD, w_in, 1000 * WAD, w_out, WAD, 3000), Err(AmmError::InvalidWeights));
+    }
+
+    #[test]
+    fn t_adr_worked_examples() {
+        // ADR-0002 E1/E2/E3 em escala WAD (E2 contínuo = 9_871.58…)
+        let (x, y) = (1_000_000u128 * WAD, 1_000_000u128 * WAD);
+        assert_eq!(ref_amount_out(x, y, 10_000u128 * WAD, 0).unwrap() / WAD, 9_900);
+        assert_eq!(ref_amount_out(x, y, 10_000u128 * WAD, 3000).unwrap() / WAD, 9_871);
+        let dx = ref_amount_in(x, y, 9_870u128 * WAD, 3000).unwrap();
+        assert!(ref_amount_out(x, y, dx, 3000).unwrap() >= 9_870u128 * WAD);
+        assert!(ref_amount_out(x, y, dx - 1, 3000).unwrap() < 9_870u128 * WAD);
+    }
+
+    #[test]
+    fn t_initial_mint_exact_floor_sqrt() {
+        assert_eq!(initial_mint_exact(4, 9), int(6));
+        assert_eq!(initial_mint_exact(2, 4), int(2));
+    }
+}
@@ -0,0 +1,328 @@
+//! Roteador multi-hop sobre um grafo de pools CPMM (`Pool`), indexados pelo par de tokens.
+//! Enumera os caminhos simples de até `max_hops` pools (sem repetir token) e escolhe:
+//! - exact-in: o maior output; exact-out: o menor input bruto;
+//! - empate: menos hops e, depois, a ordem dos ids de token (determinístico).
+//!
+//! Cada hop é simulado num clone do pool com o próprio `swap_exact_in`/`swap_exact_out`, então a
+//! cotação já inclui taxa, protocolo, mínimo de reserva e o arredondamento do ADR-0001 — executar
+//! a rota devolve exatamente os valores cotados. Como nenhum par se repete num caminho simples,
+//! os hops são independentes.
+//!
+//! Preços da rota (WAD, nearest-even como em `pricing.rs`): `spot` é o produto dos spots dos hops,
+//! `execution_price = out/in` e `slippage_ppm` compara os dois (`slippage_ppm_from_prices`).
+
+use std::collections::BTreeMap;
+
+use super::errors::AmmError;
+use super::guardrails::{div_nearest_even_u256_to_u128, ensure_nonzero, mul_u128_to_u256};
+use super::pool::Pool;
+use super::pricing::{slippage_ppm_from_prices, spot_price_dir};
+use super::types::{Direction, Ppm, U256, Wad, WAD};
+
+/// Identificador de token no grafo.
+pub type TokenId = u32;
+/// Maior número de hops aceito numa busca.
+pub const MAX_HOPS: usize = 4;
+
+/// Uma perna da rota (um pool).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hop {
+    pub token_in: TokenId,
+    pub token_out: TokenId,
+    /// Sentido no pool do par (`x` = primeiro token do registro)
+    pub direction: Direction,
+    /// Input bruto (inclui a taxa)
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+    pub fee: Wad,
+}
+
+/// Rota cotada ou executada.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub hops: Vec<Hop>,
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+    /// Produto dos spots dos hops: 1 `token_in` em `token_out` (WAD)
+    pub spot_price: Wad,
+    /// `amount_out / amount_in` (WAD)
+    pub execution_price: Wad,
+    pub slippage_ppm: Ppm,
+}
+
+impl Route {
+    /// Tokens visitados, do input ao output.
+    pub fn path(&self) -> Vec<TokenId> {
+        let mut path: Vec<TokenId> = self.hops.iter().map(|h| h.token_in).collect();
+        path.extend(self.hops.last().map(|h| h.token_out));
+        path
+    }
+}
+
+/// Aresta do grafo: pool `key` percorrido em `direction`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Edge {
+    pub(crate) key: (TokenId, TokenId),
+    pub(crate) direction: Direction,
+    pub(crate) token_in: TokenId,
+    pub(crate) token_out: TokenId,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Router {
+    pools: BTreeMap<(TokenId, TokenId), Pool>,
+}
+
+impl Router {
+    pub fn new() -> Self { Self::default() }
+
+    /// Registra o pool do par (`token_x` = reserva X, `token_y` = reserva Y); substitui o pool
+    /// existente do mesmo par (em qualquer ordem) e devolve o anterior.
+    pub fn add_pool(&mut self, token_x: TokenId, token_y: TokenId, pool: Pool) -> Result<Option<Pool>, AmmError> {
+        if token_x == token_y { return Err(AmmError::InvalidPair { token: token_x }); }
+        let old = self.pools.remove(&(token_x, token_y)).or_else(|| self.pools.remove(&(token_y, token_x)));
+        self.pools.insert((token_x, token_y), pool);
+        Ok(old)
+    }
+
+    /// Pool do par, em qualquer ordem.
+    pub fn pool(&self, a: TokenId, b: TokenId) -> Option<&Pool> {
+        self.pools.get(&(a, b)).or_else(|| self.pools.get(&(b, a)))
+    }
+
+    /// Pools registrados com o par `(x, y)` de cada um.
+    pub fn pools(&self) -> impl Iterator<Item = ((TokenId, TokenId), &Pool)> { self.pools.iter().map(|(k, p)| (*k, p)) }
+
+    fn edges_from(&self, token: TokenId) -> Vec<Edge> {
+        let mut out: Vec<Edge> = self.pools.keys().filter_map(|&(x, y)| {
+            if x == token { Some(Edge { key: (x, y), direction: Direction::XtoY, token_in: x, token_out: y }) }
+            else if y == token { Some(Edge { key: (x, y), direction: Direction::YtoX, token_in: y, token_out: x }) }
+            else { None }
+        }).collect();
+        out.sort_by_key(|e| e.token_out);
+        out
+    }
+
+    /// Caminhos simples de `from` a `to` com até `max_hops` arestas, dos mais curtos para os mais longos.
+    pub(crate) fn paths(&self, from: TokenId, to: TokenId, max_hops: usize) -> Vec<Vec<Edge>> {
+        fn dfs(r: &Router, at: TokenId, to: TokenId, left: usize, stack: &mut Vec<Edge>, seen: &mut Vec<TokenId>, out: &mut Vec<Vec<Edge>>) {
+            if left == 0 { return; }
+            for e in r.edges_from(at) {
+                if seen.contains(&e.token_out) { continue; }
+                stack.push(e);
+                if e.token_out == to {
+                    out.push(stack.clone());
+                } else {
+                    seen.push(e.token_out);
+                    dfs(r, e.token_out, to, left - 1, stack, seen, out);
+                    seen.pop();
+                }
+                stack.pop();
+            }
+        }
+        let mut out = Vec::new();
+        if from != to { dfs(self, from, to, max_hops.min(MAX_HOPS), &mut Vec::new(), &mut vec![from], &mut out); }
+        out.sort_by_key(Vec::len);
+        out
+    }
+
+    pub(crate) fn simulate_exact_in(&self, path: &[Edge], amount_in: Wad) -> Result<Vec<Hop>, AmmError> {
+        let mut amount = amount_in;
+        let mut hops = Vec::with_capacity(path.len());
+        for e in path {
+            let r = self.pools[&e.key].clone().swap_exact_in(e.direction, amount, 0)?;
+            hops.push(Hop { token_in: e.token_in, token_out: e.token_out, direction: e.direction, amount_in: amount, amount_out: r.amount_out, fee: r.fee });
+            amount = r.amount_out;
+        }
+        Ok(hops)
+    }
+
+    fn simulate_exact_out(&self, path: &[Edge], amount_out: Wad) -> Result<Vec<Hop>, AmmError> {
+        let mut amount = amount_out;
+        let mut hops = Vec::with_capacity(path.len());
+        for e in path.iter().rev() {
+            let r = self.pools[&e.key].clone().swap_exact_out(e.direction, amount, u128::MAX)?;
+            hops.push(Hop { token_in: e.token_in, token_out: e.token_out, direction: e.direction, amount_in: r.amount_in, amount_out: amount, fee: r.fee });
+            amount = r.amount_in;
+        }
+        hops.reverse();
+        Ok(hops)
+    }
+
+    /// Monta a `Route` de `token_in` para `token_out` a partir dos hops simulados; lista vazia é `NoRoute`.
+    pub(crate) fn route(&self, token_in: TokenId, token_out: TokenId, hops: Vec<Hop>) -> Result<Route, AmmError> {
+        let no_route = AmmError::NoRoute { token_in, token_out };
+        let (Some(first), Some(last)) = (hops.first(), hops.last()) else { return Err(no_route) };
+        let (amount_in, amount_out) = (first.amount_in, last.amount_out);
+        let mut spot = WAD;
+        for h in &hops {
+            let r = self.pool(h.token_in, h.token_out).ok_or(no_route.clone())?.reserves();
+            let s = spot_price_dir(h.direction, r.x, r.y)?;
+            spot = div_nearest_even_u256_to_u128(mul_u128_to_u256(spot, s), U256::from(WAD))?;
+        }
+        let execution_price = div_nearest_even_u256_to_u128(mul_u128_to_u256(amount_out, WAD), U256::from(amount_in))?;
+        let slippage_ppm = slippage_ppm_from_prices(spot, execution_price)?;
+        Ok(Route { hops, amount_in, amount_out, spot_price: spot, execution_price, slippage_ppm })
+    }
+
+    /// Melhor candidato segundo `better`; sem candidato viável devolve o erro do caminho mais curto.
+    fn best(
+        &self,
+        token_in: TokenId,
+        token_out: TokenId,
+        max_hops: usize,
+        simulate: impl Fn(&[Edge]) -> Result<Vec<Hop>, AmmError>,
+        better: impl Fn(&[Hop], &[Hop]) -> bool,
+    ) -> Result<Route, AmmError> {
+        let mut best: Option<Vec<Hop>> = None;
+        let mut first_err = None;
+        for path in self.paths(token_in, token_out, max_hops) {
+            match simulate(&path) {
+                Ok(hops) => if best.as_deref().is_none_or(|b| better(&hops, b)) { best = Some(hops); },
+                Err(e) => { first_err.get_or_insert(e); }
+            }
+        }
+        match (best, first_err) {
+            (Some(hops), _) => self.route(token_in, token_out, hops),
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(AmmError::NoRoute { token_in, token_out }),
+        }
+    }
+
+    /// Cota a melhor rota para vender exatamente `amount_in` de `token_in` (maior output).
+    pub fn quote_exact_in(&self, token_in: TokenId, token_out: TokenId, amount_in: Wad, max_hops: usize) -> Result<Route, AmmError> {
+        ensure_nonzero(amount_in)?;
+        // estrito: em empate fica o candidato anterior (menos hops)
+        let out = |h: &[Hop]| h.last().map_or(0, |l| l.amount_out);
+        self.best(token_in, token_out, max_hops, |p| self.simulate_exact_in(p, amount_in), |a, b| out(a) > out(b))
+    }
+
+    /// Cota a melhor rota para receber exatamente `amount_out` de `token_out` (menor input bruto).
+    pub fn quote_exact_out(&self, token_in: TokenId, token_out: TokenId, amount_out: Wad, max_hops: usize) -> Result<Route, AmmError> {
+        ensure_nonzero(amount_out)?;
+        let inp = |h: &[Hop]| h.first().map_or(u128::MAX, |f| f.amount_in);
+        self.best(token_in, token_out, max_hops, |p| self.simulate_exact_out(p, amount_out), |a, b| inp(a) < inp(b))
+    }
+
+    /// Aplica os hops aos pools (atômico: nada muda se algum hop falhar).
+    pub(crate) fn execute(&mut self, route: &Route, exact_in: bool) -> Result<(), AmmError> {
+        let mut pools = self.pools.clone();
+        for h in &route.hops {
+            let key = if pools.contains_key(&(h.token_in, h.token_out)) { (h.token_in, h.token_out) } else { (h.token_out, h.token_in) };
+            let pool = pools.get_mut(&key).ok_or(AmmError::NoRoute { token_in: h.token_in, token_out: h.token_out })?;
+            if exact_in { pool.swap_exact_in(h.direction, h.amount_in, h.amount_out)?; } else { pool.swap_exact_out(h.direction, h.amount_out, h.amount_in)?; }
+        }
+        self.pools = pools;
+        Ok(())
+    }
+
+    /// Executa a melhor rota exact-in; exige output ≥ `min_out`.
+    pub fn swap_exact_in(&mut self, token_in: TokenId, token_out: TokenId, amount_in: Wad, min_out: Wad, max_hops: usize) -> Result<Route, AmmError> {
+        let route = self.quote_exact_in(token_in, token_out, amount_in, max_hops)?;
+        if route.amount_out < min_out { return Err(AmmError::SlippageExceeded { expected: min_out, actual: route.amount_out }); }
+        self.execute(&route, true)?;
+        Ok(route)
+    }
+
+    /// Executa a melhor rota exact-out; exige input bruto ≤ `max_in`.
+    pub fn swap_exact_out(&mut self, token_in: TokenId, token_out: TokenId, amount_out: Wad, max_in: Wad, max_hops: usize) -> Result<Route, AmmError> {
+        let route = self.quote_exact_out(token_in, token_out, amount_out, max_hops)?;
+        if route.amount_in > max_in { return Err(AmmError::SlippageExceeded { expected: max_in, actual: route.amount_in }); }
+        self.execute(&route, false)?;
+        Ok(route)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::swap::get_amount_out;
+
+    const A: TokenId = 1;
+    const B: TokenId = 2;
+    const C: TokenId = 3;
+    const D: TokenId = 4;
+
+    fn pool(x: u128, y: u128) -> Pool { Pool::new(x * WAD, y * WAD, 3_000).unwrap() }
+
+    /// A-B raso; A-C-B profundo (melhor para ordens grandes); D isolado de B.
+    fn router() -> Router {
+        let mut r = Router::new();
+        r.add_pool(A, B, pool(1_000, 1_000)).unwrap();
+        r.add_pool(A, C, pool(1_000_000, 1_000_000)).unwrap();
+        r.add_pool(B, C, pool(1_000_000, 1_000_000)).unwrap();
+        r.add_pool(D, A, pool(1_000, 2_000)).unwrap();
+        r
+    }
+
+    #[test]
+    fn t_direct_vs_two_hops() {
+        let r = router();
+        // pequeno: direto ganha (uma taxa só)
+        let small = r.quote_exact_in(A, B, WAD, 3).unwrap();
+        assert_eq!(small.path(), vec![A, B]);
+        assert_eq!(small.amount_out, get_amount_out(1_000 * WAD, 1_000 * WAD, WAD, 3_000).unwrap());
+        // grande: o caminho profundo tem menos impacto
+        let big = r.quote_exact_in(A, B, 500 * WAD, 3).unwrap();
+        assert_eq!(big.path(), vec![A, C, B]);
+        assert_eq!(big.hops[0].amount_out, big.hops[1].amount_in);
+        assert!(big.slippage_ppm < 10_000);
+        // limitado a 1 hop, só resta o direto
+        assert_eq!(r.quote_exact_in(A, B, 500 * WAD, 1).unwrap().path(), vec![A, B]);
+    }
+
+    #[test]
+    fn t_exact_out_minimizes_input() {
+        let r = router();
+        let q = r.quote_exact_out(A, B, 300 * WAD, 3).unwrap();
+        assert_eq!(q.path(), vec![A, C, B]);
+        assert_eq!(q.amount_out, 300 * WAD);
+        // o input cotado, vendido exact-in pela mesma rota, entrega pelo menos o pedido
+        let back = r.quote_exact_in(A, B, q.amount_in, 3).unwrap();
+        assert!(back.amount_out >= 300 * WAD);
+    }
+
+    #[test]
+    fn t_reverse_direction_and_prices() {
+        let r = router();
+        let q = r.quote_exact_in(B, D, WAD, 3).unwrap();
+        assert_eq!(q.path(), vec![B, A, D]);
+        assert_eq!(q.hops[1].direction, Direction::YtoX); // pool registrado como (D, A)
+        // spot B→A→D ≈ 1 · 0,5
+        assert_eq!(q.spot_price, WAD / 2);
+        assert!(q.execution_price < q.spot_price);
+    }
+
+    #[test]
+    fn t_no_route_and_errors() {
+        let mut r = router();
+        r.add_pool(10, 11, pool(1_000, 1_000)).unwrap();
+        assert_eq!(r.quote_exact_in(A, 10, WAD, 4).unwrap_err(), AmmError::NoRoute { token_in: A, token_out: 10 });
+        assert_eq!(r.quote_exact_in(A, A, WAD, 4).unwrap_err(), AmmError::NoRoute { token_in: A, token_out: A });
+        assert_eq!(r.quote_exact_in(A, B, WAD, 0).unwrap_err(), AmmError::NoRoute { token_in: A, token_out: B });
+        assert_eq!(r.add_pool(A, A, pool(1, 1)).unwrap_err(), AmmError::InvalidPair { token: A });
+        assert_eq!(r.route(A, B, Vec::new()).unwrap_err(), AmmError::NoRoute { token_in: A, token_out: B });
+        // substituição em ordem invertida
+        assert!(r.add_pool(B, A, pool(2_000, 2_000)).unwrap().is_some());
+        assert!(r.pool(A, B).is_some());
+        // pedido acima de qualquer liquidez: erro do caminho mais curto
+        assert!(matches!(r.quote_exact_out(10, 11, 1_000 * WAD, 4), Err(AmmError::InsufficientLiquidity { .. } | AmmError::MinReserveBreached)));
+    }
+
+    #[test]
+    fn t_execute_matches_quote_and_is_atomic() {
+        let mut r = router();
+        let q = r.quote_exact_in(A, B, 500 * WAD, 3).unwrap();
+        let before = r.clone();
+        assert!(matches!(r.swap_exact_in(A, B, 500 * WAD, q.amount_out + 1, 3), Err(AmmError::SlippageExceeded { .. })));
+        assert_eq!(r, before);
+        assert_eq!(r.swap_exact_in(A, B, 500 * WAD, q.amount_out, 3).unwrap(), q);
+        assert_eq!(r.pool(A, C).unwrap().reserves().x, 1_000_000 * WAD + 500 * WAD);
+
+        let q = r.quote_exact_out(B, A, 10 * WAD, 3).unwrap();
+        assert_eq!(r.swap_exact_out(B, A, 10 * WAD, q.amount_in, 3).unwrap(), q);
+    }
+}
@@ -0,0 +1,415 @@
+//! Ciclo de vida de um mercado de outcome: `Open → Halted → Resolved → Settled`.
+//! Envolve `OutcomeMarket` com ledgers por conta (posições de holders e shares de LP), uma fonte de
+//! resolução plugável (`ResolutionSource`) e um log de eventos (uma entrada por operação bem-sucedida:
+//! negociação, liquidez, transição de fase ou pagamento).
+//! O `OutcomeMarket` interno fica congelado a partir de `halt`; a contabilidade pós-resolução é daqui.
+//!
+//! Payout (ADR-0001: floor, a favor do mercado):
+//! - vetor `Payout { yes, no }` em WAD com `yes + no = WAD` (vencedor único = (1, 0); inválido = (½, ½));
+//! - holder: `floor((yes·p_yes + no·p_no) / WAD)`;
+//! - LP: reservas do pool avaliadas no payout, pro rata por shares (floor); o último LP leva o
+//!   colateral restante (inclui a poeira dos floors) depois de reservar os claims dos holders.
+//!
+//! Como `yes_total = no_total = colateral`, a soma de todos os claims nunca passa do colateral.
+
+use std::collections::BTreeMap;
+
+use super::errors::AmmError;
+use super::guardrails::{checked_add, checked_sub, u256_to_u128_checked};
+use super::outcome::{FundingReceipt, Outcome, OutcomeMarket, OutcomeTrade};
+use super::types::{AccountId, Ppm, Reserves, U256, Wad, WAD};
+
+pub use super::types::MarketPhase;
+
+/// Quanto cada token paga em colateral (WAD), com `yes + no = WAD`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Payout { pub yes: Wad, pub no: Wad }
+impl Payout {
+    pub fn new(yes: Wad, no: Wad) -> Result<Self, AmmError> {
+        if yes.checked_add(no) != Some(WAD) { return Err(AmmError::InvalidPayout { yes, no }); }
+        Ok(Self { yes, no })
+    }
+    /// Vencedor único: paga 1 por token vencedor.
+    pub fn winner(outcome: Outcome) -> Self {
+        match outcome { Outcome::Yes => Self { yes: WAD, no: 0 }, Outcome::No => Self { yes: 0, no: WAD } }
+    }
+    /// Mercado inválido: ½ para cada lado.
+    pub fn invalid() -> Self { Self { yes: WAD / 2, no: WAD / 2 } }
+
+    /// Valor em colateral de `(yes_tokens, no_tokens)`: `floor((yes·p_yes + no·p_no) / WAD)`.
+    pub fn value(&self, yes_tokens: Wad, no_tokens: Wad) -> Result<Wad, AmmError> {
+        let n = U256::from(yes_tokens) * U256::from(self.yes) + U256::from(no_tokens) * U256::from(self.no);
+        u256_to_u128_checked(n / U256::from(WAD))
+    }
+}
+
+/// Resultado final informado pela fonte de resolução.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Winner(Outcome),
+    /// Mercado anulado: payout (½, ½)
+    Invalid,
+    /// Payout arbitrário (ex.: resolução escalar/parcial)
+    Split(Payout),
+}
+impl Resolution {
+    pub fn payout(&self) -> Payout {
+        match *self {
+            Resolution::Winner(o) => Payout::winner(o),
+            Resolution::Invalid => Payout::invalid(),
+            Resolution::Split(p) => p,
+        }
+    }
+}
+
+/// Fonte do resultado de um mercado (oráculo, comitê, operador...).
+pub trait ResolutionSource {
+    /// Resolução final, ou `None` se ainda não disponível.
+    fn resolution(&self) -> Option<Resolution>;
+}
+
+/// Fonte local: um operador registra o resultado manualmente.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ManualResolution { resolution: Option<Resolution> }
+impl ManualResolution {
+    pub fn new() -> Self { Self::default() }
+    /// Registra (ou corrige, antes de ser consumido) o resultado.
+    pub fn set(&mut self, resolution: Resolution) -> Result<(), AmmError> {
+        let p = resolution.payout();
+        Payout::new(p.yes, p.no)?;
+        self.resolution = Some(resolution);
+        Ok(())
+    }
+}
+impl ResolutionSource for ManualResolution {
+    fn resolution(&self) -> Option<Resolution> { self.resolution }
+}
+
+/// Tokens de outcome de uma conta.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position { pub yes: Wad, pub no: Wad }
+impl Position {
+    pub fn get(&self, outcome: Outcome) -> Wad {
+        match outcome { Outcome::Yes => self.yes, Outcome::No => self.no }
+    }
+    fn get_mut(&mut self, outcome: Outcome) -> &mut Wad {
+        match outcome { Outcome::Yes => &mut self.yes, Outcome::No => &mut self.no }
+    }
+}
+
+/// Evento do log do mercado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketEvent {
+    Opened { funder: AccountId, funding: Wad, shares: Wad },
+    Bought { account: AccountId, outcome: Outcome, collateral: Wad, tokens: Wad },
+    Sold { account: AccountId, outcome: Outcome, collateral: Wad, tokens: Wad },
+    SetsMinted { account: AccountId, amount: Wad },
+    SetsBurned { account: AccountId, amount: Wad },
+    FundingAdded { account: AccountId, amount: Wad, shares: Wad },
+    Halted,
+    Resolved { payout: Payout },
+    Redeemed { account: AccountId, amount: Wad },
+    LpWithdrawn { account: AccountId, shares: Wad, amount: Wad },
+    Settled { dust: Wad },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketLifecycle {
+    market: OutcomeMarket,
+    phase: MarketPhase,
+    payout: Option<Payout>,
+    positions: BTreeMap<AccountId, Position>,
+    lp_shares: BTreeMap<AccountId, Wad>,
+    /// Claim restante dos LPs sobre as reservas do pool (após resolver)
+    pool_claim: Wad,
+    /// Colateral ainda não pago (após resolver)
+    collateral_left: Wad,
+    events: Vec<MarketEvent>,
+}
+
+impl MarketLifecycle {
+    /// Abre o mercado com o funding inicial de `funder` (que recebe todas as shares de LP).
+    pub fn open(funder: AccountId, funding: Wad, fee_ppm: Ppm) -> Result<Self, AmmError> {
+        let market = OutcomeMarket::new(funding, fee_ppm)?;
+        let shares = market.pool().total_shares();
+        Ok(Self {
+            market,
+            phase: MarketPhase::Open,
+            payout: None,
+            positions: BTreeMap::new(),
+            lp_shares: BTreeMap::from([(funder, shares)]),
+            pool_claim: 0,
+            collateral_left: 0,
+            events: vec![MarketEvent::Opened { funder, funding, shares }],
+        })
+    }
+
+    pub fn market(&self) -> &OutcomeMarket { &self.market }
+    pub fn phase(&self) -> MarketPhase { self.phase }
+    pub fn payout(&self) -> Option<Payout> { self.payout }
+    pub fn events(&self) -> &[MarketEvent] { &self.events }
+    pub fn position(&self, account: AccountId) -> Position { self.positions.get(&account).copied().unwrap_or_default() }
+    pub fn lp_shares(&self, account: AccountId) -> Wad { self.lp_shares.get(&account).copied().unwrap_or(0) }
+    /// Colateral ainda não pago (após resolver).
+    pub fn collateral_left(&self) -> Wad { self.collateral_left }
+
+    #[inline]
+    fn expect_phase(&self, expected: MarketPhase) -> Result<(), AmmError> {
+        if self.phase != expected { Err(AmmError::WrongPhase { expected, actual: self.phase }) } else { Ok(()) }
+    }
+
+    fn credit(&mut self, account: AccountId, outcome: Outcome, amount: Wad) -> Result<(), AmmError> {
+        let pos = self.positions.entry(account).or_default();
+        *pos.get_mut(outcome) = checked_add(pos.get(outcome), amount)?;
+        Ok(())
+    }
+
+    // ---- Open ----
+
+    pub fn buy(&mut self, account: AccountId, outcome: Outcome, collateral: Wad, min_tokens: Wad) -> Result<OutcomeTrade, AmmError> {
+        self.expect_phase(MarketPhase::Open)?;
+        let mut market = self.market.clone();
+        let trade = market.buy(outcome, collateral, min_tokens)?;
+        self.credit(account, outcome, trade.tokens)?;
+        self.market = market;
+        self.events.push(MarketEvent::Bought { account, outcome, collateral, tokens: trade.tokens });
+        Ok(trade)
+    }
+
+    pub fn sell(&mut self, account: AccountId, outcome: Outcome, collateral: Wad, max_tokens: Wad) -> Result<OutcomeTrade, AmmError> {
+        self.expect_phase(MarketPhase::Open)?;
+        let mut market = self.market.clone();
+        let trade = market.sell(outcome, collateral, max_tokens)?;
+        let available = self.position(account).get(outcome);
+        if trade.tokens > available { return Err(AmmError::InsufficientShares { requested: trade.tokens, available }); }
+        if let Some(pos) = self.positions.get_mut(&account) { *pos.get_mut(outcome) -= trade.tokens; }
+        self.market = market;
+        self.events.push(MarketEvent::Sold { account, outcome, collateral, tokens: trade.tokens });
+        Ok(trade)
+    }
+
+    pub fn mint_sets(&mut self, account: AccountId, amount: Wad) -> Result<(), AmmError> {
+        self.expect_phase(MarketPhase::Open)?;
+        let pos = self.position(account);
+        let (yes, no) = (checked_add(pos.yes, amount)?, checked_add(pos.no, amount)?);
+        self.market.mint_sets(amount)?;
+        self.positions.insert(account, Position { yes, no });
+        self.events.push(MarketEvent::SetsMinted { account, amount });
+        Ok(())
+    }
+
+    pub fn burn_sets(&mut self, account: AccountId, amount: Wad) -> Result<(), AmmError> {
+        self.expect_phase(MarketPhase::Open)?;
+        let pos = self.position(account);
+        let available = pos.yes.min(pos.no);
+        if amount > available { return Err(AmmError::InsufficientShares { requested: amount, available }); }
+        self.market.burn_sets(amount)?;
+        self.positions.insert(account, Position { yes: pos.yes - amount, no: pos.no - amount });
+        self.events.push(MarketEvent::SetsBurned { account, amount });
+        Ok(())
+    }
+
+    /// Adiciona liquidez: shares de LP para `account` e a sobra de tokens na sua posição.
+    pub fn add_funding(&mut self, account: AccountId, amount: Wad) -> Result<FundingReceipt, AmmError> {
+        self.expect_phase(MarketPhase::Open)?;
+        let mut market = self.market.clone();
+        let f = market.add_funding(amount)?;
+        let shares = checked_add(self.lp_shares(account), f.liquidity.shares)?;
+        for outcome in [Outcome::Yes, Outcome::No] {
+            if f.leftover(outcome) > 0 { self.credit(account, outcome, f.leftover(outcome))?; }
+        }
+        self.lp_shares.insert(account, shares);
+        self.market = market;
+        self.events.push(MarketEvent::FundingAdded { account, amount, shares: f.liquidity.shares });
+        Ok(f)
+    }
+
+    // ---- Transições ----
+
+    /// `Open → Halted`: encerra a negociação.
+    pub fn halt(&mut self) -> Result<(), AmmError> {
+        self.expect_phase(MarketPhase::Open)?;
+        self.phase = MarketPhase::Halted;
+        self.events.push(MarketEvent::Halted);
+        Ok(())
+    }
+
+    /// `Halted → Resolved`: consulta a fonte e fixa o vetor de payout.
+    pub fn resolve(&mut self, source: &dyn ResolutionSource) -> Result<Payout, AmmError> {
+        self.expect_phase(MarketPhase::Halted)?;
+        let p = source.resolution().ok_or(AmmError::ResolutionUnavailable)?.payout();
+        let payout = Payout::new(p.yes, p.no)?;
+        let Reserves { x, y } = self.market.pool().reserves();
+        self.pool_claim = payout.value(x, y)?;
+        self.collateral_left = self.market.collateral();
+        self.payout = Some(payout);
+        self.phase = MarketPhase::Resolved;
+        self.events.push(MarketEvent::Resolved { payout });
+        Ok(payout)
+    }
+
+    fn resolved_payout(&self) -> Result<Payout, AmmError> {
+        self.payout.ok_or(AmmError::WrongPhase { expected: MarketPhase::Resolved, actual: self.phase })
+    }
+
+    /// Soma dos claims dos holders ainda não resgatados.
+    fn holder_claims(&self, payout: &Payout) -> Result<Wad, AmmError> {
+        self.positions.values().try_fold(0u128, |acc, p| checked_add(acc, payout.value(p.yes, p.no)?))
+    }
+
+    /// Quanto `account` recebe ao resgatar sua posição.
+    pub fn holder_payout(&self, account: AccountId) -> Result<Wad, AmmError> {
+        let pos = self.position(account);
+        self.resolved_payout()?.value(pos.yes, pos.no)
+    }
+
+    /// Quanto `account` recebe ao sacar suas shares de LP.
+    pub fn lp_payout(&self, account: AccountId) -> Result<Wad, AmmError> {
+        let payout = self.resolved_payout()?;
+        let shares = self.lp_shares(account);
+        let total = self.lp_shares.values().try_fold(0u128, |acc, &s| checked_add(acc, s))?;
+        if shares == 0 { return Ok(0); }
+        if shares == total { return checked_sub(self.collateral_left, self.holder_claims(&payout)?); }
+        u256_to_u128_checked(U256::from(self.pool_claim) * U256::from(shares) / U256::from(total))
+    }
+
+    // ---- Resolved ----
+
+    /// Resgata toda a posição de `account` pelo payout.
+    pub fn redeem(&mut self, account: AccountId) -> Result<Wad, AmmError> {
+        self.expect_phase(MarketPhase::Resolved)?;
+        if !self.positions.contains_key(&account) { return Err(AmmError::UnknownAccount { account }); }
+        let amount = self.holder_payout(account)?;
+        self.collateral_left = checked_sub(self.collateral_left, amount)?;
+        self.positions.remove(&account);
+        self.events.push(MarketEvent::Redeemed { account, amount });
+        Ok(amount)
+    }
+
+    /// Saca as shares de LP de `account` (o último LP leva o colateral restante).
+    pub fn withdraw_lp(&mut self, account: AccountId) -> Result<Wad, AmmError> {
+        self.expect_phase(MarketPhase::Resolved)?;
+        let shares = self.lp_shares(account);
+        if shares == 0 { return Err(AmmError::UnknownAccount { account }); }
+        let amount = self.lp_payout(account)?;
+        self.collateral_left = checked_sub(self.collateral_left, amount)?;
+        self.pool_claim = self.pool_claim.saturating_sub(amount);
+        self.lp_shares.remove(&account);
+        self.events.push(MarketEvent::LpWithdrawn { account, shares, amount });
+        Ok(amount)
+    }
+
+    /// `Resolved → Settled`: exige todos os claims pagos; devolve a poeira restante (se não houver LP).
+    pub fn settle(&mut self) -> Result<Wad, AmmError> {
+        self.expect_phase(MarketPhase::Resolved)?;
+        let payout = self.resolved_payout()?;
+        // com LPs pendentes, todo o colateral restante ainda tem dono
+        let owed = if self.lp_shares.is_empty() { self.holder_claims(&payout)? } else { self.collateral_left };
+        if owed > 0 { return Err(AmmError::OutstandingClaims { owed }); }
+        let dust = self.collateral_left;
+        self.collateral_left = 0;
+        self.phase = MarketPhase::Settled;
+        self.events.push(MarketEvent::Settled { dust });
+        Ok(dust)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE2: Ppm = 20_000; // 2%
+    const LP: AccountId = 1;
+    const ALICE: AccountId = 2;
+    const BOB: AccountId = 3;
+
+    fn traded() -> MarketLifecycle {
+        let mut m = MarketLifecycle::open(LP, 10_000u128 * WAD, FEE2).unwrap();
+        m.buy(ALICE, Outcome::Yes, 2_000u128 * WAD, 0).unwrap();
+        m.buy(BOB, Outcome::No, 500u128 * WAD, 0).unwrap();
+        m.mint_sets(BOB, 100u128 * WAD).unwrap();
+        m
+    }
+
+    fn resolved(r: Resolution) -> MarketLifecycle {
+        let mut m = traded();
+        let mut src = ManualResolution::new();
+        src.set(r).unwrap();
+        m.halt().unwrap();
+        m.resolve(&src).unwrap();
+        m
+    }
+
+    #[test]
+    fn t_full_lifecycle_pays_exactly_the_collateral() {
+        let mut m = resolved(Resolution::Winner(Outcome::Yes));
+        let total = m.collateral_left();
+        let alice = m.redeem(ALICE).unwrap();
+        assert_eq!(alice, m.events().iter().find_map(|e| match e { MarketEvent::Redeemed { amount, .. } => Some(*amount), _ => None }).unwrap());
+        assert!(alice > 2_000u128 * WAD); // YES venceu: Alice lucra
+        let bob = m.redeem(BOB).unwrap();
+        assert_eq!(bob, 100u128 * WAD); // só os sets mintados pagam
+        let lp = m.withdraw_lp(LP).unwrap();
+        assert_eq!(alice + bob + lp, total);
+        assert_eq!(m.settle().unwrap(), 0);
+        assert_eq!(m.phase(), MarketPhase::Settled);
+        assert!(matches!(m.events().last(), Some(MarketEvent::Settled { dust: 0 })));
+    }
+
+    #[test]
+    fn t_invalid_and_split_payouts() {
+        let m = resolved(Resolution::Invalid);
+        let pos = m.position(BOB);
+        assert_eq!(m.holder_payout(BOB).unwrap(), (pos.yes + pos.no) / 2);
+        let p = Payout::new(3 * WAD / 10, 7 * WAD / 10).unwrap();
+        let m = resolved(Resolution::Split(p));
+        assert_eq!(m.payout(), Some(p));
+        assert_eq!(Payout::new(WAD, 1).unwrap_err(), AmmError::InvalidPayout { yes: WAD, no: 1 });
+        assert!(ManualResolution::new().set(Resolution::Split(Payout { yes: 1, no: 1 })).is_err());
+    }
+
+    #[test]
+    fn t_multiple_lps_share_pro_rata() {
+        let mut m = MarketLifecycle::open(LP, 10_000u128 * WAD, FEE2).unwrap();
+        m.add_funding(BOB, 10_000u128 * WAD).unwrap();
+        m.buy(ALICE, Outcome::No, 1_000u128 * WAD, 0).unwrap();
+        let mut src = ManualResolution::new();
+        src.set(Resolution::Winner(Outcome::No)).unwrap();
+        m.halt().unwrap();
+        m.resolve(&src).unwrap();
+        let (lp1, lp2) = (m.lp_payout(LP).unwrap(), m.lp_payout(BOB).unwrap());
+        assert!(lp1.abs_diff(lp2) <= 1, "lp1={} lp2={}", lp1, lp2);
+        let total = m.collateral_left();
+        let paid = m.withdraw_lp(LP).unwrap() + m.redeem(ALICE).unwrap() + m.withdraw_lp(BOB).unwrap();
+        assert_eq!(paid, total);
+        m.settle().unwrap();
+    }
+
+    #[test]
+    fn t_illegal_transitions_are_typed_errors() {
+        let mut m = traded();
+        let src = ManualResolution::new();
+        let wrong = |expected, actual| AmmError::WrongPhase { expected, actual };
+        assert_eq!(m.resolve(&src).unwrap_err(), wrong(MarketPhase::Halted, MarketPhase::Open));
+        assert_eq!(m.redeem(ALICE).unwrap_err(), wrong(MarketPhase::Resolved, MarketPhase::Open));
+        assert_eq!(m.settle().unwrap_err(), wrong(MarketPhase::Resolved, MarketPhase::Open));
+        m.halt().unwrap();
+        assert_eq!(m.halt().unwrap_err(), wrong(MarketPhase::Open, MarketPhase::Halted));
+        assert_eq!(m.buy(ALICE, Outcome::Yes, WAD, 0).unwrap_err(), wrong(MarketPhase::Open, MarketPhase::Halted));
+        assert_eq!(m.resolve(&src).unwrap_err(), AmmError::ResolutionUnavailable);
+        assert_eq!(m.phase(), MarketPhase::Halted);
+        let mut src = src;
+        src.set(Resolution::Winner(Outcome::No)).unwrap();
+        m.resolve(&src).unwrap();
+        assert!(matches!(m.settle(), Err(AmmError::OutstandingClaims { .. })));
+        assert_eq!(m.withdraw_lp(ALICE).unwrap_err(), AmmError::UnknownAccount { account: ALICE });
+        assert_eq!(m.redeem(LP).unwrap_err(), AmmError::UnknownAccount { account: LP });
+        // só as operações que passaram: Opened, 2× Bought, SetsMinted, Halted, Resolved
+        assert_eq!(m.events().len(), 6);
+        assert!(matches!(m.events()[1], MarketEvent::Bought { account: ALICE, outcome: Outcome::Yes, .. }));
+        assert!(matches!(m.events()[3], MarketEvent::SetsMinted { account: BOB, amount } if amount == 100u128 * WAD));
+    }
+}
@@ -0,0 +1,270 @@
+//! Split de ordens exact-in entre rotas paralelas do `Router`, para reduzir o impacto de preço.
+//!
+//! Candidatas: os caminhos simples de até `max_hops`, ordenados pelo output de uma fatia
+//! (`amount_in / SPLIT_CHUNKS`, ≈ preço marginal na origem) e escolhidos de forma gulosa sem repetir
+//! pool — rotas disjuntas não interferem entre si, então cada perna é cotada isoladamente.
+//!
+//! Alocação (inteira, em wei):
+//! 1. gulosa: cada fatia vai para a rota com o maior output incremental;
+//! 2. refino: move `step` wei entre pares de rotas enquanto o output total cresce, com `step`
+//!    caindo à metade até 1 wei — no fim nenhum movimento de 1 wei melhora, i.e. os preços
+//!    marginais das pernas ficam iguais a menos do arredondamento.
+//!
+//! Cada perna é simulada com o `swap_exact_in` do próprio pool (output floor, taxa ceil — ADR-0001),
+//! então o output total é a soma dos floors e executar o plano devolve exatamente o cotado. Se o
+//! split não superar a melhor rota única, o plano é a própria rota única (melhora 0).
+
+use super::errors::AmmError;
+use super::guardrails::{div_nearest_even_u256_to_u128, ensure_nonzero, mul_u128_to_u256};
+use super::router::{Edge, Route, Router, TokenId};
+use super::types::{Ppm, U256, Wad, PPM_SCALE, WAD};
+
+/// Maior número de pernas num split.
+pub const MAX_SPLIT_ROUTES: usize = 4;
+/// Fatias da alocação gulosa inicial.
+pub const SPLIT_CHUNKS: u128 = 64;
+/// Limite de passadas do refino por tamanho de passo.
+const MAX_PASSES: usize = 32;
+
+/// Plano de split cotado ou executado.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitPlan {
+    /// Pernas com alocação > 0; a soma dos `amount_in` é o `amount_in` do plano
+    pub legs: Vec<Route>,
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+    /// `amount_out / amount_in` (WAD, nearest-even)
+    pub execution_price: Wad,
+    /// Melhor rota única (`Router::quote_exact_in`) para a mesma ordem
+    pub best_single: Route,
+    /// `amount_out - best_single.amount_out`
+    pub improvement: Wad,
+    /// Melhora relativa ao output da rota única (ppm, floor; satura em `Ppm::MAX`)
+    pub improvement_ppm: Ppm,
+}
+
+/// Estado da alocação: caminho, alocado e output atual de cada perna.
+struct Legs<'a> {
+    router: &'a Router,
+    paths: Vec<Vec<Edge>>,
+    alloc: Vec<Wad>,
+    outs: Vec<Wad>,
+}
+
+impl Legs<'_> {
+    /// Output da perna `i` com `amount` de input; `None` se o pool recusa (reserva mínima, input pequeno…).
+    fn out(&self, i: usize, amount: Wad) -> Option<Wad> {
+        if amount == 0 { return Some(0); }
+        self.router.simulate_exact_in(&self.paths[i], amount).ok().and_then(|h| h.last().map(|l| l.amount_out))
+    }
+
+    fn total_out(&self) -> Option<Wad> { self.outs.iter().try_fold(0u128, |acc, &o| acc.checked_add(o)) }
+
+    /// Distribui `amount` em fatias, cada uma para o maior output incremental (empate: a primeira).
+    fn greedy(&mut self, amount: Wad, chunk: Wad) -> bool {
+        let mut left = amount;
+        while left > 0 {
+            let c = chunk.min(left);
+            let mut best: Option<(usize, Wad, Wad)> = None;
+            for i in 0..self.paths.len() {
+                let Some(next) = self.alloc[i].checked_add(c).and_then(|a| self.out(i, a)) else { continue };
+                let gain = next - self.outs[i];
+                if best.is_none_or(|(_, g, _)| gain > g) { best = Some((i, gain, next)); }
+            }
+            let Some((i, _, next)) = best else { return false };
+            self.alloc[i] += c;
+            self.outs[i] = next;
+            left -= c;
+        }
+        true
+    }
+
+    /// Move `step` wei de `i` para `j` se o output somado cresce.
+    fn try_move(&mut self, i: usize, j: usize, step: Wad) -> bool {
+        if self.alloc[i] < step { return false; }
+        let (Some(oi), Some(oj)) = (self.out(i, self.alloc[i] - step), self.alloc[j].checked_add(step).and_then(|a| self.out(j, a))) else { return false };
+        if oi.checked_add(oj).is_none_or(|s| s <= self.outs[i] + self.outs[j]) { return false; }
+        self.alloc[i] -= step;
+        self.alloc[j] += step;
+        (self.outs[i], self.outs[j]) = (oi, oj);
+        true
+    }
+
+    fn refine(&mut self, mut step: Wad) {
+        let n = self.paths.len();
+        while step > 0 {
+            for _ in 0..MAX_PASSES {
+                let mut moved = false;
+                for i in 0..n {
+                    for j in 0..n {
+                        if i != j && self.try_move(i, j, step) { moved = true; }
+                    }
+                }
+                if !moved { break; }
+            }
+            step /= 2;
+        }
+    }
+}
+
+impl Router {
+    /// Rotas candidatas ao split: ordenadas pelo output de `probe` e sem pool repetido.
+    fn split_candidates(&self, token_in: TokenId, token_out: TokenId, probe: Wad, max_hops: usize, max_routes: usize) -> Vec<Vec<Edge>> {
+        let mut ranked: Vec<(Wad, Vec<Edge>)> = self.paths(token_in, token_out, max_hops).into_iter().filter_map(|p| {
+            let out = self.simulate_exact_in(&p, probe).ok()?.last()?.amount_out;
+            Some((out, p))
+        }).collect();
+        // estável: em empate ficam os caminhos mais curtos
+        ranked.sort_by_key(|r| std::cmp::Reverse(r.0));
+        let mut chosen: Vec<Vec<Edge>> = Vec::new();
+        for (_, p) in ranked {
+            if chosen.len() == max_routes { break; }
+            let shares_pool = chosen.iter().flatten().any(|c| p.iter().any(|e| e.key == c.key));
+            if !shares_pool { chosen.push(p); }
+        }
+        chosen
+    }
+
+    /// Cota o split de `amount_in` em até `max_routes` rotas disjuntas (1..=`MAX_SPLIT_ROUTES`).
+    pub fn quote_split_exact_in(&self, token_in: TokenId, token_out: TokenId, amount_in: Wad, max_hops: usize, max_routes: usize) -> Result<SplitPlan, AmmError> {
+        ensure_nonzero(amount_in)?;
+        let best_single = self.quote_exact_in(token_in, token_out, amount_in, max_hops)?;
+        let single = |best_single: Route| SplitPlan {
+            legs: vec![best_single.clone()],
+            amount_in,
+            amount_out: best_single.amount_out,
+            execution_price: best_single.execution_price,
+            best_single,
+            improvement: 0,
+            improvement_ppm: 0,
+        };
+        let max_routes = max_routes.clamp(1, MAX_SPLIT_ROUTES);
+        let chunk = (amount_in / SPLIT_CHUNKS).max(1);
+        let paths = self.split_candidates(token_in, token_out, chunk, max_hops, max_routes);
+        if paths.len() < 2 { return Ok(single(best_single)); }
+
+        let n = paths.len();
+        let mut legs = Legs { router: self, paths, alloc: vec![0; n], outs: vec![0; n] };
+        if !legs.greedy(amount_in, chunk) { return Ok(single(best_single)); }
+        legs.refine(chunk / 2);
+        let amount_out = legs.total_out().ok_or(AmmError::Overflow)?;
+        if amount_out <= best_single.amount_out { return Ok(single(best_single)); }
+
+        let mut routes = Vec::new();
+        for (path, &a) in legs.paths.iter().zip(&legs.alloc) {
+            if a > 0 { routes.push(self.route(token_in, token_out, self.simulate_exact_in(path, a)?)?); }
+        }
+        let improvement = amount_out - best_single.amount_out;
+        // rota única sem output: melhora relativa ilimitada (satura)
+        let improvement_ppm = (U256::from(improvement) * U256::from(PPM_SCALE)).checked_div(U256::from(best_single.amount_out)).unwrap_or(U256::MAX);
+        Ok(SplitPlan {
+            legs: routes,
+            amount_in,
+            amount_out,
+            execution_price: div_nearest_even_u256_to_u128(mul_u128_to_u256(amount_out, WAD), U256::from(amount_in))?,
+            best_single,
+            improvement,
+            improvement_ppm: u32::try_from(improvement_ppm).unwrap_or(Ppm::MAX),
+        })
+    }
+
+    /// Executa o split cotado; exige output total ≥ `min_out`. Atômico: todas as pernas ou nenhuma.
+    pub fn swap_split_exact_in(&mut self, token_in: TokenId, token_out: TokenId, amount_in: Wad, min_out: Wad, max_hops: usize, max_routes: usize) -> Result<SplitPlan, AmmError> {
+        let plan = self.quote_split_exact_in(token_in, token_out, amount_in, max_hops, max_routes)?;
+        if plan.amount_out < min_out { return Err(AmmError::SlippageExceeded { expected: min_out, actual: plan.amount_out }); }
+        let mut next = self.clone();
+        for leg in &plan.legs { next.execute(leg, true)?; }
+        *self = next;
+        Ok(plan)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::pool::Pool;
+
+    const A: TokenId = 1;
+    const B: TokenId = 2;
+    const C: TokenId = 3;
+    const D: TokenId = 4;
+
+    fn pool(x: u128, y: u128) -> Pool { Pool::new(x * WAD, y * WAD, 3_000).unwrap() }
+
+    /// Duas rotas idênticas A-C-B e A-D-B, mais um A-B direto raso.
+    fn router() -> Router {
+        let mut r = Router::new();
+        r.add_pool(A, B, pool(1_000, 1_000)).unwrap();
+        r.add_pool(A, C, pool(100_000, 100_000)).unwrap();
+        r.add_pool(C, B, pool(100_000, 100_000)).unwrap();
+        r.add_pool(A, D, pool(100_000, 100_000)).unwrap();
+        r.add_pool(D, B, pool(100_000, 100_000)).unwrap();
+        r
+    }
+
+    #[test]
+    fn t_split_beats_single_route() {
+        let r = router();
+        let plan = r.quote_split_exact_in(A, B, 20_000 * WAD, 3, 3).unwrap();
+        assert!(plan.legs.len() >= 2);
+        assert_eq!(plan.legs.iter().map(|l| l.amount_in).sum::<u128>(), plan.amount_in);
+        assert_eq!(plan.amount_out, plan.legs.iter().map(|l| l.amount_out).sum::<u128>());
+        assert!(plan.amount_out > plan.best_single.amount_out);
+        assert_eq!(plan.improvement, plan.amount_out - plan.best_single.amount_out);
+        assert!(plan.improvement_ppm > 0);
+        // rotas simétricas recebem (quase) o mesmo
+        let two_hop: Vec<&Route> = plan.legs.iter().filter(|l| l.hops.len() == 2).collect();
+        assert_eq!(two_hop.len(), 2);
+        let diff = two_hop[0].amount_in.abs_diff(two_hop[1].amount_in);
+        assert!(diff <= WAD / 1_000, "diff {diff}");
+    }
+
+    #[test]
+    fn t_marginal_prices_equalized() {
+        let r = router();
+        let plan = r.quote_split_exact_in(A, B, 20_000 * WAD, 3, 3).unwrap();
+        // mover 1e-6 do input de qualquer perna para outra não melhora o total
+        let step = WAD / 1_000_000;
+        let out = |l: &Route, a: Wad| l.hops.iter().fold(a, |amount, h| {
+            r.pool(h.token_in, h.token_out).unwrap().clone().swap_exact_in(h.direction, amount, 0).unwrap().amount_out
+        });
+        for i in 0..plan.legs.len() {
+            for j in 0..plan.legs.len() {
+                if i == j { continue; }
+                let (li, lj) = (&plan.legs[i], &plan.legs[j]);
+                let moved = out(li, li.amount_in - step) + out(lj, lj.amount_in + step);
+                assert!(moved <= li.amount_out + lj.amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn t_small_order_stays_single() {
+        let r = router();
+        let plan = r.quote_split_exact_in(A, B, WAD, 3, 3).unwrap();
+        assert_eq!(plan.legs.len(), 1);
+        assert_eq!(plan.legs[0], plan.best_single);
+        assert_eq!(plan.improvement, 0);
+        // max_routes 1 equivale à melhor rota única
+        let one = r.quote_split_exact_in(A, B, 20_000 * WAD, 3, 1).unwrap();
+        assert_eq!(one.amount_out, r.quote_exact_in(A, B, 20_000 * WAD, 3).unwrap().amount_out);
+        assert!(r.quote_split_exact_in(A, B, 0, 3, 3).is_err());
+        assert_eq!(r.quote_split_exact_in(A, 9, WAD, 3, 3).unwrap_err(), AmmError::NoRoute { token_in: A, token_out: 9 });
+    }
+
+    #[test]
+    fn t_execute_matches_plan_and_is_atomic() {
+        let mut r = router();
+        let plan = r.quote_split_exact_in(A, B, 20_000 * WAD, 3, 3).unwrap();
+        let before = r.clone();
+        assert!(matches!(r.swap_split_exact_in(A, B, 20_000 * WAD, plan.amount_out + 1, 3, 3), Err(AmmError::SlippageExceeded { .. })));
+        assert_eq!(r, before);
+        assert_eq!(r.swap_split_exact_in(A, B, 20_000 * WAD, plan.amount_out, 3, 3).unwrap(), plan);
+        let spent: u128 = r.pools().filter(|(k, _)| k.0 == A).map(|(_, p)| p.reserves().x).sum::<u128>()
+            - before.pools().filter(|(k, _)| k.0 == A).map(|(_, p)| p.reserves().x).sum::<u128>();
+        assert_eq!(spent, 20_000 * WAD);
+    }
+}
@@ -0,0 +1,241 @@
+//! Curva StableSwap (Curve) para pares de ativos correlacionados (ex.: stable/stable).
+//! Invariante com 2 ativos e `Ann = A·n^n = 4A`:
+//!     Ann·(x + y) + D = Ann·D + D³ / (4·x·y)
+//! `D` e o `y` de equilíbrio são resolvidos por Newton em U256 (contas checadas).
+//!
+//! Mesmos tipos (WAD/ppm), guardrails e política do ADR-0001 que `swap.rs`:
+//! - fee(input): ceil, cobrada antes da curva;
+//! - `amount_out = y - y' - 1`: o `y'` de Newton tem erro de até 1 wei, então o wei extra
+//!   garante floor do valor exato e `D` nunca diminui;
+//! - `amount_in`: menor dx bruto com `out ≥ dy` (chute pela curva + busca binária);
+//! - spot: nearest (ties-to-even).
+
+use super::errors::AmmError;
+use super::guardrails::{
+    checked_add, div_nearest_even_u256_to_u128, ensure_fee, ensure_nonzero, ensure_reserves, u256_to_u128_checked,
+};
+use super::swap::fee_on_input_ceil;
+use super::types::{Direction, Ppm, U256, Wad, MIN_RESERVE, PPM_SCALE, WAD};
+
+/// Coeficiente de amplificação `A` (inteiro).
+pub type Amp = u64;
+/// Maior `A` aceito.
+pub const MAX_AMP: Amp = 1_000_000;
+/// Limite de passos de Newton (convergência típica < 10).
+const MAX_ITER: usize = 255;
+
+#[inline]
+fn mul(a: U256, b: U256) -> Result<U256, AmmError> { a.checked_mul(b).ok_or(AmmError::Overflow) }
+
+#[inline]
+fn add(a: U256, b: U256) -> Result<U256, AmmError> { a.checked_add(b).ok_or(AmmError::Overflow) }
+
+#[inline]
+fn abs_diff(a: U256, b: U256) -> U256 { if a > b { a - b } else { b - a } }
+
+#[inline]
+pub fn ensure_amp(amp: Amp) -> Result<(), AmmError> {
+    if amp == 0 || amp > MAX_AMP { Err(AmmError::InvalidAmplification { amp }) } else { Ok(()) }
+}
+
+#[inline]
+fn ann(amp: Amp) -> U256 { U256::from(amp) * U256::from(4u8) }
+
+/// `D³ / (4·x·y)`, calculado em passos para caber em U256.
+#[inline]
+fn d_p(d: U256, x: U256, y: U256) -> Result<U256, AmmError> {
+    let two = U256::from(2u8);
+    let t = mul(d, d)? / (x * two);
+    Ok(mul(t, d)? / (y * two))
+}
+
+/// Invariante `D` para reservas `(x, y)`.
+pub fn get_d(x: Wad, y: Wad, amp: Amp) -> Result<U256, AmmError> {
+    ensure_amp(amp)?;
+    ensure_reserves(x, y)?;
+    let (xu, yu) = (U256::from(x), U256::from(y));
+    let s = xu + yu;
+    let ann = ann(amp);
+    let mut d = s;
+    for _ in 0..MAX_ITER {
+        let dp = d_p(d, xu, yu)?;
+        let prev = d;
+        // D = (Ann·S + 2·D_P)·D / ((Ann - 1)·D + 3·D_P)
+        let num = mul(add(mul(ann, s)?, dp * U256::from(2u8))?, d)?;
+        let den = add(mul(ann - U256::from(1u8), d)?, mul(dp, U256::from(3u8))?)?;
+        d = num / den;
+        if abs_diff(d, prev) <= U256::from(1u8) { return Ok(d); }
+    }
+    Err(AmmError::NoConvergence)
+}
+
+/// Reserva `y` que mantém o invariante `d` quando a outra reserva vale `x_new`.
+pub fn get_y(x_new: Wad, d: U256, amp: Amp) -> Result<Wad, AmmError> {
+    ensure_amp(amp)?;
+    ensure_nonzero(x_new)?;
+    let xu = U256::from(x_new);
+    let ann = ann(amp);
+    let two = U256::from(2u8);
+    // c = D³/(4·x·Ann), b = x + D/Ann
+    let c = mul(mul(d, d)? / (xu * two), d)? / mul(ann, two)?;
+    let b = add(xu, d / ann)?;
+    let mut y = d;
+    for _ in 0..MAX_ITER {
+        let prev = y;
+        // y = (y² + c) / (2y + b - D)
+        let den = add(y * two, b)?.checked_sub(d).ok_or(AmmError::Overflow)?;
+        y = add(mul(y, y)?, c)? / den;
+        if abs_diff(y, prev) <= U256::from(1u8) { return u256_to_u128_checked(y); }
+    }
+    Err(AmmError::NoConvergence)
+}
+
+/// Output ao enviar `dx` de X e receber Y (X→Y) na curva StableSwap.
+pub fn get_amount_out(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm, amp: Amp) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
+    ensure_reserves(x, y)?;
+    ensure_nonzero(dx)?;
+
+    let dx_net = dx - fee_on_input_ceil(dx, fee_ppm);
+    if dx_net == 0 { return Err(AmmError::InputTooSmall); }
+
+    let d = get_d(x, y, amp)?;
+    let x1 = checked_add(x, dx_net)?;
+    let y1 = get_y(x1, d, amp)?;
+    // wei de segurança: out = floor do exato e D' ≥ D
+    let out = y.checked_sub(y1).and_then(|v| v.checked_sub(1)).unwrap_or(0);
+    if out == 0 { return Err(AmmError::InputTooSmall); }
+    if y - out < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    Ok(out)
+}
+
+/// Menor `dx` bruto tal que `get_amount_out(x, y, dx, fee, amp) ≥ dy`.
+pub fn get_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm, amp: Amp) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
+    ensure_reserves(x, y)?;
+    ensure_nonzero(dy)?;
+    let available = y - MIN_RESERVE;
+    if dy >= y { return Err(AmmError::InsufficientLiquidity { requested: dy, available }); }
+    if dy >= available { return Err(AmmError::MinReserveBreached); }
+
+    // chute pela curva: x' que mantém D com y' = y - dy (+1 do wei de segurança)
+    let d = get_d(x, y, amp)?;
+    let x1 = get_y(y - dy - 1, d, amp)?;
+    let dx_net = x1.saturating_sub(x).max(1);
+    let gross = (U256::from(dx_net) * U256::from(PPM_SCALE as u64) + U256::from((PPM_SCALE - fee_ppm) as u64 - 1))
+        / U256::from((PPM_SCALE - fee_ppm) as u64);
+    let mut hi = u256_to_u128_checked(gross)?.max(1);
+
+    // garante que `hi` satisfaz (expande se necessário)
+    while get_amount_out(x, y, hi, fee_ppm, amp).unwrap_or(0) < dy {
+        hi = hi.checked_mul(2).ok_or(AmmError::Overflow)?;
+    }
+
+    // busca binária: menor dx com out ≥ dy
+    let mut lo: Wad = 1;
+    while lo < hi {
+        let mid = lo + ((hi - lo) >> 1);
+        if get_amount_out(x, y, mid, fee_ppm, amp).unwrap_or(0) >= dy { hi = mid; } else { lo = mid + 1; }
+    }
+    Ok(hi)
+}
+
+/// `get_amount_out` orientado por `Direction`.
+pub fn get_amount_out_dir(dir: Direction, x: Wad, y: Wad, amount_in: Wad, fee_ppm: Ppm, amp: Amp) -> Result<Wad, AmmError> {
+    let (r_in, r_out) = dir.orient(x, y);
+    get_amount_out(r_in, r_out, amount_in, fee_ppm, amp)
+}
+
+/// `get_amount_in` orientado por `Direction`.
+pub fn get_amount_in_dir(dir: Direction, x: Wad, y: Wad, amount_out: Wad, fee_ppm: Ppm, amp: Amp) -> Result<Wad, AmmError> {
+    let (r_in, r_out) = dir.orient(x, y);
+    get_amount_in(r_in, r_out, amount_out, fee_ppm, amp)
+}
+
+/// Preço marginal de 1 X em Y (em WAD): `-dy/dx = (Ann·x·y + t·y) / (Ann·x·y + t·x)`, `t = D³/(4xy)`.
+pub fn spot_price_x_in_y(x: Wad, y: Wad, amp: Amp) -> Result<Wad, AmmError> {
+    let d = get_d(x, y, amp)?;
+    let (xu, yu) = (U256::from(x), U256::from(y));
+    let t = d_p(d, xu, yu)?;
+    let base = mul(mul(ann(amp), xu)?, yu)?;
+    let mut num = add(base, mul(t, yu)?)?;
+    let mut den = add(base, mul(t, xu)?)?;
+    // reduz num/den juntos até `num·WAD` caber em U256 (erro relativo < 2^-150)
+    while num.bits() > 190 || den.bits() > 190 {
+        num >>= 1;
+        den >>= 1;
+    }
+    div_nearest_even_u256_to_u128(num * U256::from(WAD), den)
+}
+
+/// Preço marginal de 1 Y em X (em WAD).
+pub fn spot_price_y_in_x(x: Wad, y: Wad, amp: Amp) -> Result<Wad, AmmError> {
+    spot_price_x_in_y(y, x, amp)
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::swap;
+
+    const FEE0: Ppm = 0;
+    const FEE4: Ppm = 400; // 0,04%
+    const A: Amp = 100;
+
+    #[test]
+    fn t_d_balanced_equals_sum() {
+        let (x, y) = (1_000_000u128 * WAD, 1_000_000u128 * WAD);
+        let d = get_d(x, y, A).unwrap();
+        assert!(abs_diff(d, U256::from(x + y)) <= U256::from(1u8));
+    }
+
+    #[test]
+    fn t_get_y_round_trip() {
+        let (x, y) = (1_200_000u128 * WAD, 800_000u128 * WAD);
+        let d = get_d(x, y, A).unwrap();
+        assert!(get_y(x, d, A).unwrap().abs_diff(y) <= 2);
+        assert!(get_y(y, d, A).unwrap().abs_diff(x) <= 2);
+    }
+
+    #[test]
+    fn t_less_slippage_than_cpmm() {
+        let (x, y, dx) = (1_000_000u128 * WAD, 1_000_000u128 * WAD, 100_000u128 * WAD);
+        let stable = get_amount_out(x, y, dx, FEE0, A).unwrap();
+        let cpmm = swap::get_amount_out(x, y, dx, FEE0).unwrap();
+        assert!(stable > cpmm);
+        assert!(stable < dx); // nunca melhor que 1:1 num pool balanceado
+        assert!(dx - stable < dx / 1_000, "slippage alto demais: {}", dx - stable);
+    }
+
+    #[test]
+    fn t_amount_in_minimal() {
+        let (x, y, dy) = (1_000_000u128 * WAD, 1_500_000u128 * WAD, 50_000u128 * WAD);
+        let dx = get_amount_in(x, y, dy, FEE4, A).unwrap();
+        assert!(get_amount_out(x, y, dx, FEE4, A).unwrap() >= dy);
+        assert!(get_amount_out(x, y, dx - 1, FEE4, A).unwrap_or(0) < dy);
+    }
+
+    #[test]
+    fn t_spot_price_balanced_and_skewed() {
+        let p = spot_price_x_in_y(1_000_000u128 * WAD, 1_000_000u128 * WAD, A).unwrap();
+        assert!(p.abs_diff(WAD) <= 1);
+        // X abundante ⇒ X vale menos que 1 Y, mas bem mais perto de 1 do que no CPMM (y/x = 0.25)
+        let p = spot_price_x_in_y(2_000_000u128 * WAD, 500_000u128 * WAD, A).unwrap();
+        assert!(p < WAD && p > WAD / 2, "p={}", p);
+        assert!(spot_price_y_in_x(2_000_000u128 * WAD, 500_000u128 * WAD, A).unwrap() > WAD);
+    }
+
+    #[test]
+    fn t_guards() {
+        let (x, y) = (1_000_000u128 * WAD, 1_000_000u128 * WAD);
+        assert_eq!(get_amount_out(x, y, WAD, FEE0, 0).unwrap_err(), AmmError::InvalidAmplification { amp: 0 });
+        assert_eq!(get_amount_out(x, y, WAD, 1_000_000, A).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: 1_000_000 });
+        assert_eq!(get_amount_out(x, y, 0, FEE0, A).unwrap_err(), AmmError::ZeroAmount);
+        assert_eq!(get_amount_in(x, y, y - 1, FEE0, A).unwrap_err(), AmmError::MinReserveBreached);
+        assert!(matches!(get_amount_in(x, y, y, FEE0, A), Err(AmmError::InsufficientLiquidity { .. })));
+        assert_eq!(get_amount_out_dir(Direction::YtoX, x, y, WAD, FEE0, A).unwrap(), get_amount_out(y, x, WAD, FEE0, A).unwrap());
+    }
+}
@@ -1,15 +1,19 @@
 //! Funções puras de swap (CPMM x·y=k), conforme ADR-0001/0002.
 //! - get_amount_out: trocando X→Y com taxa sobre o input
 //! - get_amount_in: menor dx bruto que entrega ao menos dy em Y (minimalidade garantida)
+//! - swap_exact_in / swap_exact_out: execução com proteção de slippage (`min_out` / `max_in`)
+//! - variantes `*_dir`: mesmas regras para qualquer `Direction` (X→Y ou Y→X)
 
 use super::errors::AmmError;
 use super::guardrails::{
     checked_add,
     div_nearest_even_u256_to_u128,
+    ensure_fee,
     ensure_nonzero,
     ensure_reserves,
+    u256_to_u128_checked,
 };
-use super::types::{U256, Ppm, Wad, PPM_SCALE, MIN_RESERVE};
+use super::types::{Direction, U256, Ppm, Wad, PPM_SCALE, MIN_RESERVE};
 
 #[inline]
 fn ceil_div_u256(n: U256, d: U256) -> U256 {
@@ -18,7 +22,7 @@ fn ceil_div_u256(n: U256, d: U256) -> U256 {
 }
 
 #[inline]
-fn fee_on_input_ceil(dx: Wad, fee_ppm: Ppm) -> Wad {
+pub(crate) fn fee_on_input_ceil(dx: Wad, fee_ppm: Ppm) -> Wad {
     if fee_ppm == 0 { return 0; }
     let n = U256::from(dx) * U256::from(fee_ppm as u64);
     let d = U256::from(PPM_SCALE as u64);
@@ -31,6 +35,7 @@ fn fee_on_input_ceil(dx: Wad, fee_ppm: Ppm) -> Wad {
 /// - divisão interna k/x': nearest (ties-to-even)
 /// - fronteira (out): floor via subtração inteira (y - y*)
 pub fn get_amount_out(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
     ensure_reserves(x, y)?;
     ensure_nonzero(dx)?;
 
@@ -65,11 +70,14 @@ pub fn get_amount_out(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmE
 /// 3) expande `hi` até `out(hi) ≥ dy` (se necessário)
 /// 4) busca binária no menor `dx` com `out ≥ dy`
 pub fn get_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
     ensure_reserves(x, y)?;
     ensure_nonzero(dy)?;
 
-    // Não pode esvaziar o pool além do mínimo
-    if dy >= y.checked_sub(MIN_RESERVE).ok_or(AmmError::Overflow)? {
+    // Não existe output maior que a reserva; e não pode esvaziar o pool além do mínimo
+    let available = y.checked_sub(MIN_RESERVE).ok_or(AmmError::Overflow)?;
+    if dy >= y { return Err(AmmError::InsufficientLiquidity { requested: dy, available }); }
+    if dy >= available {
         return Err(AmmError::MinReserveBreached);
     }
 
@@ -77,15 +85,14 @@ pub fn get_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmEr
     // dx_net = ceil( x * dy / (y - dy) )
     let num = U256::from(x) * U256::from(dy);
     let den = U256::from(y.checked_sub(dy).ok_or(AmmError::Overflow)?);
-    let dx_net = ceil_div_u256(num, den).as_u128();
+    let dx_net = u256_to_u128_checked(ceil_div_u256(num, den))?;
 
     // dx_gross = ceil( dx_net * 1e6 / (1e6 - fee) )
-    let denom_ppm = (PPM_SCALE as u64).checked_sub(fee_ppm as u64).ok_or(AmmError::InputTooSmall)?;
-    if denom_ppm == 0 { return Err(AmmError::InputTooSmall); }
-    let mut hi = ceil_div_u256(
+    let denom_ppm = (PPM_SCALE - fee_ppm) as u64; // > 0 por ensure_fee
+    let mut hi = u256_to_u128_checked(ceil_div_u256(
         U256::from(dx_net) * U256::from(PPM_SCALE as u64),
         U256::from(denom_ppm),
-    ).as_u128();
+    ))?;
     if hi == 0 { hi = 1; }
 
     // garante que `hi` satisfaz (expande se necessário)
@@ -96,16 +103,13 @@ pub fn get_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmEr
     }
 
     // -------- busca binária: menor dx com out ≥ dy --------
-    let mut lo: Wad = 0;
+    let mut lo: Wad = 1; // dx=0 nunca serve
     while lo < hi {
         // mid seguro: lo + (hi-lo)/2
-        let mut mid = lo + ((hi - lo) >> 1);
-        if mid == 0 { mid = 1; } // dx=0 nunca serve
+        let mid = lo + ((hi - lo) >> 1);
 
-        let out_mid = match get_amount_out(x, y, mid, fee_ppm) {
-            Ok(v) => v,
-            Err(_) => 0, // por robustez: trate erro como insuficiente
-        };
+        // por robustez: trate erro como insuficiente
+        let out_mid = get_amount_out(x, y, mid, fee_ppm).unwrap_or_default();
 
         if out_mid >= dy {
             // satisfaz → tenta menor
@@ -119,6 +123,53 @@ pub fn get_amount_in(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmEr
     Ok(hi)
 }
 
+/// `get_amount_out` orientado: `amount_in` entra no lado de entrada de `dir`.
+/// Taxa e MIN_RESERVE se aplicam ao input e ao output do sentido escolhido.
+pub fn get_amount_out_dir(dir: Direction, x: Wad, y: Wad, amount_in: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let (r_in, r_out) = dir.orient(x, y);
+    get_amount_out(r_in, r_out, amount_in, fee_ppm)
+}
+
+/// `get_amount_in` orientado: menor input bruto que entrega ao menos `amount_out` no sentido `dir`.
+pub fn get_amount_in_dir(dir: Direction, x: Wad, y: Wad, amount_out: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let (r_in, r_out) = dir.orient(x, y);
+    get_amount_in(r_in, r_out, amount_out, fee_ppm)
+}
+
+/// Troca Y→X: envia `dy` de Y e recebe X.
+pub fn get_amount_out_y_to_x(x: Wad, y: Wad, dy: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    get_amount_out_dir(Direction::YtoX, x, y, dy, fee_ppm)
+}
+
+/// Menor `dy` bruto tal que a troca Y→X entregue ao menos `dx` de X.
+pub fn get_amount_in_y_to_x(x: Wad, y: Wad, dx: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    get_amount_in_dir(Direction::YtoX, x, y, dx, fee_ppm)
+}
+
+/// Execução exact-in no sentido `dir`: devolve o output, ou `SlippageExceeded` se ficar abaixo de `min_out`.
+pub fn swap_exact_in_dir(dir: Direction, x: Wad, y: Wad, amount_in: Wad, min_out: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let out = get_amount_out_dir(dir, x, y, amount_in, fee_ppm)?;
+    if out < min_out { return Err(AmmError::SlippageExceeded { expected: min_out, actual: out }); }
+    Ok(out)
+}
+
+/// Execução exact-out no sentido `dir`: devolve o input bruto, ou `SlippageExceeded` se passar de `max_in`.
+pub fn swap_exact_out_dir(dir: Direction, x: Wad, y: Wad, amount_out: Wad, max_in: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    let amount_in = get_amount_in_dir(dir, x, y, amount_out, fee_ppm)?;
+    if amount_in > max_in { return Err(AmmError::SlippageExceeded { expected: max_in, actual: amount_in }); }
+    Ok(amount_in)
+}
+
+/// Execução exact-in X→Y com `min_out` (ver `swap_exact_in_dir`).
+pub fn swap_exact_in(x: Wad, y: Wad, dx: Wad, min_out: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    swap_exact_in_dir(Direction::XtoY, x, y, dx, min_out, fee_ppm)
+}
+
+/// Execução exact-out X→Y com `max_in` (ver `swap_exact_out_dir`).
+pub fn swap_exact_out(x: Wad, y: Wad, dy: Wad, max_in: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    swap_exact_out_dir(Direction::XtoY, x, y, dy, max_in, fee_ppm)
+}
+
 // -------------------------
 // TESTES
 // -------------------------
@@ -219,4 +270,76 @@ mod tests {
         let err = get_amount_out(x, y, dx, FEE0).unwrap_err();
         assert_eq!(err, AmmError::MinReserveBreached);
     }
+
+    #[test]
+    fn t_y_to_x_mirrors_x_to_y() {
+        let (x, y, d) = (1_000u128 * WAD, 1_000_000_000u128 * WAD, 100u128 * WAD);
+        // Y→X no pool (x,y) == X→Y no pool espelhado (y,x)
+        assert_eq!(get_amount_out_y_to_x(x, y, d, FEE3).unwrap(), get_amount_out(y, x, d, FEE3).unwrap());
+        assert_eq!(get_amount_out_dir(Direction::XtoY, x, y, d, FEE3).unwrap(), get_amount_out(x, y, d, FEE3).unwrap());
+        assert_ne!(get_amount_out_y_to_x(x, y, d, FEE3).unwrap(), get_amount_out(x, y, d, FEE3).unwrap());
+    }
+
+    #[test]
+    fn t_y_to_x_in_minimal() {
+        let (x, y, dx) = (1_000_000u128 * WAD, 2_000_000u128 * WAD, 9_870u128 * WAD);
+        let dy = get_amount_in_y_to_x(x, y, dx, FEE3).unwrap();
+        assert!(get_amount_out_y_to_x(x, y, dy, FEE3).unwrap() >= dx);
+        assert!(get_amount_out_y_to_x(x, y, dy - 1, FEE3).unwrap_or(0) < dx);
+    }
+
+    #[test]
+    fn t_y_to_x_min_reserve_on_x_side() {
+        // X é o lado de saída: a guarda de mínimo vale para x', não y'
+        let (x, y) = (MIN_RESERVE + 1_000, 1_000_000u128 * WAD);
+        let err = get_amount_in_y_to_x(x, y, 1_000, FEE0).unwrap_err();
+        assert_eq!(err, AmmError::MinReserveBreached);
+        let err = get_amount_out_y_to_x(x, y, 1_000_000u128 * WAD, FEE0).unwrap_err();
+        assert_eq!(err, AmmError::MinReserveBreached);
+    }
+
+    #[test]
+    fn t_swap_exact_in_min_out() {
+        let (x, y, dx) = (1_000_000u128 * WAD, 1_000_000u128 * WAD, 10_000u128 * WAD);
+        let out = get_amount_out(x, y, dx, FEE3).unwrap();
+        assert_eq!(swap_exact_in(x, y, dx, out, FEE3).unwrap(), out);
+        let err = swap_exact_in(x, y, dx, out + 1, FEE3).unwrap_err();
+        assert_eq!(err, AmmError::SlippageExceeded { expected: out + 1, actual: out });
+    }
+
+    #[test]
+    fn t_swap_exact_out_max_in() {
+        let (x, y, dy) = (1_000_000u128 * WAD, 1_000_000u128 * WAD, 9_870u128 * WAD);
+        let dx = get_amount_in(x, y, dy, FEE3).unwrap();
+        assert_eq!(swap_exact_out(x, y, dy, dx, FEE3).unwrap(), dx);
+        let err = swap_exact_out_dir(Direction::XtoY, x, y, dy, dx - 1, FEE3).unwrap_err();
+        assert_eq!(err, AmmError::SlippageExceeded { expected: dx - 1, actual: dx });
+    }
+
+    #[test]
+    fn t_in_one_wei_terminates() {
+        // dx mínimo = 1 wei: a busca binária precisa terminar em lo=hi=1
+        let (x, y) = (1_000_000u128 * WAD, 1_000_000u128 * WAD);
+        assert_eq!(get_amount_in(x, y, 1, FEE0).unwrap(), 1);
+    }
+
+    #[test]
+    fn t_dy_beyond_reserve_insufficient_liquidity() {
+        let (x, y) = (1_000_000u128 * WAD, 1_000_000u128 * WAD);
+        let err = get_amount_in(x, y, y, FEE0).unwrap_err();
+        assert_eq!(err, AmmError::InsufficientLiquidity { requested: y, available: y - MIN_RESERVE });
+    }
+
+    #[test]
+    fn t_fee_edges_rejected() {
+        let (x, y, d) = (1_000_000u128 * WAD, 1_000_000u128 * WAD, 10_000u128 * WAD);
+        // 999_999 ppm ainda é válido: sobra 1 ppm do input
+        assert!(get_amount_out(x, y, d, 999_999).is_ok());
+        assert!(get_amount_in(x, y, 1_000, 999_999).is_ok());
+        for fee in [1_000_000, u32::MAX] {
+            assert_eq!(get_amount_out(x, y, d, fee).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: fee });
+            assert_eq!(get_amount_in(x, y, d, fee).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: fee });
+            assert_eq!(get_amount_out_y_to_x(x, y, d, fee).unwrap_err(), AmmError::FeeTooHigh { fee_ppm: fee });
+        }
+    }
 }
@@ -1,11 +1,20 @@
 //! Tipos básicos do AMM (escala fixa) + U256 para intermediários.
 //! Depende do ADR-0001.
 
-use uint::construct_uint;
-construct_uint! {
-    /// Inteiro de 256 bits para contas intermediárias seguras.
-    pub struct U256(4);
+// O macro gera código que dispara lints do clippy fora do nosso controle.
+#[allow(clippy::manual_div_ceil, clippy::assign_op_pattern)]
+mod u256 {
+    use uint::construct_uint;
+    construct_uint! {
+        /// Inteiro de 256 bits para contas intermediárias seguras.
+        pub struct U256(4);
+    }
+    construct_uint! {
+        /// Inteiro de 512 bits: só para o produto intermediário de `mul_div` sobre U256.
+        pub struct U512(8);
+    }
 }
+pub use u256::{U256, U512};
 
 pub type Wad = u128;   // escala 1e18
 pub type Ppm = u32;    // 0..=1_000_000
@@ -14,8 +23,34 @@ pub const WAD: Wad = 1_000_000_000_000_000_000u128; // 1e18
 pub const PPM_SCALE: Ppm = 1_000_000;                // 1e6 (ppm)
 pub const MIN_RESERVE: Wad = WAD;                    // 1 unidade inteira (1e-18 do ativo)
 
+/// Identificador de conta (holder, LP, tomador ou fornecedor).
+pub type AccountId = u64;
+
+/// Segundos num ano de 365 dias (base de APRs e taxas anuais).
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Fase de um mercado de outcome (`settlement`); fica aqui para os erros não dependerem do ciclo de vida.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MarketPhase { Open, Halted, Resolved, Settled }
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Reserves { pub x: Wad, pub y: Wad }
 impl Reserves {
     pub fn new(x: Wad, y: Wad) -> Self { Self { x, y } }
 }
+
+/// Sentido da troca: qual reserva recebe o input e qual entrega o output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction { XtoY, YtoX }
+impl Direction {
+    /// Ordena `(x, y)` como `(reserva_in, reserva_out)` para este sentido.
+    #[inline]
+    pub fn orient(self, x: Wad, y: Wad) -> (Wad, Wad) {
+        match self { Direction::XtoY => (x, y), Direction::YtoX => (y, x) }
+    }
+    /// Sentido oposto.
+    #[inline]
+    pub fn reverse(self) -> Self {
+        match self { Direction::XtoY => Direction::YtoX, Direction::YtoX => Direction::XtoY }
+    }
+}
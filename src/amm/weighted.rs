@@ -0,0 +1,389 @@
+//! Pools com pesos (estilo Balancer): invariante `V = Π b_i^w_i` com `Σ w_i = 1` (em WAD),
+//! de 2 a `MAX_ASSETS` ativos (ex.: 80/20 colateral/stable).
+//! Potências via `fixed_math` (escala 1e36) com cotas direcionadas: a aproximação sempre favorece o pool.
+//!
+//! Política (ADR-0001):
+//! - fee(input): ceil, cobrada antes da curva (como em `swap.rs`);
+//! - out-given-in: `out = floor(b_o·(1 - up((b_i / (b_i + a_net))^(w_i/w_o))))`;
+//! - in-given-out: `a_net = ceil(b_i·(up((b_o / (b_o - a_o))^(w_o/w_i)) - 1))`, bruto ceil e
+//!   ajustado até `get_amount_out(in) ≥ a_o` (as duas direções arredondam contra o usuário);
+//! - join proporcional: amounts ceil; exit proporcional: amounts floor;
+//! - spot: nearest (ties-to-even).
+
+use super::errors::AmmError;
+use super::fixed_math::{exp_e36, ln_e36, mul_div_signed, pow_ratio_up, E36};
+use super::guardrails::{
+    checked_add, checked_sub, div_nearest_even_u256_to_u128, ensure_fee, ensure_nonzero, ensure_reserves,
+    u256_to_u128_checked,
+};
+use super::swap::fee_on_input_ceil;
+use super::types::{Ppm, U256, Wad, MIN_RESERVE, PPM_SCALE, WAD};
+
+/// Máximo de ativos num pool.
+pub const MAX_ASSETS: usize = 8;
+/// Menor peso aceito (1%): limita o expoente `w_i/w_o` a 99.
+pub const MIN_WEIGHT: Wad = WAD / 100;
+
+#[inline]
+fn ensure_weight_pair(w_in: Wad, w_out: Wad) -> Result<(), AmmError> {
+    if w_in == 0 || w_out == 0 { Err(AmmError::InvalidWeights) } else { Ok(()) }
+}
+
+/// Pesos normalizados: `2..=MAX_ASSETS` entradas, cada uma ≥ `MIN_WEIGHT`, soma exatamente `WAD`.
+pub fn ensure_weights(weights: &[Wad]) -> Result<(), AmmError> {
+    if weights.len() < 2 || weights.len() > MAX_ASSETS { return Err(AmmError::InvalidWeights); }
+    if weights.iter().any(|&w| w < MIN_WEIGHT) { return Err(AmmError::InvalidWeights); }
+    let sum = weights.iter().try_fold(0u128, |acc, &w| acc.checked_add(w));
+    if sum != Some(WAD) { return Err(AmmError::InvalidWeights); }
+    Ok(())
+}
+
+#[inline]
+fn ensure_balance(b: Wad) -> Result<(), AmmError> { ensure_reserves(b, b) }
+
+/// `floor(b·f / 1e36)`
+#[inline]
+fn mul_e36_floor(b: Wad, f: U256) -> Result<Wad, AmmError> {
+    let n = U256::from(b).checked_mul(f).ok_or(AmmError::Overflow)?;
+    u256_to_u128_checked(n / U256::from(E36))
+}
+
+/// `ceil(b·f / 1e36)`
+#[inline]
+fn mul_e36_ceil(b: Wad, f: U256) -> Result<Wad, AmmError> {
+    let n = U256::from(b).checked_mul(f).ok_or(AmmError::Overflow)?;
+    let d = U256::from(E36);
+    u256_to_u128_checked((n + d - U256::from(1u8)) / d)
+}
+
+/// Output ao enviar `amount_in` do ativo de saldo `b_in`/peso `w_in` e receber o de `b_out`/`w_out`.
+pub fn get_amount_out(b_in: Wad, w_in: Wad, b_out: Wad, w_out: Wad, amount_in: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
+    ensure_reserves(b_in, b_out)?;
+    ensure_weight_pair(w_in, w_out)?;
+    ensure_nonzero(amount_in)?;
+
+    let net = amount_in - fee_on_input_ceil(amount_in, fee_ppm);
+    if net == 0 { return Err(AmmError::InputTooSmall); }
+
+    // base < 1: potência por cima ⇒ complemento (e o output) por baixo
+    let p = pow_ratio_up(b_in, checked_add(b_in, net)?, w_in, w_out)?.min(U256::from(E36));
+    let out = mul_e36_floor(b_out, U256::from(E36) - p)?;
+    if out == 0 { return Err(AmmError::InputTooSmall); }
+    if b_out - out < MIN_RESERVE { return Err(AmmError::MinReserveBreached); }
+    Ok(out)
+}
+
+/// Input bruto para receber `amount_out`; garante `get_amount_out(.., in, ..) ≥ amount_out`.
+pub fn get_amount_in(b_in: Wad, w_in: Wad, b_out: Wad, w_out: Wad, amount_out: Wad, fee_ppm: Ppm) -> Result<Wad, AmmError> {
+    ensure_fee(fee_ppm)?;
+    ensure_reserves(b_in, b_out)?;
+    ensure_weight_pair(w_in, w_out)?;
+    ensure_nonzero(amount_out)?;
+    let available = b_out - MIN_RESERVE;
+    if amount_out >= b_out { return Err(AmmError::InsufficientLiquidity { requested: amount_out, available }); }
+    if amount_out >= available { return Err(AmmError::MinReserveBreached); }
+
+    // base > 1: potência por cima ⇒ input líquido por cima
+    let p = pow_ratio_up(b_out, b_out - amount_out, w_out, w_in)?;
+    let net = mul_e36_ceil(b_in, p.saturating_sub(U256::from(E36)))?.max(1);
+
+    // bruto: chute fechado `ceil(net / (1 - fee))` e ajuste local (net(g) é monótono)
+    let keep = U256::from(PPM_SCALE - fee_ppm);
+    let mut g = u256_to_u128_checked((U256::from(net) * U256::from(PPM_SCALE) + keep - U256::from(1u8)) / keep)?;
+    while g - fee_on_input_ceil(g, fee_ppm) < net { g = checked_add(g, 1)?; }
+
+    // consistência com exact-in: os dois lados arredondam a favor do pool, então completa em galope
+    // e fecha por bisseção. Sonda com erro (ex.: `MinReserveBreached`) conta como "grande demais":
+    // `lo` é sempre insuficiente e `hi` serve ou erra, então o predicado é monótono no intervalo
+    let probe = |g: Wad| get_amount_out(b_in, w_in, b_out, w_out, g, fee_ppm);
+    let short = |g: Wad| match probe(g) {
+        Ok(out) => out < amount_out,
+        Err(e) => e == AmmError::InputTooSmall,
+    };
+    if probe(g).is_ok_and(|out| out >= amount_out) { return Ok(g); }
+    let (mut lo, mut hi) = (0, g);
+    let mut step: Wad = 1;
+    while short(hi) {
+        lo = hi;
+        hi = checked_add(hi, step)?;
+        step = step.saturating_mul(2);
+    }
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if short(mid) { lo = mid; } else { hi = mid; }
+    }
+    // sem input que sirva dentro dos limites: devolve o erro da menor sonda acima do alvo
+    probe(hi).map(|_| hi)
+}
+
+/// Preço marginal de 1 unidade do ativo `base` no ativo `quote` (WAD): `(b_q/w_q) / (b_b/w_b)`.
+pub fn spot_price(b_base: Wad, w_base: Wad, b_quote: Wad, w_quote: Wad) -> Result<Wad, AmmError> {
+    ensure_reserves(b_base, b_quote)?;
+    ensure_weight_pair(w_base, w_quote)?;
+    let n = U256::from(b_quote) * U256::from(w_base) * U256::from(WAD);
+    let d = U256::from(b_base) * U256::from(w_quote);
+    div_nearest_even_u256_to_u128(n, d)
+}
+
+/// Invariante `V = Π b_i^w_i` em WAD (floor), calculado como `exp(Σ w_i·ln b_i)`.
+pub fn invariant(balances: &[Wad], weights: &[Wad]) -> Result<Wad, AmmError> {
+    if balances.len() != weights.len() { return Err(AmmError::InvalidWeights); }
+    let mut t: i128 = 0;
+    for (&b, &w) in balances.iter().zip(weights) {
+        if b == 0 { return Err(AmmError::ZeroReserve); }
+        t = t.checked_add(mul_div_signed(ln_e36(b)?, w, WAD)?).ok_or(AmmError::Overflow)?;
+    }
+    u256_to_u128_checked(exp_e36(t)? / U256::from(WAD))
+}
+
+/// Resultado de um swap aplicado ao pool com pesos.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightedSwapReceipt {
+    pub token_in: usize,
+    pub token_out: usize,
+    /// Input bruto (inclui a taxa)
+    pub amount_in: Wad,
+    pub amount_out: Wad,
+    /// Parte do input retida como taxa (ceil), fica no pool
+    pub fee: Wad,
+}
+
+/// Resultado de um join/exit proporcional.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedLiquidityReceipt {
+    /// Amounts por ativo (depositados no join, sacados no exit)
+    pub amounts: Vec<Wad>,
+    /// Shares mintados (join) ou queimados (exit)
+    pub shares: Wad,
+}
+
+/// Pool com pesos e estado. Como `Pool`, cada operação calcula o estado novo inteiro antes de gravar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedPool {
+    balances: Vec<Wad>,
+    weights: Vec<Wad>,
+    fee_ppm: Ppm,
+    total_shares: Wad,
+}
+
+impl WeightedPool {
+    /// Cria o pool com o depósito inicial `balances`; o supply inicial é o invariante `V` (floor).
+    pub fn new(balances: Vec<Wad>, weights: Vec<Wad>, fee_ppm: Ppm) -> Result<Self, AmmError> {
+        ensure_weights(&weights)?;
+        let total_shares = invariant(&balances, &weights)?;
+        Self::from_state(balances, weights, fee_ppm, total_shares)
+    }
+
+    /// Reconstrói um pool a partir de estado já existente (ex.: carregado de storage).
+    pub fn from_state(balances: Vec<Wad>, weights: Vec<Wad>, fee_ppm: Ppm, total_shares: Wad) -> Result<Self, AmmError> {
+        ensure_fee(fee_ppm)?;
+        ensure_weights(&weights)?;
+        if balances.len() != weights.len() { return Err(AmmError::InvalidWeights); }
+        for &b in &balances { ensure_balance(b)?; }
+        if total_shares == 0 { return Err(AmmError::EmptyPool); }
+        Ok(Self { balances, weights, fee_ppm, total_shares })
+    }
+
+    pub fn balances(&self) -> &[Wad] { &self.balances }
+    pub fn weights(&self) -> &[Wad] { &self.weights }
+    pub fn fee_ppm(&self) -> Ppm { self.fee_ppm }
+    pub fn total_shares(&self) -> Wad { self.total_shares }
+
+    /// Invariante `V` atual.
+    pub fn invariant(&self) -> Result<Wad, AmmError> { invariant(&self.balances, &self.weights) }
+
+    /// Preço marginal de 1 `base` em `quote` (WAD).
+    pub fn spot_price(&self, base: usize, quote: usize) -> Result<Wad, AmmError> {
+        self.check_pair(base, quote)?;
+        spot_price(self.balances[base], self.weights[base], self.balances[quote], self.weights[quote])
+    }
+
+    #[inline]
+    fn check_pair(&self, token_in: usize, token_out: usize) -> Result<(), AmmError> {
+        let len = self.balances.len();
+        if token_in >= len { return Err(AmmError::InvalidAssetIndex { index: token_in, len }); }
+        if token_out >= len || token_out == token_in { return Err(AmmError::InvalidAssetIndex { index: token_out, len }); }
+        Ok(())
+    }
+
+    #[inline]
+    fn check_len(&self, limits: &[Wad]) -> Result<(), AmmError> {
+        let len = self.balances.len();
+        if limits.len() != len { return Err(AmmError::InvalidAssetIndex { index: limits.len(), len }); }
+        Ok(())
+    }
+
+    /// Swap com input exato; exige output ≥ `min_out`.
+    pub fn swap_exact_in(&mut self, token_in: usize, token_out: usize, amount_in: Wad, min_out: Wad) -> Result<WeightedSwapReceipt, AmmError> {
+        self.check_pair(token_in, token_out)?;
+        let (i, o) = (token_in, token_out);
+        let out = get_amount_out(self.balances[i], self.weights[i], self.balances[o], self.weights[o], amount_in, self.fee_ppm)?;
+        if out < min_out { return Err(AmmError::SlippageExceeded { expected: min_out, actual: out }); }
+        self.commit_swap(i, o, amount_in, out)
+    }
+
+    /// Swap com output exato; exige input ≤ `max_in`.
+    pub fn swap_exact_out(&mut self, token_in: usize, token_out: usize, amount_out: Wad, max_in: Wad) -> Result<WeightedSwapReceipt, AmmError> {
+        self.check_pair(token_in, token_out)?;
+        let (i, o) = (token_in, token_out);
+        let amount_in = get_amount_in(self.balances[i], self.weights[i], self.balances[o], self.weights[o], amount_out, self.fee_ppm)?;
+        if amount_in > max_in { return Err(AmmError::SlippageExceeded { expected: max_in, actual: amount_in }); }
+        self.commit_swap(i, o, amount_in, amount_out)
+    }
+
+    fn commit_swap(&mut self, i: usize, o: usize, amount_in: Wad, amount_out: Wad) -> Result<WeightedSwapReceipt, AmmError> {
+        let b_in = checked_add(self.balances[i], amount_in)?;
+        let b_out = checked_sub(self.balances[o], amount_out)?;
+        ensure_balance(b_out)?;
+        self.balances[i] = b_in;
+        self.balances[o] = b_out;
+        let fee = fee_on_input_ceil(amount_in, self.fee_ppm);
+        Ok(WeightedSwapReceipt { token_in: i, token_out: o, amount_in, amount_out, fee })
+    }
+
+    /// Join proporcional: minta `shares` depositando `ceil(b_i·shares/S)` de cada ativo (≤ `max_amounts_in`).
+    pub fn join(&mut self, shares: Wad, max_amounts_in: &[Wad]) -> Result<WeightedLiquidityReceipt, AmmError> {
+        ensure_nonzero(shares)?;
+        self.check_len(max_amounts_in)?;
+        let s = U256::from(self.total_shares);
+        let mut amounts = Vec::with_capacity(self.balances.len());
+        let mut balances = self.balances.clone();
+        for (b, &max_in) in balances.iter_mut().zip(max_amounts_in) {
+            let a = u256_to_u128_checked((U256::from(*b) * U256::from(shares) + s - U256::from(1u8)) / s)?;
+            if a > max_in { return Err(AmmError::SlippageExceeded { expected: max_in, actual: a }); }
+            *b = checked_add(*b, a)?;
+            amounts.push(a);
+        }
+        let s1 = checked_add(self.total_shares, shares)?;
+        self.balances = balances;
+        self.total_shares = s1;
+        Ok(WeightedLiquidityReceipt { amounts, shares })
+    }
+
+    /// Exit proporcional: queima `shares` e saca `floor(b_i·shares/S)` de cada ativo (≥ `min_amounts_out`).
+    pub fn exit(&mut self, shares: Wad, min_amounts_out: &[Wad]) -> Result<WeightedLiquidityReceipt, AmmError> {
+        ensure_nonzero(shares)?;
+        self.check_len(min_amounts_out)?;
+        if shares > self.total_shares {
+            return Err(AmmError::InsufficientShares { requested: shares, available: self.total_shares });
+        }
+        let s = U256::from(self.total_shares);
+        let mut amounts = Vec::with_capacity(self.balances.len());
+        let mut balances = self.balances.clone();
+        for (b, &min_out) in balances.iter_mut().zip(min_amounts_out) {
+            let a = u256_to_u128_checked(U256::from(*b) * U256::from(shares) / s)?;
+            if a < min_out { return Err(AmmError::SlippageExceeded { expected: min_out, actual: a }); }
+            *b -= a;
+            ensure_balance(*b)?;
+            amounts.push(a);
+        }
+        if amounts.iter().all(|&a| a == 0) { return Err(AmmError::InputTooSmall); }
+        self.balances = balances;
+        self.total_shares -= shares;
+        Ok(WeightedLiquidityReceipt { amounts, shares })
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::swap;
+
+    const FEE3: Ppm = 3000; // 0,30%
+    const W80: Wad = 8 * WAD / 10;
+    const W20: Wad = 2 * WAD / 10;
+
+    fn pool_80_20() -> WeightedPool {
+        WeightedPool::new(vec![800_000u128 * WAD, 200_000u128 * WAD], vec![W80, W20], FEE3).unwrap()
+    }
+
+    #[test]
+    fn t_equal_weights_match_cpmm() {
+        let (x, y, dx) = (1_000_000u128 * WAD, 2_000_000u128 * WAD, 10_000u128 * WAD);
+        let w = WAD / 2;
+        let weighted = get_amount_out(x, w, y, w, dx, FEE3).unwrap();
+        let cpmm = swap::get_amount_out(x, y, dx, FEE3).unwrap();
+        assert!(weighted <= cpmm && cpmm - weighted <= 2, "weighted={} cpmm={}", weighted, cpmm);
+        assert_eq!(spot_price(x, w, y, w).unwrap(), 2 * WAD);
+    }
+
+    #[test]
+    fn t_80_20_spot_and_out() {
+        let p = pool_80_20();
+        // (200k/0.2) / (800k/0.8) = 1
+        assert_eq!(p.spot_price(0, 1).unwrap(), WAD);
+        // out = 200k·(1 - (800k/801k)^4)
+        let out = get_amount_out(800_000u128 * WAD, W80, 200_000u128 * WAD, W20, 1_000u128 * WAD, 0).unwrap();
+        let exact = 200_000.0 * (1.0 - (800_000.0f64 / 801_000.0).powi(4));
+        assert!((out as f64 / WAD as f64 - exact).abs() < 1e-6, "out={} exact={}", out, exact);
+    }
+
+    #[test]
+    fn t_amount_in_round_trip() {
+        let (b_in, b_out) = (800_000u128 * WAD, 200_000u128 * WAD);
+        let dy = 5_000u128 * WAD;
+        let dx = get_amount_in(b_in, W80, b_out, W20, dy, FEE3).unwrap();
+        assert!(get_amount_out(b_in, W80, b_out, W20, dx, FEE3).unwrap() >= dy);
+        let back = get_amount_in(b_out, W20, b_in, W80, dy, FEE3).unwrap();
+        assert!(get_amount_out(b_out, W20, b_in, W80, back, FEE3).unwrap() >= dy);
+    }
+
+    #[test]
+    fn t_amount_in_probe_errors_do_not_abort() {
+        // alvos nos extremos: sondas com erro (out = 0, reserva mínima) não podem abortar a busca
+        let (b_in, b_out) = (800_000u128 * WAD, 200_000u128 * WAD);
+        for dy in [1, 2, 1_000, b_out - MIN_RESERVE - 1, b_out - 2 * MIN_RESERVE] {
+            let dx = get_amount_in(b_in, W80, b_out, W20, dy, FEE3).unwrap();
+            assert!(get_amount_out(b_in, W80, b_out, W20, dx, FEE3).unwrap() >= dy);
+            assert!(get_amount_out(b_in, W80, b_out, W20, dx - 1, FEE3).map_or(true, |o| o < dy), "dy={}", dy);
+        }
+    }
+
+    #[test]
+    fn t_invariant_never_decreases_on_swap() {
+        let mut p = WeightedPool::new(
+            vec![1_000_000u128 * WAD, 500_000u128 * WAD, 250_000u128 * WAD],
+            vec![WAD / 2, 3 * WAD / 10, W20],
+            FEE3,
+        ).unwrap();
+        let v0 = p.invariant().unwrap();
+        p.swap_exact_in(0, 2, 10_000u128 * WAD, 0).unwrap();
+        let v1 = p.invariant().unwrap();
+        p.swap_exact_out(2, 1, 7_000u128 * WAD, u128::MAX).unwrap();
+        let v2 = p.invariant().unwrap();
+        assert!(v1 >= v0 && v2 >= v1, "v0={} v1={} v2={}", v0, v1, v2);
+    }
+
+    #[test]
+    fn t_join_exit_proportional() {
+        let mut p = pool_80_20();
+        let s0 = p.total_shares();
+        let j = p.join(s0 / 10, &[u128::MAX, u128::MAX]).unwrap();
+        assert_eq!(j.amounts, vec![80_000u128 * WAD, 20_000u128 * WAD]);
+        assert_eq!(p.total_shares(), s0 + s0 / 10);
+        let e = p.exit(s0 / 10, &[0, 0]).unwrap();
+        // exit floor ≤ join ceil: o pool nunca perde no ciclo
+        assert!(e.amounts[0] <= j.amounts[0] && e.amounts[1] <= j.amounts[1]);
+        assert_eq!(p.total_shares(), s0);
+    }
+
+    #[test]
+    fn t_guards_and_atomicity() {
+        assert_eq!(WeightedPool::new(vec![WAD, WAD], vec![WAD / 2, WAD / 3], 0).unwrap_err(), AmmError::InvalidWeights);
+        assert_eq!(WeightedPool::new(vec![WAD, WAD], vec![WAD - WAD / 1000, WAD / 1000], 0).unwrap_err(), AmmError::InvalidWeights);
+        assert_eq!(WeightedPool::new(vec![WAD; 9], vec![WAD / 9; 9], 0).unwrap_err(), AmmError::InvalidWeights);
+        let mut p = pool_80_20();
+        let before = p.clone();
+        assert_eq!(p.swap_exact_in(0, 0, WAD, 0).unwrap_err(), AmmError::InvalidAssetIndex { index: 0, len: 2 });
+        assert_eq!(p.swap_exact_in(0, 5, WAD, 0).unwrap_err(), AmmError::InvalidAssetIndex { index: 5, len: 2 });
+        assert!(matches!(p.swap_exact_in(0, 1, WAD, u128::MAX), Err(AmmError::SlippageExceeded { .. })));
+        assert!(matches!(p.join(WAD, &[0, 0]), Err(AmmError::SlippageExceeded { .. })));
+        assert!(matches!(p.exit(p.total_shares() + 1, &[0, 0]), Err(AmmError::InsufficientShares { .. })));
+        assert!(matches!(p.swap_exact_out(0, 1, 200_000u128 * WAD, u128::MAX), Err(AmmError::InsufficientLiquidity { .. })));
+        assert_eq!(p, before);
+    }
+}
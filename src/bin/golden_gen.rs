@@ -0,0 +1,31 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use credit_engine_core::golden::{clmm, clmm_pool, gen, sha256_hex};
+
+/// Uso: `golden_gen [saida.csv]` (padrão: goldens/amm_cpmw_v2.csv) ou
+/// `golden_gen clmm [saida.csv]` (padrão: goldens/amm_clmm_v1.csv) ou
+/// `golden_gen clmm-pool [saida.csv]` (padrão: goldens/amm_clmm_pool_v1.csv). Grava também `<saida>.sha256`.
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1).peekable();
+    let (csv, default) = match args.peek().map(String::as_str) {
+        Some("clmm") => {
+            args.next();
+            (clmm::render_v1()?, "goldens/amm_clmm_v1.csv")
+        }
+        Some("clmm-pool") => {
+            args.next();
+            (clmm_pool::render_v1()?, "goldens/amm_clmm_pool_v1.csv")
+        }
+        _ => (gen::render_v2()?, "goldens/amm_cpmw_v2.csv"),
+    };
+    let path = args.next().map(PathBuf::from).unwrap_or_else(|| PathBuf::from(default));
+    std::fs::write(&path, &csv).with_context(|| format!("gravando {}", path.display()))?;
+
+    let mut sha_path = path.as_os_str().to_owned();
+    sha_path.push(".sha256");
+    std::fs::write(&sha_path, format!("{}  {}\n", sha256_hex(csv.as_bytes()), path.display()))
+        .with_context(|| format!("gravando {:?}", sha_path))?;
+    println!("{}: {} linhas", path.display(), csv.lines().filter(|l| !l.starts_with('#')).count() - 1);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anyhow::{bail, Context, Result};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use credit_engine_core::golden;
+
+/// Nome do formato na primeira linha (`# format: <nome>/<versão>`); vazio em arquivo sem cabeçalho (v1).
+fn format_name(path: &Path) -> Result<String> {
+    let file = std::fs::File::open(path).with_context(|| format!("abrindo {}", path.display()))?;
+    let mut first = String::new();
+    std::io::BufReader::new(file).read_line(&mut first)?;
+    let name = first.trim_end().strip_prefix("# format:").and_then(|f| f.trim().split('/').next());
+    Ok(name.unwrap_or_default().to_string())
+}
+
+/// Uso: `golden_runner [arquivo.csv ...]` (padrão: goldens/amm_cpmw_v1.csv); o formato vem do `# format:`.
+fn main() -> Result<()> {
+    let mut paths: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() { paths.push(PathBuf::from("goldens/amm_cpmw_v1.csv")); }
+
+    let mut failed = 0usize;
+    for path in &paths {
+        let format = format_name(path)?;
+        let report = if format == golden::clmm_pool::FORMAT_NAME {
+            golden::clmm_pool::run_file(path)?
+        } else if format == golden::clmm::FORMAT_NAME {
+            golden::clmm::run_file(path)?
+        } else {
+            golden::run_file(path)?
+        };
+        for (id, msg) in &report.failures {
+            println!("FAIL {} {}: {}", path.display(), id, msg);
+        }
+        println!("{}: {} ok, {} falhas", path.display(), report.passed, report.failures.len());
+        failed += report.failures.len();
+    }
+    if failed > 0 { bail!("{} linha(s) golden divergente(s)", failed); }
+    Ok(())
+}
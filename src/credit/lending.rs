@@ -0,0 +1,532 @@
+//! Linhas de crédito colateralizadas: oferta de liquidez, depósito de colateral, empréstimo limitado
+//! por LTV, juros acumulados em índices e health factor.
+//!
+//! Um `CreditMarket` empresta o ativo de saída de `params.collateral` (um `Direction` de um pool do AMM)
+//! contra o ativo de entrada. O colateral é cotado por `PriceSource`: spot das reservas do pool
+//! (`spot_price_dir`) ou um preço médio informado pelo chamador (ex.: `Oracle::consult`), com haircut.
+//!
+//! Contabilidade por índices: cada linha guarda `scaled_debt` e `scaled_supply`; a dívida é
+//! `scaled_debt·borrow_index/WAD` e a oferta `scaled_supply·supply_index/WAD`. A cada accrual o
+//! `InterestRateModel` (ver `rates`) dá o crescimento do `borrow_index` à utilização vigente e os juros
+//! novos da dívida total são repassados aos fornecedores pelo `supply_index`. Os empréstimos saem do
+//! caixa (`cash`): oferta ainda não emprestada mais pagamentos.
+//!
+//! Arredondamento (ADR-0001, sempre a favor do mercado):
+//! - valor do colateral, limites de LTV, health factor, oferta e crescimento do `supply_index`: **floor**;
+//! - dívida e crescimento do `borrow_index`: **ceil**;
+//! - shares de dívida no empréstimo e de oferta no resgate: **ceil**; no pagamento e no depósito: **floor**.
+//!
+//! Assim a oferta total nunca passa de `cash + dívida total`. Como no `Pool`, cada operação calcula
+//! tudo antes de gravar (inclusive o accrual e o estado do modelo): em erro, nada muda.
+
+use std::collections::BTreeMap;
+
+use super::rates::{grow_index, InterestRateModel};
+use crate::amm::errors::AmmError;
+use crate::amm::guardrails::{
+    checked_add, checked_sub, ensure_nonzero, mul_div_ceil_u256, mul_div_floor_u256, mul_u128_to_u256, u256_to_u128_checked,
+};
+use crate::amm::pricing::spot_price_dir;
+use crate::amm::types::{AccountId, Direction, Ppm, Reserves, U256, Wad, PPM_SCALE, WAD};
+
+/// Origem do preço do colateral (1 unidade de colateral cotada no ativo da dívida, WAD).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceSource {
+    /// Spot das reservas atuais do pool
+    Spot(Reserves),
+    /// Preço médio (TWAP etc.) calculado pelo chamador
+    Averaged(Wad),
+}
+
+impl PriceSource {
+    /// Preço do ativo de entrada de `collateral` no ativo de saída.
+    pub fn price(&self, collateral: Direction) -> Result<Wad, AmmError> {
+        match *self {
+            PriceSource::Spot(r) => spot_price_dir(collateral, r.x, r.y),
+            PriceSource::Averaged(p) => {
+                ensure_nonzero(p)?;
+                Ok(p)
+            }
+        }
+    }
+}
+
+/// Parâmetros de risco do mercado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CreditParams {
+    /// Ativo de entrada = colateral, ativo de saída = dívida
+    pub collateral: Direction,
+    /// Desconto aplicado ao preço do colateral
+    pub haircut_ppm: Ppm,
+    /// Dívida máxima / valor do colateral para novos empréstimos e saques
+    pub max_ltv_ppm: Ppm,
+    /// Dívida / valor do colateral a partir do qual o health factor fica < 1
+    pub liquidation_threshold_ppm: Ppm,
+}
+
+impl CreditParams {
+    pub fn validate(&self) -> Result<(), AmmError> {
+        let ok = self.haircut_ppm < PPM_SCALE
+            && self.max_ltv_ppm > 0
+            && self.max_ltv_ppm <= self.liquidation_threshold_ppm
+            && self.liquidation_threshold_ppm <= PPM_SCALE;
+        if ok { Ok(()) } else { Err(AmmError::InvalidCreditParams) }
+    }
+}
+
+/// Estado gravado de uma conta no mercado.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CreditLine {
+    pub collateral: Wad,
+    /// Dívida em unidades do índice (`debt = scaled_debt·borrow_index/WAD`)
+    pub scaled_debt: Wad,
+    /// Oferta em unidades do índice (`supply = scaled_supply·supply_index/WAD`)
+    pub scaled_supply: Wad,
+}
+
+/// Linha de crédito avaliada a um preço.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CreditPosition {
+    pub collateral: Wad,
+    pub debt: Wad,
+    /// Colateral ao preço com haircut, no ativo da dívida
+    pub collateral_value: Wad,
+    /// Dívida máxima permitida (`collateral_value·max_ltv`)
+    pub max_debt: Wad,
+    /// Dívida a partir da qual a linha é liquidável (`collateral_value·limiar`)
+    pub liquidation_debt: Wad,
+    /// `liquidation_debt / debt` (WAD); `Wad::MAX` sem dívida
+    pub health_factor: Wad,
+}
+
+impl CreditPosition {
+    /// Health factor abaixo de 1.
+    pub fn is_liquidatable(&self) -> bool { self.health_factor < WAD }
+}
+
+fn ppm_floor(v: Wad, ppm: Ppm) -> Result<Wad, AmmError> {
+    u256_to_u128_checked(mul_u128_to_u256(v, ppm.into()) / U256::from(PPM_SCALE))
+}
+
+fn scaled_to_amount(scaled: Wad, index: Wad, ceil: bool) -> Result<Wad, AmmError> {
+    let (s, i, w) = (U256::from(scaled), U256::from(index), U256::from(WAD));
+    u256_to_u128_checked(if ceil { mul_div_ceil_u256(s, i, w)? } else { mul_div_floor_u256(s, i, w)? })
+}
+
+fn amount_to_scaled(amount: Wad, index: Wad, ceil: bool) -> Result<Wad, AmmError> {
+    let (a, w, i) = (U256::from(amount), U256::from(WAD), U256::from(index));
+    u256_to_u128_checked(if ceil { mul_div_ceil_u256(a, w, i)? } else { mul_div_floor_u256(a, w, i)? })
+}
+
+/// Índices e estado do modelo calculados para `now`, ainda não gravados.
+#[derive(Clone, Debug)]
+struct Accrual<M> {
+    borrow_index: Wad,
+    supply_index: Wad,
+    model: M,
+    now: u64,
+}
+
+/// Mercado de crédito de um par colateral/dívida com o modelo de juros `M`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreditMarket<M> {
+    params: CreditParams,
+    model: M,
+    /// Índices de dívida e de oferta (WAD, começam em 1)
+    borrow_index: Wad,
+    supply_index: Wad,
+    last_accrual: u64,
+    total_scaled_debt: Wad,
+    total_scaled_supply: Wad,
+    total_collateral: Wad,
+    /// Ativo da dívida disponível para empréstimo e resgates
+    cash: Wad,
+    /// Só contas com colateral, dívida ou oferta
+    lines: BTreeMap<AccountId, CreditLine>,
+}
+
+impl<M: InterestRateModel + Clone> CreditMarket<M> {
+    pub fn new(params: CreditParams, model: M, now: u64) -> Result<Self, AmmError> {
+        params.validate()?;
+        Ok(Self {
+            params,
+            model,
+            borrow_index: WAD,
+            supply_index: WAD,
+            last_accrual: now,
+            total_scaled_debt: 0,
+            total_scaled_supply: 0,
+            total_collateral: 0,
+            cash: 0,
+            lines: BTreeMap::new(),
+        })
+    }
+
+    pub fn params(&self) -> &CreditParams { &self.params }
+    pub fn model(&self) -> &M { &self.model }
+    pub fn borrow_index(&self) -> Wad { self.borrow_index }
+    pub fn supply_index(&self) -> Wad { self.supply_index }
+    pub fn last_accrual(&self) -> u64 { self.last_accrual }
+    pub fn total_collateral(&self) -> Wad { self.total_collateral }
+    pub fn cash(&self) -> Wad { self.cash }
+    /// Soma das `scaled_debt` de todas as linhas.
+    pub fn total_scaled_debt(&self) -> Wad { self.total_scaled_debt }
+    /// Soma das `scaled_supply` de todas as linhas.
+    pub fn total_scaled_supply(&self) -> Wad { self.total_scaled_supply }
+    /// Dívida total ao índice atual (ceil).
+    pub fn total_debt(&self) -> Result<Wad, AmmError> { scaled_to_amount(self.total_scaled_debt, self.borrow_index, true) }
+    /// Oferta total ao índice atual (floor).
+    pub fn total_supply(&self) -> Result<Wad, AmmError> { scaled_to_amount(self.total_scaled_supply, self.supply_index, false) }
+    pub fn line(&self, owner: AccountId) -> CreditLine { self.lines.get(&owner).copied().unwrap_or_default() }
+    /// Contas abertas, em ordem de id.
+    pub fn lines(&self) -> impl Iterator<Item = (AccountId, CreditLine)> + '_ { self.lines.iter().map(|(&o, &l)| (o, l)) }
+
+    /// Dívida de `owner` ao índice atual (ceil; chame `accrue` antes para incluir juros pendentes).
+    pub fn debt_of(&self, owner: AccountId) -> Result<Wad, AmmError> { scaled_to_amount(self.line(owner).scaled_debt, self.borrow_index, true) }
+    /// Oferta de `owner` ao índice atual (floor).
+    pub fn supply_of(&self, owner: AccountId) -> Result<Wad, AmmError> { scaled_to_amount(self.line(owner).scaled_supply, self.supply_index, false) }
+
+    /// `dívida total / oferta total` (WAD, floor, no máximo 1); 0 sem oferta.
+    pub fn utilization(&self) -> Result<Wad, AmmError> {
+        let supply = self.total_supply()?;
+        if supply == 0 { return Ok(0); }
+        let u = mul_div_floor_u256(U256::from(self.total_debt()?), U256::from(WAD), U256::from(supply))?;
+        Ok(u256_to_u128_checked(u)?.min(WAD))
+    }
+
+    /// Taxa anual de empréstimo vigente (WAD).
+    pub fn borrow_rate(&self) -> Result<Wad, AmmError> { self.model.borrow_rate(self.utilization()?) }
+
+    /// Juros de `last_accrual` até `now` à utilização atual: `borrow_index·e^g` (ceil) e os juros
+    /// da dívida total repassados ao `supply_index` (floor).
+    fn accrual(&self, now: u64) -> Result<Accrual<M>, AmmError> {
+        if now < self.last_accrual { return Err(AmmError::TimestampRegression { last: self.last_accrual, now }); }
+        let mut model = self.model.clone();
+        let growth = model.accrue(self.utilization()?, now - self.last_accrual)?;
+        let borrow_index = grow_index(self.borrow_index, growth)?;
+        let interest = checked_sub(scaled_to_amount(self.total_scaled_debt, borrow_index, true)?, self.total_debt()?)?;
+        let supply_index = if self.total_scaled_supply == 0 {
+            self.supply_index
+        } else {
+            checked_add(self.supply_index, amount_to_scaled(interest, self.total_scaled_supply, false)?)?
+        };
+        Ok(Accrual { borrow_index, supply_index, model, now })
+    }
+
+    /// Índice de dívida em `now` sem gravar.
+    pub fn index_at(&self, now: u64) -> Result<Wad, AmmError> { Ok(self.accrual(now)?.borrow_index) }
+
+    fn apply_accrual(&mut self, acc: Accrual<M>) {
+        self.borrow_index = acc.borrow_index;
+        self.supply_index = acc.supply_index;
+        self.model = acc.model;
+        self.last_accrual = acc.now;
+    }
+
+    /// Acumula os juros até `now`.
+    pub fn accrue(&mut self, now: u64) -> Result<(), AmmError> {
+        let acc = self.accrual(now)?;
+        self.apply_accrual(acc);
+        Ok(())
+    }
+
+    fn commit(&mut self, owner: AccountId, line: CreditLine, acc: Accrual<M>) {
+        self.apply_accrual(acc);
+        if line == CreditLine::default() { self.lines.remove(&owner); } else { self.lines.insert(owner, line); }
+    }
+
+    fn evaluate(&self, line: CreditLine, borrow_index: Wad, price: &PriceSource) -> Result<CreditPosition, AmmError> {
+        let p = price.price(self.params.collateral)?;
+        let haircut_price = ppm_floor(p, PPM_SCALE - self.params.haircut_ppm)?;
+        let collateral_value = u256_to_u128_checked(mul_u128_to_u256(line.collateral, haircut_price) / U256::from(WAD))?;
+        let debt = scaled_to_amount(line.scaled_debt, borrow_index, true)?;
+        let liquidation_debt = ppm_floor(collateral_value, self.params.liquidation_threshold_ppm)?;
+        let health_factor = if debt == 0 {
+            Wad::MAX
+        } else {
+            u256_to_u128_checked(mul_div_floor_u256(U256::from(liquidation_debt), U256::from(WAD), U256::from(debt))?)?
+        };
+        Ok(CreditPosition {
+            collateral: line.collateral,
+            debt,
+            collateral_value,
+            max_debt: ppm_floor(collateral_value, self.params.max_ltv_ppm)?,
+            liquidation_debt,
+            health_factor,
+        })
+    }
+
+    /// Linha de `owner` avaliada em `price`, com os juros até `now`.
+    pub fn position(&self, owner: AccountId, price: &PriceSource, now: u64) -> Result<CreditPosition, AmmError> {
+        self.evaluate(self.line(owner), self.index_at(now)?, price)
+    }
+
+    fn ensure_within_ltv(&self, line: CreditLine, borrow_index: Wad, price: &PriceSource) -> Result<(), AmmError> {
+        let pos = self.evaluate(line, borrow_index, price)?;
+        if pos.debt > pos.max_debt { return Err(AmmError::LtvExceeded { debt: pos.debt, limit: pos.max_debt }); }
+        Ok(())
+    }
+
+    fn ensure_cash(&self, amount: Wad) -> Result<(), AmmError> {
+        if amount > self.cash { return Err(AmmError::InsufficientLiquidity { requested: amount, available: self.cash }); }
+        Ok(())
+    }
+
+    /// Fornece `amount` do ativo da dívida ao mercado.
+    pub fn supply(&mut self, owner: AccountId, amount: Wad, now: u64) -> Result<(), AmmError> {
+        ensure_nonzero(amount)?;
+        let acc = self.accrual(now)?;
+        let scaled = amount_to_scaled(amount, acc.supply_index, false)?;
+        let mut line = self.line(owner);
+        line.scaled_supply = checked_add(line.scaled_supply, scaled)?;
+        let total = checked_add(self.total_scaled_supply, scaled)?;
+        let cash = checked_add(self.cash, amount)?;
+        self.total_scaled_supply = total;
+        self.cash = cash;
+        self.commit(owner, line, acc);
+        Ok(())
+    }
+
+    /// Resgata `amount` da oferta de `owner` (limitado ao saldo e ao caixa).
+    pub fn withdraw_supply(&mut self, owner: AccountId, amount: Wad, now: u64) -> Result<(), AmmError> {
+        ensure_nonzero(amount)?;
+        let acc = self.accrual(now)?;
+        let mut line = self.line(owner);
+        let available = scaled_to_amount(line.scaled_supply, acc.supply_index, false)?;
+        if amount > available { return Err(AmmError::InsufficientSupply { requested: amount, available }); }
+        self.ensure_cash(amount)?;
+        // amount ≤ floor(scaled·index/WAD) ⇒ ceil(amount·WAD/index) ≤ scaled
+        let scaled = amount_to_scaled(amount, acc.supply_index, true)?;
+        line.scaled_supply -= scaled;
+        self.total_scaled_supply = checked_sub(self.total_scaled_supply, scaled)?;
+        self.cash -= amount;
+        self.commit(owner, line, acc);
+        Ok(())
+    }
+
+    /// Deposita `amount` de colateral na linha de `owner`.
+    pub fn deposit_collateral(&mut self, owner: AccountId, amount: Wad, now: u64) -> Result<(), AmmError> {
+        ensure_nonzero(amount)?;
+        let acc = self.accrual(now)?;
+        let mut line = self.line(owner);
+        line.collateral = checked_add(line.collateral, amount)?;
+        self.total_collateral = checked_add(self.total_collateral, amount)?;
+        self.commit(owner, line, acc);
+        Ok(())
+    }
+
+    /// Saca `amount` de colateral; com dívida aberta, a linha precisa continuar dentro do LTV máximo.
+    pub fn withdraw_collateral(&mut self, owner: AccountId, amount: Wad, price: &PriceSource, now: u64) -> Result<(), AmmError> {
+        ensure_nonzero(amount)?;
+        let acc = self.accrual(now)?;
+        let mut line = self.line(owner);
+        if amount > line.collateral { return Err(AmmError::InsufficientCollateral { requested: amount, available: line.collateral }); }
+        line.collateral -= amount;
+        if line.scaled_debt > 0 { self.ensure_within_ltv(line, acc.borrow_index, price)?; }
+        self.total_collateral = checked_sub(self.total_collateral, amount)?;
+        self.commit(owner, line, acc);
+        Ok(())
+    }
+
+    /// Toma `amount` emprestado do caixa; a dívida resultante precisa caber no LTV máximo.
+    pub fn borrow(&mut self, owner: AccountId, amount: Wad, price: &PriceSource, now: u64) -> Result<(), AmmError> {
+        ensure_nonzero(amount)?;
+        let acc = self.accrual(now)?;
+        self.ensure_cash(amount)?;
+        let mut line = self.line(owner);
+        let scaled = amount_to_scaled(amount, acc.borrow_index, true)?;
+        line.scaled_debt = checked_add(line.scaled_debt, scaled)?;
+        self.ensure_within_ltv(line, acc.borrow_index, price)?;
+        self.total_scaled_debt = checked_add(self.total_scaled_debt, scaled)?;
+        self.cash -= amount;
+        self.commit(owner, line, acc);
+        Ok(())
+    }
+
+    /// Paga até `amount` da dívida de `owner`; devolve o valor efetivamente pago (≤ dívida).
+    pub fn repay(&mut self, owner: AccountId, amount: Wad, now: u64) -> Result<Wad, AmmError> {
+        ensure_nonzero(amount)?;
+        let acc = self.accrual(now)?;
+        let mut line = self.line(owner);
+        let debt = scaled_to_amount(line.scaled_debt, acc.borrow_index, true)?;
+        let (repaid, scaled) = if amount >= debt {
+            (debt, line.scaled_debt)
+        } else {
+            // amount < ceil(scaled·index/WAD) ⇒ floor(amount·WAD/index) ≤ scaled
+            (amount, amount_to_scaled(amount, acc.borrow_index, false)?)
+        };
+        line.scaled_debt -= scaled;
+        let total = checked_sub(self.total_scaled_debt, scaled)?;
+        let cash = checked_add(self.cash, repaid)?;
+        self.total_scaled_debt = total;
+        self.cash = cash;
+        self.commit(owner, line, acc);
+        Ok(repaid)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::types::SECONDS_PER_YEAR;
+    use crate::amm::pool::Pool;
+    use crate::credit::rates::{FixedRate, KinkedRate};
+
+    const ALICE: AccountId = 1;
+    const BOB: AccountId = 2;
+    const CAROL: AccountId = 3;
+    const YEAR: u64 = SECONDS_PER_YEAR;
+
+    fn params() -> CreditParams {
+        CreditParams { collateral: Direction::XtoY, haircut_ppm: 100_000, max_ltv_ppm: 500_000, liquidation_threshold_ppm: 800_000 }
+    }
+
+    /// Mercado com 1_000 de oferta de `BOB`.
+    fn market<M: InterestRateModel + Clone>(params: CreditParams, model: M) -> CreditMarket<M> {
+        let mut m = CreditMarket::new(params, model, 0).unwrap();
+        m.supply(BOB, 1_000 * WAD, 0).unwrap();
+        m
+    }
+
+    #[test]
+    fn t_params_validation() {
+        assert!(CreditMarket::new(params(), FixedRate(0), 0).is_ok());
+        for bad in [
+            CreditParams { haircut_ppm: PPM_SCALE, ..params() },
+            CreditParams { max_ltv_ppm: 0, ..params() },
+            CreditParams { max_ltv_ppm: 900_000, ..params() },
+            CreditParams { liquidation_threshold_ppm: PPM_SCALE + 1, ..params() },
+        ] {
+            assert_eq!(CreditMarket::new(bad, FixedRate(0), 0).unwrap_err(), AmmError::InvalidCreditParams);
+        }
+    }
+
+    #[test]
+    fn t_borrow_limited_by_haircut_and_ltv() {
+        // 1 X = 4 Y no pool
+        let pool = Pool::new(1_000 * WAD, 4_000 * WAD, 3_000).unwrap();
+        let spot = PriceSource::Spot(pool.reserves());
+        let mut m = market(params(), FixedRate(WAD / 10));
+        m.deposit_collateral(ALICE, 10 * WAD, 0).unwrap();
+        // 10 X · 4 · 0,9 = 36 Y; LTV 50% ⇒ 18 Y
+        let pos = m.position(ALICE, &spot, 0).unwrap();
+        assert_eq!((pos.collateral_value, pos.max_debt, pos.liquidation_debt), (36 * WAD, 18 * WAD, 288 * WAD / 10));
+        assert_eq!(pos.health_factor, Wad::MAX);
+        let before = m.clone();
+        assert_eq!(m.borrow(ALICE, 18 * WAD + 1, &spot, 0).unwrap_err(), AmmError::LtvExceeded { debt: 18 * WAD + 1, limit: 18 * WAD });
+        assert_eq!(m, before);
+        m.borrow(ALICE, 18 * WAD, &spot, 0).unwrap();
+        // HF = 28,8 / 18 = 1,6
+        assert_eq!(m.position(ALICE, &spot, 0).unwrap().health_factor, 16 * WAD / 10);
+        // preço médio informado pelo chamador no lugar do spot
+        let twap = PriceSource::Averaged(2 * WAD);
+        let pos = m.position(ALICE, &twap, 0).unwrap();
+        assert_eq!(pos.health_factor, 8 * WAD / 10);
+        assert!(pos.is_liquidatable());
+        assert_eq!(twap.price(Direction::YtoX).unwrap(), 2 * WAD);
+        assert_eq!(PriceSource::Averaged(0).price(Direction::XtoY).unwrap_err(), AmmError::ZeroAmount);
+    }
+
+    #[test]
+    fn t_interest_compounds_and_reaches_suppliers() {
+        let price = PriceSource::Averaged(WAD);
+        let mut m = market(CreditParams { haircut_ppm: 0, ..params() }, FixedRate(WAD / 10));
+        m.deposit_collateral(ALICE, 1_000 * WAD, 0).unwrap();
+        m.borrow(ALICE, 100 * WAD, &price, 0).unwrap();
+        // 10% a.a. composto por segundo: 100·(1 + 0,1/ano)^ano ≈ 110,5170918
+        let one_shot = m.position(ALICE, &price, YEAR).unwrap().debt;
+        assert!(one_shot.abs_diff(110_517_091_790 * WAD / 1_000_000_000) < WAD / 1_000_000_000, "{one_shot}");
+        // dividir o ano em accruals não muda o resultado (a menos de wei)
+        m.accrue(YEAR / 3).unwrap();
+        m.accrue(YEAR / 2).unwrap();
+        m.accrue(YEAR).unwrap();
+        assert!(m.debt_of(ALICE).unwrap().abs_diff(one_shot) <= 300);
+        // juros vão para a oferta, sem criar mais do que existe (floor do índice: ≤ 1_000 wei por accrual)
+        let interest = m.debt_of(ALICE).unwrap() - 100 * WAD;
+        let supply = m.supply_of(BOB).unwrap();
+        assert!(supply <= 1_000 * WAD + interest && 1_000 * WAD + interest - supply <= 3_000, "{supply} {interest}");
+        assert!(m.total_supply().unwrap() <= m.cash() + m.total_debt().unwrap());
+        // quem entra depois não paga os juros passados
+        m.deposit_collateral(CAROL, 1_000 * WAD, YEAR).unwrap();
+        m.borrow(CAROL, 100 * WAD, &price, YEAR).unwrap();
+        assert_eq!(m.debt_of(CAROL).unwrap() - 100 * WAD, 1);
+        assert_eq!(m.accrue(YEAR - 1).unwrap_err(), AmmError::TimestampRegression { last: YEAR, now: YEAR - 1 });
+        // índice > 1: a cota cabe, o caixa estoura — nada muda
+        let before = m.clone();
+        assert_eq!(m.supply(CAROL, u128::MAX - m.cash() + 1, YEAR).unwrap_err(), AmmError::Overflow);
+        assert_eq!(m, before);
+    }
+
+    #[test]
+    fn t_kinked_rate_follows_utilization() {
+        let price = PriceSource::Averaged(WAD);
+        let pct = WAD / 100;
+        let full = CreditParams { haircut_ppm: 0, max_ltv_ppm: 1_000_000, liquidation_threshold_ppm: 1_000_000, ..params() };
+        let mut m = market(full, KinkedRate::new(2 * pct, 8 * pct, 100 * pct, 80 * pct).unwrap());
+        m.deposit_collateral(ALICE, 10_000 * WAD, 0).unwrap();
+        assert_eq!(m.borrow_rate().unwrap(), 2 * pct);
+        m.borrow(ALICE, 400 * WAD, &price, 0).unwrap();
+        assert_eq!(m.utilization().unwrap(), 40 * pct);
+        assert_eq!(m.borrow_rate().unwrap(), 6 * pct);
+        m.borrow(ALICE, 500 * WAD, &price, 0).unwrap();
+        assert_eq!(m.borrow_rate().unwrap(), 60 * pct);
+        // acima do joelho o índice corre mais rápido
+        let fast = m.index_at(YEAR / 12).unwrap();
+        m.repay(ALICE, 500 * WAD, 0).unwrap();
+        assert!(m.index_at(YEAR / 12).unwrap() < fast);
+    }
+
+    #[test]
+    fn t_liquidity_and_supply_limits() {
+        let price = PriceSource::Averaged(WAD);
+        let mut m = market(CreditParams { haircut_ppm: 0, ..params() }, FixedRate(0));
+        m.deposit_collateral(ALICE, 10_000 * WAD, 0).unwrap();
+        assert_eq!(
+            m.borrow(ALICE, 1_001 * WAD, &price, 0).unwrap_err(),
+            AmmError::InsufficientLiquidity { requested: 1_001 * WAD, available: 1_000 * WAD }
+        );
+        m.borrow(ALICE, 600 * WAD, &price, 0).unwrap();
+        // saldo existe, mas o caixa foi emprestado
+        assert_eq!(
+            m.withdraw_supply(BOB, 500 * WAD, 0).unwrap_err(),
+            AmmError::InsufficientLiquidity { requested: 500 * WAD, available: 400 * WAD }
+        );
+        assert_eq!(
+            m.withdraw_supply(BOB, 1_001 * WAD, 0).unwrap_err(),
+            AmmError::InsufficientSupply { requested: 1_001 * WAD, available: 1_000 * WAD }
+        );
+        m.withdraw_supply(BOB, 400 * WAD, 0).unwrap();
+        assert_eq!((m.cash(), m.supply_of(BOB).unwrap()), (0, 600 * WAD));
+        assert_eq!(m.utilization().unwrap(), WAD);
+    }
+
+    #[test]
+    fn t_repay_and_withdraw() {
+        let price = PriceSource::Averaged(WAD);
+        let mut m = market(params(), FixedRate(0));
+        m.deposit_collateral(ALICE, 100 * WAD, 0).unwrap();
+        m.borrow(ALICE, 40 * WAD, &price, 0).unwrap();
+        // 100 · 0,9 · 0,5 = 45 ⇒ no máximo ~11,1 de colateral sai
+        assert_eq!(
+            m.withdraw_collateral(ALICE, 20 * WAD, &price, 0).unwrap_err(),
+            AmmError::LtvExceeded { debt: 40 * WAD, limit: 36 * WAD }
+        );
+        m.withdraw_collateral(ALICE, 10 * WAD, &price, 0).unwrap();
+        assert_eq!(m.repay(ALICE, 15 * WAD, YEAR).unwrap(), 15 * WAD);
+        assert_eq!(m.debt_of(ALICE).unwrap(), 25 * WAD);
+        // pagar a mais devolve só a dívida
+        assert_eq!(m.repay(ALICE, 100 * WAD, YEAR).unwrap(), 25 * WAD);
+        assert_eq!((m.total_debt().unwrap(), m.cash()), (0, 1_000 * WAD));
+        assert_eq!(
+            m.withdraw_collateral(ALICE, 91 * WAD, &price, YEAR).unwrap_err(),
+            AmmError::InsufficientCollateral { requested: 91 * WAD, available: 90 * WAD }
+        );
+        // sem dívida, o colateral sai inteiro e a linha some
+        m.withdraw_collateral(ALICE, 90 * WAD, &price, YEAR).unwrap();
+        assert_eq!(m.lines().map(|l| l.0).collect::<Vec<_>>(), vec![BOB]);
+        assert_eq!(m.total_collateral(), 0);
+    }
+}
@@ -0,0 +1,8 @@
+//! Primitivas de crédito sobre os pools do AMM.
+//! Usa os mesmos tipos (WAD/ppm), erros (`AmmError`) e guardrails do `amm`.
+
+pub mod lending;      // linhas de crédito colateralizadas
+pub mod rates;        // modelos de juros e crescimento dos índices
+
+pub use lending::{CreditLine, CreditMarket, CreditParams, CreditPosition, PriceSource};
+pub use rates::{AdaptiveRate, FixedRate, InterestRateModel, KinkedRate};
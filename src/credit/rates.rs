@@ -0,0 +1,256 @@
+//! Modelos de taxa de juros e crescimento determinístico dos índices de crédito.
+//!
+//! Um `InterestRateModel` dá a taxa anual de empréstimo (WAD) em função da utilização
+//! `u = dívida / oferta` (WAD, ≤ 1) e, para um intervalo de `dt` segundos com `u` constante, o
+//! crescimento logarítmico do índice de dívida `g` (escala 1e36 do `fixed_math`):
+//! - taxa constante no intervalo (`FixedRate`, `KinkedRate`): composição por segundo,
+//!   `g = dt·ln(1 + r/ano)`;
+//! - `AdaptiveRate`: a taxa anda continuamente, `r(t) = r0·e^(velocidade·erro(u)·t)` presa em
+//!   `[min, max]`, e `g = ∫ r dt / ano` em forma fechada (limite contínuo da composição por segundo;
+//!   diferença ≤ `(r/ano)²/2` por segundo).
+//!
+//! Nos dois casos `g(t1 + t2) = g(t1) + g(t2)` na matemática exata, então o índice
+//! `index·e^g` não depende de como o intervalo é dividido em accruals. Cotas de erro por accrual:
+//! `g` com erro ≤ `dt·1e-33` (`ln_e36`) e floor de 1 unidade; `e^g` com erro relativo ≤ 1e-33
+//! (`exp_e36`); `grow_index` arredonda para cima (1 wei, a favor do mercado). Dividir um intervalo em
+//! N accruals muda o índice em no máximo ~N wei por WAD de índice (a `AdaptiveRate` soma o floor da
+//! taxa gravada, ≤ 1e-18 relativo por accrual).
+
+use crate::amm::errors::AmmError;
+use crate::amm::fixed_math::{exp_e36, ln_scaled_e36, E36, MAX_EXP_E36};
+use crate::amm::guardrails::{checked_add, mul_div_ceil_u256, mul_div_floor_u256, mul_u128_to_u256, u256_to_u128_checked};
+use crate::amm::types::{U256, Wad, SECONDS_PER_YEAR, WAD};
+
+/// Modelo de taxa de empréstimo plugável no `CreditMarket`.
+pub trait InterestRateModel {
+    /// Taxa anual de empréstimo (WAD) à utilização `utilization` (WAD).
+    fn borrow_rate(&self, utilization: Wad) -> Result<Wad, AmmError>;
+
+    /// Avança o modelo `dt` segundos com `utilization` constante e devolve o crescimento
+    /// logarítmico do índice de dívida (1e36). Padrão: taxa constante, composta por segundo.
+    fn accrue(&mut self, utilization: Wad, dt: u64) -> Result<i128, AmmError> {
+        per_second_growth_e36(self.borrow_rate(utilization)?, dt)
+    }
+}
+
+fn year_wad() -> U256 { U256::from(WAD) * U256::from(SECONDS_PER_YEAR) }
+
+fn to_growth(g: U256) -> Result<i128, AmmError> {
+    if g > U256::from(MAX_EXP_E36 as u128) { return Err(AmmError::Overflow); }
+    Ok(g.as_u128() as i128)
+}
+
+/// `dt·ln(1 + rate/ano)·1e36` (floor): composição por segundo de uma taxa anual constante.
+pub fn per_second_growth_e36(rate: Wad, dt: u64) -> Result<i128, AmmError> {
+    if rate == 0 || dt == 0 { return Ok(0); }
+    let ln = ln_scaled_e36(year_wad() + U256::from(rate), year_wad())?;
+    to_growth(U256::from(ln as u128) * U256::from(dt))
+}
+
+/// `index·e^(growth/1e36)` (ceil).
+pub fn grow_index(index: Wad, growth_e36: i128) -> Result<Wad, AmmError> {
+    if growth_e36 == 0 { return Ok(index); }
+    u256_to_u128_checked(mul_div_ceil_u256(U256::from(index), exp_e36(growth_e36)?, U256::from(E36))?)
+}
+
+/// Taxa fixa (anual, WAD), independente da utilização.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedRate(pub Wad);
+
+impl InterestRateModel for FixedRate {
+    fn borrow_rate(&self, _utilization: Wad) -> Result<Wad, AmmError> { Ok(self.0) }
+}
+
+/// Curva com joelho: `base + slope_low·u/kink` até `kink`, depois sobe `slope_high` até `u = 1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KinkedRate {
+    base_rate: Wad,
+    slope_low: Wad,
+    slope_high: Wad,
+    kink: Wad,
+}
+
+impl KinkedRate {
+    /// `0 < kink < 1` (WAD).
+    pub fn new(base_rate: Wad, slope_low: Wad, slope_high: Wad, kink: Wad) -> Result<Self, AmmError> {
+        if kink == 0 || kink >= WAD { return Err(AmmError::InvalidRateModel); }
+        Ok(Self { base_rate, slope_low, slope_high, kink })
+    }
+
+    pub fn kink(&self) -> Wad { self.kink }
+}
+
+impl InterestRateModel for KinkedRate {
+    /// Floor em cada trecho; utilização acima de 1 é tratada como 1.
+    fn borrow_rate(&self, utilization: Wad) -> Result<Wad, AmmError> {
+        let u = utilization.min(WAD);
+        if u <= self.kink {
+            let low = u256_to_u128_checked(mul_u128_to_u256(self.slope_low, u) / U256::from(self.kink))?;
+            return checked_add(self.base_rate, low);
+        }
+        let high = u256_to_u128_checked(mul_u128_to_u256(self.slope_high, u - self.kink) / U256::from(WAD - self.kink))?;
+        checked_add(checked_add(self.base_rate, self.slope_low)?, high)
+    }
+}
+
+/// Maior taxa anual aceita como teto da `AdaptiveRate` (100.000% a.a.).
+pub const MAX_RATE: Wad = 1_000 * WAD;
+
+/// Taxa que se ajusta à utilização: `r' = speed·erro(u)·r`, com `erro ∈ [-1, 1]` a distância
+/// normalizada de `u` ao alvo. Acima do alvo a taxa sobe exponencialmente, abaixo cai, sempre em `[min, max]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptiveRate {
+    rate: Wad,
+    target_utilization: Wad,
+    /// Variação de `ln r` por ano com erro máximo (WAD)
+    speed: Wad,
+    min_rate: Wad,
+    max_rate: Wad,
+}
+
+impl AdaptiveRate {
+    /// `0 < min ≤ rate ≤ max ≤ MAX_RATE` e `0 < alvo < 1`.
+    pub fn new(rate: Wad, target_utilization: Wad, speed: Wad, min_rate: Wad, max_rate: Wad) -> Result<Self, AmmError> {
+        let ok = min_rate > 0 && min_rate <= rate && rate <= max_rate && max_rate <= MAX_RATE
+            && target_utilization > 0 && target_utilization < WAD;
+        if !ok { return Err(AmmError::InvalidRateModel); }
+        Ok(Self { rate, target_utilization, speed, min_rate, max_rate })
+    }
+
+    /// Taxa atual (anual, WAD).
+    pub fn rate(&self) -> Wad { self.rate }
+
+    /// `(|erro|, acima do alvo)`, `|erro|` em WAD.
+    fn error(&self, utilization: Wad) -> (Wad, bool) {
+        let (u, t) = (utilization.min(WAD), self.target_utilization);
+        if u >= t {
+            ((mul_u128_to_u256(u - t, WAD) / U256::from(WAD - t)).as_u128(), true)
+        } else {
+            ((mul_u128_to_u256(t - u, WAD) / U256::from(t)).as_u128(), false)
+        }
+    }
+}
+
+impl InterestRateModel for AdaptiveRate {
+    fn borrow_rate(&self, _utilization: Wad) -> Result<Wad, AmmError> { Ok(self.rate) }
+
+    fn accrue(&mut self, utilization: Wad, dt: u64) -> Result<i128, AmmError> {
+        let (err, up) = self.error(utilization);
+        // velocidade efetiva |a| por ano (WAD)
+        let a = u256_to_u128_checked(mul_u128_to_u256(self.speed, err) / U256::from(WAD))?;
+        let r0 = self.rate;
+        if dt == 0 { return Ok(0); }
+        if a == 0 {
+            return to_growth(mul_u128_to_u256(r0, u128::from(dt)) * U256::from(WAD) / U256::from(SECONDS_PER_YEAR));
+        }
+        // |ln r| percorrido: a·dt/ano, até bater no limite
+        let x_full = mul_u128_to_u256(a, u128::from(dt)) * U256::from(WAD) / U256::from(SECONDS_PER_YEAR);
+        let (bound, x_bound) = if up {
+            (self.max_rate, ln_scaled_e36(U256::from(self.max_rate), U256::from(r0))?)
+        } else {
+            (self.min_rate, ln_scaled_e36(U256::from(r0), U256::from(self.min_rate))?)
+        };
+        let x_bound = U256::from(x_bound.max(0) as u128);
+        let clamped = x_full >= x_bound;
+        let xu = x_full.min(x_bound).as_u128() as i128;
+        let ex = exp_e36(if up { xu } else { -xu })?;
+        let e36 = U256::from(E36);
+        // ∫ r0·e^(±a·s) ds = r0·|e^(±xu) − 1| / a
+        let diff = if ex >= e36 { ex - e36 } else { e36 - ex };
+        let mut g = mul_div_floor_u256(U256::from(r0), diff, U256::from(a))?;
+        if clamped {
+            // resto do intervalo na taxa limite: bound·(dt/ano − xu/a)
+            let s = U256::from(dt) * e36 / U256::from(SECONDS_PER_YEAR);
+            let s_free = U256::from(xu as u128) * U256::from(WAD) / U256::from(a);
+            let tail = mul_div_floor_u256(U256::from(bound), s.saturating_sub(s_free), U256::from(WAD))?;
+            g = g.checked_add(tail).ok_or(AmmError::Overflow)?;
+            self.rate = bound;
+        } else {
+            self.rate = u256_to_u128_checked(mul_div_floor_u256(U256::from(r0), ex, e36)?)?.clamp(self.min_rate, self.max_rate);
+        }
+        to_growth(g)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YEAR: u64 = SECONDS_PER_YEAR;
+    const PCT: Wad = WAD / 100;
+
+    #[test]
+    fn t_per_second_compounding() {
+        // 10% a.a. composto por segundo por um ano ≈ e^0,1 (diferença ~1,6e-10)
+        let idx = grow_index(WAD, per_second_growth_e36(10 * PCT, YEAR).unwrap()).unwrap();
+        let e01 = 1_105_170_918_075_647_624u128;
+        assert!(idx <= e01 && e01 - idx < 1_000_000_000, "{idx}");
+        // taxa zero ou intervalo vazio: índice parado
+        assert_eq!(per_second_growth_e36(0, YEAR).unwrap(), 0);
+        assert_eq!(grow_index(3 * WAD, per_second_growth_e36(10 * PCT, 0).unwrap()).unwrap(), 3 * WAD);
+        assert_eq!(per_second_growth_e36(Wad::MAX / 2, YEAR).unwrap_err(), AmmError::Overflow);
+    }
+
+    #[test]
+    fn t_kinked_curve() {
+        let m = KinkedRate::new(2 * PCT, 8 * PCT, 100 * PCT, 80 * PCT).unwrap();
+        assert_eq!(m.borrow_rate(0).unwrap(), 2 * PCT);
+        assert_eq!(m.borrow_rate(40 * PCT).unwrap(), 6 * PCT);
+        assert_eq!(m.borrow_rate(80 * PCT).unwrap(), 10 * PCT);
+        assert_eq!(m.borrow_rate(90 * PCT).unwrap(), 60 * PCT);
+        assert_eq!(m.borrow_rate(WAD).unwrap(), 110 * PCT);
+        assert_eq!(m.borrow_rate(2 * WAD).unwrap(), 110 * PCT);
+        assert_eq!(KinkedRate::new(0, 0, 0, WAD).unwrap_err(), AmmError::InvalidRateModel);
+        assert_eq!(KinkedRate::new(0, 0, 0, 0).unwrap_err(), AmmError::InvalidRateModel);
+    }
+
+    #[test]
+    fn t_adaptive_moves_toward_bounds() {
+        let m = AdaptiveRate::new(4 * PCT, 90 * PCT, 50 * WAD, PCT, 200 * PCT).unwrap();
+        // no alvo: taxa constante, crescimento = r·dt/ano
+        let mut at = m;
+        assert_eq!(at.accrue(90 * PCT, YEAR).unwrap(), (4 * PCT * WAD) as i128);
+        assert_eq!(at.rate(), 4 * PCT);
+        // u = 1: erro 1, ln r sobe 50 por ano ⇒ em ~1 dia chega perto de ×e^0,137
+        let mut hot = m;
+        hot.accrue(WAD, 86_400).unwrap();
+        let expected = (4 * PCT) as f64 * (50.0f64 / 365.0).exp();
+        assert!((hot.rate() as f64 - expected).abs() / expected < 1e-12, "{}", hot.rate());
+        // muito tempo: presa no máximo / mínimo
+        let mut hot = m;
+        hot.accrue(WAD, YEAR).unwrap();
+        assert_eq!(hot.rate(), 200 * PCT);
+        let mut cold = m;
+        cold.accrue(0, YEAR).unwrap();
+        assert_eq!(cold.rate(), PCT);
+        assert_eq!(AdaptiveRate::new(PCT, 90 * PCT, WAD, 2 * PCT, 3 * PCT).unwrap_err(), AmmError::InvalidRateModel);
+        assert_eq!(AdaptiveRate::new(PCT, WAD, WAD, PCT, PCT).unwrap_err(), AmmError::InvalidRateModel);
+        assert_eq!(AdaptiveRate::new(WAD, WAD / 2, WAD, 1, MAX_RATE + 1).unwrap_err(), AmmError::InvalidRateModel);
+    }
+
+    #[test]
+    fn t_adaptive_extreme_inputs_do_not_panic() {
+        // antes: `bound·(dt/ano − xu/a)` estourava U256 com teto u128::MAX e dt enorme
+        assert_eq!(AdaptiveRate::new(WAD, WAD / 2, WAD, 1, u128::MAX).unwrap_err(), AmmError::InvalidRateModel);
+        for (rate, speed, u) in [(WAD, WAD, WAD), (MAX_RATE, u128::MAX, WAD), (MAX_RATE, u128::MAX, 0), (1, u128::MAX, WAD)] {
+            let mut m = AdaptiveRate::new(rate, WAD / 2, speed, 1, MAX_RATE).unwrap();
+            let got = m.accrue(u, u64::MAX);
+            assert!(matches!(got, Ok(_) | Err(AmmError::Overflow)), "{:?}", got);
+        }
+    }
+
+    #[test]
+    fn t_adaptive_growth_matches_closed_form_through_clamp() {
+        // sobe de 4% até 8% (ln 2 / 50 ano ≈ 5,06 dias) e fica em 8% até completar 30 dias
+        let mut m = AdaptiveRate::new(4 * PCT, 90 * PCT, 50 * WAD, PCT, 8 * PCT).unwrap();
+        let dt = 30 * 86_400u64;
+        let g = m.accrue(WAD, dt).unwrap() as f64 / 1e36;
+        let t_hit = 2f64.ln() / 50.0;
+        let exact = 0.04 * (2.0 - 1.0) / 50.0 + 0.08 * (dt as f64 / YEAR as f64 - t_hit);
+        assert!((g - exact).abs() < 1e-15, "{g} vs {exact}");
+        assert_eq!(m.rate(), 8 * PCT);
+    }
+}
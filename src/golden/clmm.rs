@@ -0,0 +1,277 @@
+//! Golden da liquidez concentrada (`goldens/amm_clmm_v1.csv`): runner e gerador.
+//! O gerador usa o modelo exato (`ref_bigdecimal::spec_*` de Q64.96); o runner executa
+//! `clmm_math` e compara bit a bit. Mesmo esquema de sha256 e cabeçalho do golden CPMM.
+//! As constantes de tick da v3 usadas pelos dois lados são conferidas contra a definição `1.0001^(t/2)`
+//! em `ref_bigdecimal::sqrt_ratio_at_tick_exact`; o pool inteiro tem golden próprio em `clmm_pool`.
+//!
+//! Colunas: `id,op,tick,sqrt_a_x96,sqrt_b_x96,liquidity,amount,fee_ppm,expect_kind,expect`.
+//!
+//! | op | entradas | esperado |
+//! |---|---|---|
+//! | `SQRT_AT_TICK` | `tick` | preço raiz Q64.96 |
+//! | `TICK_AT_SQRT` | `sqrt_a` | tick |
+//! | `AMOUNT0_UP` / `AMOUNT0_DOWN` / `AMOUNT1_UP` / `AMOUNT1_DOWN` | `sqrt_a`, `sqrt_b`, `liquidity` | Δ do token |
+//! | `NEXT_IN` / `NEXT_IN_YX` | `sqrt_a`, `liquidity`, `amount` (input de x / y) | preço raiz |
+//! | `NEXT_OUT` / `NEXT_OUT_YX` | `sqrt_a`, `liquidity`, `amount` (output de y / x) | preço raiz |
+//! | `STEP_IN` / `STEP_OUT` | `sqrt_a` (atual), `sqrt_b` (alvo), `liquidity`, `amount`, `fee_ppm` | `sqrt;in;out;fee` |
+
+use anyhow::{anyhow, bail, Result};
+use num_bigint::BigInt;
+use std::path::Path;
+
+use crate::amm::clmm_math::{
+    amount0_delta, amount1_delta, compute_swap_step, next_sqrt_price_from_input, next_sqrt_price_from_output,
+    sqrt_ratio_at_tick, tick_at_sqrt_ratio, MAX_TICK, MIN_TICK,
+};
+use crate::amm::errors::AmmError;
+use crate::amm::ref_bigdecimal as rf;
+use crate::amm::types::{Ppm, U256, Wad, WAD};
+
+use super::{check_row_count, compare, parse_meta_as, read_verified, variant_name, Report, RowOutcome};
+
+/// Nome do formato na linha `# format:`.
+pub const FORMAT_NAME: &str = "amm_clmm";
+/// Maior versão de formato que este runner entende.
+pub const FORMAT_VERSION: u32 = 1;
+/// Identificador da grade de cenários (mude ao alterar `grid()`).
+pub const GRID_ID: &str = "clmm-grid-1";
+
+const HEADER: &str = "id,op,tick,sqrt_a_x96,sqrt_b_x96,liquidity,amount,fee_ppm,expect_kind,expect";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClmmRow {
+    pub id: String,
+    pub op: String,
+    pub tick: i32,
+    pub sqrt_a: U256,
+    pub sqrt_b: U256,
+    pub liquidity: u128,
+    pub amount: Wad,
+    pub fee_ppm: Ppm,
+    pub expect_kind: String,
+    pub expect: String,
+}
+
+pub(super) fn parse_num<T: std::str::FromStr + Default>(field: &str, col: &str, id: &str) -> Result<T> {
+    if field.is_empty() { return Ok(T::default()); }
+    field.parse::<T>().map_err(|_| anyhow!("{}: coluna {} inválida: {:?}", id, col, field))
+}
+
+pub(super) fn parse_u256(field: &str, col: &str, id: &str) -> Result<U256> {
+    if field.is_empty() { return Ok(U256::zero()); }
+    U256::from_dec_str(field).map_err(|_| anyhow!("{}: coluna {} inválida: {:?}", id, col, field))
+}
+
+/// Faz o parse do CSV (colunas localizadas pelo cabeçalho; linhas `#` são metadados).
+pub fn parse_csv(text: &str) -> Result<Vec<ClmmRow>> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+    let header: Vec<&str> = lines.next().ok_or_else(|| anyhow!("CSV sem cabeçalho"))?.split(',').map(str::trim).collect();
+    let col = |name: &str| header.iter().position(|h| *h == name);
+    for required in ["id", "op", "expect_kind", "expect"] {
+        if col(required).is_none() { bail!("coluna obrigatória ausente: {}", required); }
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let get = |name: &str| col(name).and_then(|i| fields.get(i).copied()).unwrap_or("");
+        let id = get("id").to_string();
+        rows.push(ClmmRow {
+            op: get("op").to_string(),
+            tick: parse_num(get("tick"), "tick", &id)?,
+            sqrt_a: parse_u256(get("sqrt_a_x96"), "sqrt_a_x96", &id)?,
+            sqrt_b: parse_u256(get("sqrt_b_x96"), "sqrt_b_x96", &id)?,
+            liquidity: parse_num(get("liquidity"), "liquidity", &id)?,
+            amount: parse_num(get("amount"), "amount", &id)?,
+            fee_ppm: parse_num(get("fee_ppm"), "fee_ppm", &id)?,
+            expect_kind: get("expect_kind").to_string(),
+            expect: get("expect").to_string(),
+            id,
+        });
+    }
+    Ok(rows)
+}
+
+/// Executa a operação da linha em `clmm_math` e devolve o valor no formato de `expect`.
+pub fn eval_row(row: &ClmmRow) -> Result<std::result::Result<String, AmmError>> {
+    let (a, b, l, amt) = (row.sqrt_a, row.sqrt_b, row.liquidity, row.amount);
+    let s = |v: U256| v.to_string();
+    let got = match row.op.as_str() {
+        "SQRT_AT_TICK" => sqrt_ratio_at_tick(row.tick).map(s),
+        "TICK_AT_SQRT" => tick_at_sqrt_ratio(a).map(|t| t.to_string()),
+        "AMOUNT0_UP" => amount0_delta(a, b, l, true).map(|v| v.to_string()),
+        "AMOUNT0_DOWN" => amount0_delta(a, b, l, false).map(|v| v.to_string()),
+        "AMOUNT1_UP" => amount1_delta(a, b, l, true).map(|v| v.to_string()),
+        "AMOUNT1_DOWN" => amount1_delta(a, b, l, false).map(|v| v.to_string()),
+        "NEXT_IN" => next_sqrt_price_from_input(a, l, amt, true).map(s),
+        "NEXT_IN_YX" => next_sqrt_price_from_input(a, l, amt, false).map(s),
+        "NEXT_OUT" => next_sqrt_price_from_output(a, l, amt, true).map(s),
+        "NEXT_OUT_YX" => next_sqrt_price_from_output(a, l, amt, false).map(s),
+        "STEP_IN" | "STEP_OUT" => compute_swap_step(a, b, l, amt, row.op == "STEP_IN", row.fee_ppm)
+            .map(|st| format!("{};{};{};{}", st.sqrt_price_x96, st.amount_in, st.amount_out, st.fee)),
+        other => bail!("{}: op desconhecida {:?}", row.id, other),
+    };
+    Ok(got)
+}
+
+/// Executa uma linha e compara com o esperado.
+pub fn run_row(row: &ClmmRow) -> Result<RowOutcome> {
+    compare(&row.id, &row.expect_kind, &row.expect, eval_row(row)?)
+}
+
+/// Lê `csv_path`, confere `csv_path.sha256` e executa todas as linhas.
+pub fn run_file(csv_path: &Path) -> Result<Report> {
+    let text = read_verified(csv_path)?;
+    let meta = parse_meta_as(&text, FORMAT_NAME, FORMAT_VERSION)?;
+    let rows = parse_csv(&text)?;
+    check_row_count(&meta, rows.len())?;
+    let mut report = Report::default();
+    for row in rows {
+        match run_row(&row)? {
+            Ok(()) => report.passed += 1,
+            Err(msg) => report.failures.push((row.id, msg)),
+        }
+    }
+    Ok(report)
+}
+
+// --------- Gerador ---------
+
+pub(super) fn big(v: U256) -> BigInt { v.to_string().parse().expect("decimal") }
+
+fn u256(tick: i32) -> U256 { U256::from_dec_str(&rf::spec_sqrt_ratio_at_tick(tick).expect("tick da grade").to_string()).expect("u256") }
+
+/// Valor esperado da linha segundo o modelo exato.
+fn expected(r: &ClmmRow) -> Result<std::result::Result<String, AmmError>> {
+    let (a, b, l, amt) = (big(r.sqrt_a), big(r.sqrt_b), r.liquidity, r.amount);
+    let s = |v: BigInt| v.to_string();
+    let want = match r.op.as_str() {
+        "SQRT_AT_TICK" => rf::spec_sqrt_ratio_at_tick(r.tick).map(s),
+        "TICK_AT_SQRT" => rf::spec_tick_at_sqrt_ratio(&a).map(|t| t.to_string()),
+        "AMOUNT0_UP" => rf::spec_amount0_delta(&a, &b, l, true).map(|v| v.to_string()),
+        "AMOUNT0_DOWN" => rf::spec_amount0_delta(&a, &b, l, false).map(|v| v.to_string()),
+        "AMOUNT1_UP" => rf::spec_amount1_delta(&a, &b, l, true).map(|v| v.to_string()),
+        "AMOUNT1_DOWN" => rf::spec_amount1_delta(&a, &b, l, false).map(|v| v.to_string()),
+        "NEXT_IN" => rf::spec_next_sqrt_from_input(&a, l, amt, true).map(s),
+        "NEXT_IN_YX" => rf::spec_next_sqrt_from_input(&a, l, amt, false).map(s),
+        "NEXT_OUT" => rf::spec_next_sqrt_from_output(&a, l, amt, true).map(s),
+        "NEXT_OUT_YX" => rf::spec_next_sqrt_from_output(&a, l, amt, false).map(s),
+        "STEP_IN" | "STEP_OUT" => rf::spec_swap_step(&a, &b, l, amt, r.op == "STEP_IN", r.fee_ppm)
+            .map(|(p, i, o, f)| format!("{};{};{};{}", p, i, o, f)),
+        other => bail!("{}: op sem gerador: {:?}", r.id, other),
+    };
+    Ok(want)
+}
+
+fn render(r: &ClmmRow, result: std::result::Result<String, AmmError>) -> String {
+    let opt = |v: U256| if v.is_zero() { String::new() } else { v.to_string() };
+    let (kind, value) = match result {
+        Ok(v) => ("ok".to_string(), v),
+        Err(e) => (format!("err:{}", variant_name(&e)), String::new()),
+    };
+    format!("{},{},{},{},{},{},{},{},{},{}", r.id, r.op, r.tick, opt(r.sqrt_a), opt(r.sqrt_b), r.liquidity, r.amount, r.fee_ppm, kind, value)
+}
+
+/// Faixas `(nome, tick_lower, tick_upper, liquidez)`.
+pub fn scenarios() -> Vec<(&'static str, i32, i32, u128)> {
+    vec![
+        ("NARROW", -60, 60, 1_000_000 * WAD),
+        ("WIDE", -100_000, 100_000, 1_000 * WAD),
+        ("LOW", -500_000, -499_000, 1_000_000 * WAD),
+        ("HIGH", 499_000, 500_000, 1_000_000 * WAD),
+        ("TINY_L", -60, 60, 1),
+        ("MAX_L", -60, 60, u128::MAX),
+    ]
+}
+
+/// Linhas da grade: ticks ↔ preços (incluindo bordas), deltas, próximo preço e passos de swap.
+fn grid() -> Vec<ClmmRow> {
+    let row = |id: String, op: &str| ClmmRow { id, op: op.to_string(), ..Default::default() };
+    let mut rows = Vec::new();
+    let ticks = [MIN_TICK, MIN_TICK + 1, -500_000, -100_000, -1_000, -60, -1, 0, 1, 60, 1_000, 100_000, 500_000, MAX_TICK - 1, MAX_TICK];
+    for t in ticks.into_iter().chain([MIN_TICK - 1, MAX_TICK + 1]) {
+        rows.push(ClmmRow { tick: t, ..row(format!("SQRT_AT_TICK_{}", t), "SQRT_AT_TICK") });
+    }
+    for t in ticks {
+        let p = u256(t);
+        rows.push(ClmmRow { sqrt_a: p, ..row(format!("TICK_AT_SQRT_{}", t), "TICK_AT_SQRT") });
+        rows.push(ClmmRow { sqrt_a: p - U256::one(), ..row(format!("TICK_AT_SQRT_{}_M1", t), "TICK_AT_SQRT") });
+        rows.push(ClmmRow { sqrt_a: p + U256::one(), ..row(format!("TICK_AT_SQRT_{}_P1", t), "TICK_AT_SQRT") });
+    }
+
+    for (name, lo, hi, l) in scenarios() {
+        let (a, b, mid) = (u256(lo), u256(hi), u256((lo + hi) / 2));
+        for op in ["AMOUNT0_UP", "AMOUNT0_DOWN", "AMOUNT1_UP", "AMOUNT1_DOWN"] {
+            rows.push(ClmmRow { sqrt_a: a, sqrt_b: b, liquidity: l, ..row(format!("{}_{}", name, op), op) });
+        }
+        for (label, amt) in [("1WEI", 1), ("1", WAD), ("1M", 1_000_000 * WAD), ("MAX", u128::MAX)] {
+            for op in ["NEXT_IN", "NEXT_IN_YX", "NEXT_OUT", "NEXT_OUT_YX"] {
+                rows.push(ClmmRow { sqrt_a: mid, liquidity: l, amount: amt, ..row(format!("{}_{}_{}", name, op, label), op) });
+            }
+            for fee in [0, 3_000, 999_999, 1_000_000] {
+                for (side, target) in [("DOWN", a), ("UP", b)] {
+                    for op in ["STEP_IN", "STEP_OUT"] {
+                        rows.push(ClmmRow {
+                            sqrt_a: mid,
+                            sqrt_b: target,
+                            liquidity: l,
+                            amount: amt,
+                            fee_ppm: fee,
+                            ..row(format!("{}_{}_{}_{}_F{}", name, op, side, label, fee), op)
+                        });
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Conteúdo completo do arquivo `amm_clmm_v1.csv` (cabeçalho de proveniência + linhas).
+pub fn render_v1() -> Result<String> {
+    let rows = grid().iter().map(|r| Ok(render(r, expected(r)?))).collect::<Result<Vec<String>>>()?;
+    let mut out = String::new();
+    out.push_str(&format!("# format: {}/{}\n", FORMAT_NAME, FORMAT_VERSION));
+    out.push_str("# generator: golden_gen (src/golden/clmm.rs)\n");
+    out.push_str("# reference: ref_bigdecimal::spec_* Q64.96 (racionais exatos)\n");
+    out.push_str("# rounding: ADR-0001 in=ceil out=floor fee=ceil sqrt_at_tick=ceil next_price=a favor do pool\n");
+    out.push_str(&format!("# grid: {}\n", GRID_ID));
+    out.push_str(&format!("# rows: {}\n", rows.len()));
+    out.push_str(HEADER);
+    out.push('\n');
+    for r in rows {
+        out.push_str(&r);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "id,op,tick,sqrt_a_x96,sqrt_b_x96,liquidity,amount,fee_ppm,expect_kind,expect\n\
+        A,SQRT_AT_TICK,0,,,0,0,0,ok,79228162514264337593543950336\n\
+        B,SQRT_AT_TICK,887273,,,0,0,0,err:InvalidTick,\n\
+        C,TICK_AT_SQRT,0,79228162514264337593543950335,,0,0,0,ok,-1\n";
+
+    #[test]
+    fn t_parse_and_run() {
+        let rows = parse_csv(CSV).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].sqrt_a, U256::from_dec_str("79228162514264337593543950335").unwrap());
+        for r in &rows { assert_eq!(run_row(r).unwrap(), Ok(()), "{}", r.id); }
+    }
+
+    #[test]
+    fn t_generator_matches_core_on_grid() {
+        for r in grid() {
+            let want = expected(&r).unwrap();
+            let got = eval_row(&r).unwrap();
+            assert_eq!(got.as_ref().map_err(variant_name), want.as_ref().map_err(variant_name), "{}", r.id);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Golden do pool concentrado (`goldens/amm_clmm_pool_v1.csv`): sequências mint/swap/burn/collect
+//! executadas no `ConcentratedPool` e comparadas com `ref_bigdecimal::RefClPool`, que calcula
+//! liquidez ativa e taxas direto das faixas das posições (sem ticks inicializados nem `fee_growth_outside`).
+//!
+//! As linhas são sequenciais: cada `NEW` cria um pool novo e as seguintes operam sobre ele.
+//! Linha com erro não altera o estado.
+//!
+//! Colunas: `id,op,owner,tick_lower,tick_upper,liquidity,amount,sqrt_x96,fee_ppm,tick_spacing,expect_kind,expect`.
+//!
+//! | op | entradas | esperado |
+//! |---|---|---|
+//! | `NEW` | `sqrt_x96`, `fee_ppm`, `tick_spacing` | tick inicial |
+//! | `MINT` / `BURN` | `owner`, `tick_lower`, `tick_upper`, `liquidity` | `x;y` (depositado / creditado) |
+//! | `COLLECT` | `owner`, `tick_lower`, `tick_upper` | `x;y` pagos |
+//! | `SWAP_IN` / `SWAP_IN_YX` | `amount` (input bruto de x / y) | `in;out;fee;sqrt` |
+//! | `SWAP_OUT` / `SWAP_OUT_YX` | `amount` (output de y / x) | `in;out;fee;sqrt` |
+//! | `BALANCES` | — | `x;y` no pool |
+
+use anyhow::{anyhow, bail, Result};
+use std::path::Path;
+
+use crate::amm::clmm_math::{MAX_SQRT_RATIO, MAX_TICK, MIN_TICK};
+use crate::amm::concentrated::{ClSwapReceipt, ConcentratedPool};
+use crate::amm::errors::AmmError;
+use crate::amm::ref_bigdecimal::{self as rf, RefClPool, RefClSwap};
+use crate::amm::types::{AccountId, Direction, Ppm, U256, Wad, WAD};
+
+use super::clmm::{big, parse_num, parse_u256};
+use super::{check_row_count, compare, parse_meta_as, read_verified, variant_name, Report};
+
+/// Nome do formato na linha `# format:`.
+pub const FORMAT_NAME: &str = "amm_clmm_pool";
+/// Maior versão de formato que este runner entende.
+pub const FORMAT_VERSION: u32 = 1;
+/// Identificador da grade de cenários (mude ao alterar `grid()`).
+pub const GRID_ID: &str = "clmm-pool-grid-1";
+
+const HEADER: &str = "id,op,owner,tick_lower,tick_upper,liquidity,amount,sqrt_x96,fee_ppm,tick_spacing,expect_kind,expect";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClmmPoolRow {
+    pub id: String,
+    pub op: String,
+    pub owner: AccountId,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount: Wad,
+    pub sqrt_x96: U256,
+    pub fee_ppm: Ppm,
+    pub tick_spacing: i32,
+    pub expect_kind: String,
+    pub expect: String,
+}
+
+/// Faz o parse do CSV (colunas localizadas pelo cabeçalho; linhas `#` são metadados).
+pub fn parse_csv(text: &str) -> Result<Vec<ClmmPoolRow>> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+    let header: Vec<&str> = lines.next().ok_or_else(|| anyhow!("CSV sem cabeçalho"))?.split(',').map(str::trim).collect();
+    let col = |name: &str| header.iter().position(|h| *h == name);
+    for required in ["id", "op", "expect_kind", "expect"] {
+        if col(required).is_none() { bail!("coluna obrigatória ausente: {}", required); }
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let get = |name: &str| col(name).and_then(|i| fields.get(i).copied()).unwrap_or("");
+        let id = get("id").to_string();
+        rows.push(ClmmPoolRow {
+            op: get("op").to_string(),
+            owner: parse_num(get("owner"), "owner", &id)?,
+            tick_lower: parse_num(get("tick_lower"), "tick_lower", &id)?,
+            tick_upper: parse_num(get("tick_upper"), "tick_upper", &id)?,
+            liquidity: parse_num(get("liquidity"), "liquidity", &id)?,
+            amount: parse_num(get("amount"), "amount", &id)?,
+            sqrt_x96: parse_u256(get("sqrt_x96"), "sqrt_x96", &id)?,
+            fee_ppm: parse_num(get("fee_ppm"), "fee_ppm", &id)?,
+            tick_spacing: parse_num(get("tick_spacing"), "tick_spacing", &id)?,
+            expect_kind: get("expect_kind").to_string(),
+            expect: get("expect").to_string(),
+            id,
+        });
+    }
+    Ok(rows)
+}
+
+fn pair((x, y): (Wad, Wad)) -> String { format!("{};{}", x, y) }
+
+fn receipt(r: ClSwapReceipt) -> String { format!("{};{};{};{}", r.amount_in, r.amount_out, r.fee, r.sqrt_price_x96) }
+
+fn ref_receipt((i, o, f, p): RefClSwap) -> String { format!("{};{};{};{}", i, o, f, p) }
+
+fn swap_kind(op: &str) -> Option<(Direction, bool)> {
+    match op {
+        "SWAP_IN" => Some((Direction::XtoY, true)),
+        "SWAP_IN_YX" => Some((Direction::YtoX, true)),
+        "SWAP_OUT" => Some((Direction::XtoY, false)),
+        "SWAP_OUT_YX" => Some((Direction::YtoX, false)),
+        _ => None,
+    }
+}
+
+/// Executa a linha no `ConcentratedPool` corrente (`NEW` substitui o pool).
+pub fn eval_row(pool: &mut Option<ConcentratedPool>, row: &ClmmPoolRow) -> Result<std::result::Result<String, AmmError>> {
+    if row.op == "NEW" {
+        let got = ConcentratedPool::new(row.sqrt_x96, row.fee_ppm, row.tick_spacing);
+        return Ok(got.map(|p| {
+            let tick = p.tick().to_string();
+            *pool = Some(p);
+            tick
+        }));
+    }
+    let p = pool.as_mut().ok_or_else(|| anyhow!("{}: operação antes do primeiro NEW", row.id))?;
+    let (owner, lo, hi) = (row.owner, row.tick_lower, row.tick_upper);
+    let got = match row.op.as_str() {
+        "MINT" => p.mint(owner, lo, hi, row.liquidity).map(pair),
+        "BURN" => p.burn(owner, lo, hi, row.liquidity).map(pair),
+        "COLLECT" => p.collect(owner, lo, hi).map(pair),
+        "BALANCES" => Ok(pair((p.balances().x, p.balances().y))),
+        op => match swap_kind(op) {
+            Some((dir, true)) => p.swap_exact_in(dir, row.amount, 0).map(receipt),
+            Some((dir, false)) => p.swap_exact_out(dir, row.amount, Wad::MAX).map(receipt),
+            None => bail!("{}: op desconhecida {:?}", row.id, op),
+        },
+    };
+    Ok(got)
+}
+
+/// Lê `csv_path`, confere `csv_path.sha256` e executa as linhas em sequência.
+pub fn run_file(csv_path: &Path) -> Result<Report> {
+    let text = read_verified(csv_path)?;
+    let meta = parse_meta_as(&text, FORMAT_NAME, FORMAT_VERSION)?;
+    let rows = parse_csv(&text)?;
+    check_row_count(&meta, rows.len())?;
+    let (mut pool, mut report) = (None, Report::default());
+    for row in rows {
+        match compare(&row.id, &row.expect_kind, &row.expect, eval_row(&mut pool, &row)?)? {
+            Ok(()) => report.passed += 1,
+            Err(msg) => report.failures.push((row.id, msg)),
+        }
+    }
+    Ok(report)
+}
+
+// --------- Gerador ---------
+
+/// Valor esperado da linha segundo o modelo de referência (mesma semântica de `eval_row`).
+fn expected(pool: &mut Option<RefClPool>, r: &ClmmPoolRow) -> Result<std::result::Result<String, AmmError>> {
+    if r.op == "NEW" {
+        let got = RefClPool::new(big(r.sqrt_x96), r.fee_ppm, r.tick_spacing).and_then(|p| {
+            let tick = p.tick()?.to_string();
+            *pool = Some(p);
+            Ok(tick)
+        });
+        return Ok(got);
+    }
+    let p = pool.as_mut().ok_or_else(|| anyhow!("{}: operação antes do primeiro NEW", r.id))?;
+    let (owner, lo, hi) = (r.owner, r.tick_lower, r.tick_upper);
+    let got = match r.op.as_str() {
+        "MINT" => p.mint(owner, lo, hi, r.liquidity).map(pair),
+        "BURN" => p.burn(owner, lo, hi, r.liquidity).map(pair),
+        "COLLECT" => p.collect(owner, lo, hi).map(pair),
+        "BALANCES" => Ok(pair(p.balances())),
+        op => match swap_kind(op) {
+            Some((dir, true)) => p.swap_exact_in(dir, r.amount).map(ref_receipt),
+            Some((dir, false)) => p.swap_exact_out(dir, r.amount).map(ref_receipt),
+            None => bail!("{}: op sem gerador: {:?}", r.id, op),
+        },
+    };
+    Ok(got)
+}
+
+fn render(r: &ClmmPoolRow, result: std::result::Result<String, AmmError>) -> String {
+    let sqrt = if r.sqrt_x96.is_zero() { String::new() } else { r.sqrt_x96.to_string() };
+    let (kind, value) = match result {
+        Ok(v) => ("ok".to_string(), v),
+        Err(e) => (format!("err:{}", variant_name(&e)), String::new()),
+    };
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{}",
+        r.id, r.op, r.owner, r.tick_lower, r.tick_upper, r.liquidity, r.amount, sqrt, r.fee_ppm, r.tick_spacing, kind, value
+    )
+}
+
+fn sqrt_at(tick: i32) -> U256 {
+    U256::from_dec_str(&rf::spec_sqrt_ratio_at_tick(tick).expect("tick da grade").to_string()).expect("u256")
+}
+
+/// Monta as linhas de um cenário com ids `<cenário>_<nn>_<op>`.
+struct Script {
+    name: &'static str,
+    rows: Vec<ClmmPoolRow>,
+}
+
+impl Script {
+    fn push(&mut self, op: &str, row: ClmmPoolRow) -> &mut Self {
+        let id = format!("{}_{:02}_{}", self.name, self.rows.len(), op);
+        self.rows.push(ClmmPoolRow { id, op: op.to_string(), ..row });
+        self
+    }
+    fn new_pool(&mut self, sqrt_x96: U256, fee_ppm: Ppm, tick_spacing: i32) -> &mut Self {
+        self.push("NEW", ClmmPoolRow { sqrt_x96, fee_ppm, tick_spacing, ..Default::default() })
+    }
+    fn position(&mut self, op: &str, owner: AccountId, tick_lower: i32, tick_upper: i32, liquidity: u128) -> &mut Self {
+        self.push(op, ClmmPoolRow { owner, tick_lower, tick_upper, liquidity, ..Default::default() })
+    }
+    fn swap(&mut self, op: &str, amount: Wad) -> &mut Self { self.push(op, ClmmPoolRow { amount, ..Default::default() }) }
+    fn balances(&mut self) -> &mut Self { self.push("BALANCES", ClmmPoolRow::default()) }
+}
+
+/// Cenários: faixa única, faixas sobrepostas com cruzamentos, lacuna sem liquidez, preço na borda
+/// de um tick inicializado e esgotamento até o limite de preço, mais as linhas de erro de cada op.
+fn grid() -> Vec<ClmmPoolRow> {
+    let mut rows = Vec::new();
+    let mut script = |name: &'static str, build: &dyn Fn(&mut Script)| {
+        let mut s = Script { name, rows: Vec::new() };
+        build(&mut s);
+        rows.extend(s.rows);
+    };
+
+    script("SINGLE", &|s| {
+        s.new_pool(sqrt_at(0), 3_000, 60)
+            .position("MINT", 1, -600, 600, 1_000_000 * WAD)
+            .swap("SWAP_IN", 1_000 * WAD)
+            .swap("SWAP_IN_YX", 1_500 * WAD)
+            .swap("SWAP_OUT", 100 * WAD)
+            .swap("SWAP_OUT_YX", 200 * WAD)
+            .swap("SWAP_IN", 1)
+            .swap("SWAP_IN", 0)
+            .balances()
+            .position("COLLECT", 1, -600, 600, 0)
+            .position("BURN", 1, -600, 600, 2_000_000 * WAD)
+            .position("BURN", 1, -600, 600, 400_000 * WAD)
+            .position("COLLECT", 1, -600, 600, 0)
+            .position("BURN", 1, -600, 600, 600_000 * WAD)
+            .position("BURN", 1, -600, 600, 0)
+            .position("COLLECT", 1, -600, 600, 0)
+            .position("COLLECT", 1, -600, 600, 0)
+            .balances();
+    });
+
+    script("CROSS", &|s| {
+        s.new_pool(sqrt_at(0), 3_000, 60)
+            .position("MINT", 1, -600, 600, 1_000_000 * WAD)
+            .position("MINT", 2, -1_200, -300, 2_000_000 * WAD)
+            .position("MINT", 3, 300, 1_800, 500_000 * WAD)
+            .position("MINT", 4, -1_200, -600, 750_000 * WAD)
+            .position("MINT", 1, -600, 600, 0)
+            .position("MINT", 1, -601, 600, WAD)
+            .position("MINT", 1, 600, 600, WAD)
+            .position("MINT", 1, -600, MAX_TICK, WAD)
+            .swap("SWAP_IN", 100_000 * WAD)
+            .swap("SWAP_IN_YX", 150_000 * WAD)
+            .swap("SWAP_OUT", 80_000 * WAD)
+            .position("COLLECT", 1, -600, 600, 0)
+            .position("COLLECT", 2, -1_200, -300, 0)
+            .position("COLLECT", 3, 300, 1_800, 0)
+            .position("COLLECT", 4, -1_200, -600, 0)
+            .position("COLLECT", 5, -600, 600, 0)
+            .position("BURN", 2, -1_200, -300, 1_000_000 * WAD)
+            .swap("SWAP_OUT_YX", 50_000 * WAD)
+            .swap("SWAP_IN", 30_000 * WAD)
+            .swap("SWAP_IN", 500_000 * WAD)
+            .position("BURN", 1, -600, 600, 1_000_000 * WAD)
+            .position("COLLECT", 1, -600, 600, 0)
+            .position("COLLECT", 2, -1_200, -300, 0)
+            .balances();
+    });
+
+    script("GAP", &|s| {
+        s.new_pool(sqrt_at(0), 500, 60)
+            .position("MINT", 1, -1_200, -600, 1_000_000 * WAD)
+            .position("MINT", 2, 600, 1_200, 1_000_000 * WAD)
+            .swap("SWAP_IN", 10 * WAD)
+            .swap("SWAP_IN_YX", 20 * WAD)
+            .swap("SWAP_OUT_YX", 40 * WAD)
+            .swap("SWAP_OUT", 25_000 * WAD)
+            .swap("SWAP_OUT", 10_000 * WAD)
+            .position("COLLECT", 1, -1_200, -600, 0)
+            .position("COLLECT", 2, 600, 1_200, 0)
+            .balances();
+    });
+
+    script("BOUNDARY", &|s| {
+        let l = 1_000_000 * WAD;
+        let exact = rf::spec_amount1_delta(&rf::spec_sqrt_ratio_at_tick(0).expect("tick"), &rf::spec_sqrt_ratio_at_tick(60).expect("tick"), l, false)
+            .expect("delta");
+        s.new_pool(sqrt_at(60), 3_000, 60)
+            .position("MINT", 1, -60, 60, l)
+            .position("MINT", 2, 60, 120, l)
+            .position("MINT", 3, 0, 60, l / 2)
+            .swap("SWAP_IN", 1)
+            .swap("SWAP_IN_YX", WAD)
+            .swap("SWAP_IN", WAD)
+            .swap("SWAP_OUT", exact)
+            .swap("SWAP_OUT_YX", exact)
+            .position("COLLECT", 1, -60, 60, 0)
+            .position("COLLECT", 2, 60, 120, 0)
+            .position("COLLECT", 3, 0, 60, 0)
+            .balances();
+    });
+
+    script("LIMIT", &|s| {
+        s.new_pool(U256::zero(), 3_000, 60)
+            .new_pool(MAX_SQRT_RATIO, 3_000, 60)
+            .new_pool(sqrt_at(0), 1_000_001, 60)
+            .new_pool(sqrt_at(0), 3_000, 0)
+            .new_pool(sqrt_at(0), 3_000, 16_385)
+            .new_pool(sqrt_at(-887_000), 10_000, 1)
+            .position("MINT", 1, MIN_TICK, MAX_TICK, WAD)
+            .position("MINT", 2, MIN_TICK, MAX_TICK, u128::MAX >> 20)
+            .swap("SWAP_OUT_YX", 2 * WAD)
+            .swap("SWAP_IN_YX", u128::MAX / 2)
+            .swap("SWAP_IN", 1_000 * WAD)
+            .swap("SWAP_IN", u128::MAX / 2)
+            .position("BURN", 1, MIN_TICK, MAX_TICK, WAD)
+            .position("COLLECT", 1, MIN_TICK, MAX_TICK, 0)
+            .balances();
+    });
+    rows
+}
+
+/// Conteúdo completo do arquivo `amm_clmm_pool_v1.csv` (cabeçalho de proveniência + linhas).
+pub fn render_v1() -> Result<String> {
+    let mut pool = None;
+    let mut rows = Vec::new();
+    for r in grid() {
+        rows.push(render(&r, expected(&mut pool, &r)?));
+    }
+    let mut out = String::new();
+    out.push_str(&format!("# format: {}/{}\n", FORMAT_NAME, FORMAT_VERSION));
+    out.push_str("# generator: golden_gen clmm-pool (src/golden/clmm_pool.rs)\n");
+    out.push_str("# reference: ref_bigdecimal::RefClPool (liquidez ativa e taxas por faixa)\n");
+    out.push_str("# rounding: ADR-0001 mint=ceil burn=floor fee_growth=floor owed=floor\n");
+    out.push_str(&format!("# grid: {}\n", GRID_ID));
+    out.push_str(&format!("# rows: {}\n", rows.len()));
+    out.push_str(HEADER);
+    out.push('\n');
+    for r in rows {
+        out.push_str(&r);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "id,op,owner,tick_lower,tick_upper,liquidity,amount,sqrt_x96,fee_ppm,tick_spacing,expect_kind,expect\n\
+        A,NEW,0,0,0,0,0,79228162514264337593543950336,3000,60,ok,0\n\
+        B,MINT,1,-60,0,1000,0,,0,0,ok,0;3\n\
+        C,BURN,1,-60,0,2000,0,,0,0,err:InsufficientShares,\n\
+        D,BALANCES,0,0,0,0,0,,0,0,ok,0;3\n";
+
+    #[test]
+    fn t_parse_and_run_sequence() {
+        let rows = parse_csv(CSV).unwrap();
+        assert_eq!(rows.len(), 4);
+        let mut pool = None;
+        for r in &rows {
+            let got = eval_row(&mut pool, r).unwrap();
+            assert_eq!(compare(&r.id, &r.expect_kind, &r.expect, got).unwrap(), Ok(()), "{}", r.id);
+        }
+        assert!(eval_row(&mut None, &rows[1]).is_err());
+    }
+
+    #[test]
+    fn t_generator_matches_core_on_grid() {
+        let (mut core, mut model) = (None, None);
+        let mut errors = 0;
+        for r in grid() {
+            let want = expected(&mut model, &r).unwrap();
+            let got = eval_row(&mut core, &r).unwrap();
+            errors += want.is_err() as usize;
+            assert_eq!(got.as_ref().map_err(variant_name), want.as_ref().map_err(variant_name), "{}", r.id);
+        }
+        assert!(errors > 10);
+    }
+}
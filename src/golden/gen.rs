@@ -0,0 +1,127 @@
+//! Gerador de goldens v2 a partir do modelo de referência exato (`ref_bigdecimal::spec_*`).
+//! A saída é determinística (sem data/hora): regenerar e comparar com o arquivo versionado
+//! transforma qualquer mudança na política de arredondamento num diff revisável.
+
+use anyhow::{bail, Result};
+
+use crate::amm::errors::AmmError;
+use crate::amm::ref_bigdecimal as rf;
+use crate::amm::types::{Ppm, Wad, MIN_RESERVE, WAD};
+
+use super::{variant_name, FORMAT_NAME, FORMAT_VERSION};
+
+/// Identificador da grade de cenários (mude ao alterar `scenarios()`/`fees`).
+pub const GRID_ID: &str = "grid-2";
+
+const HEADER: &str = "id,op,x_wad,y_wad,dx_wad,dy_wad,fee_ppm,expect_kind,expect_wad,shares_wad,total_shares_wad";
+const FEES: [Ppm; 2] = [0, 3000];
+
+/// Cenário de reservas `(nome, x, y)`.
+pub fn scenarios() -> Vec<(&'static str, Wad, Wad)> {
+    vec![
+        ("SYM", 1_000_000 * WAD, 1_000_000 * WAD),
+        ("ASYM_XGG", 1_000_000_000 * WAD, 1_000 * WAD),
+        ("ASYM_YGG", 1_000 * WAD, 1_000_000_000 * WAD),
+        ("NEAR_MIN", MIN_RESERVE + 1_000, MIN_RESERVE + 1_000),
+        ("NEAR_MIN_X", MIN_RESERVE + 5, 1_000_000 * WAD),
+        ("MAX_HALF", u128::MAX / 2, u128::MAX / 2),
+        ("MAX_X", u128::MAX - 1, 1_000_000 * WAD),
+    ]
+}
+
+#[derive(Default)]
+struct RowSpec {
+    id: String,
+    op: &'static str,
+    x: Wad,
+    y: Wad,
+    dx: Wad,
+    dy: Wad,
+    fee_ppm: Ppm,
+    shares: Wad,
+    total_shares: Wad,
+}
+
+fn render(r: &RowSpec, result: std::result::Result<String, AmmError>) -> String {
+    let opt = |v: Wad| if v == 0 { String::new() } else { v.to_string() };
+    let (kind, value) = match result {
+        Ok(v) => ("ok".to_string(), v),
+        Err(e) => (format!("err:{}", variant_name(&e)), String::new()),
+    };
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{}",
+        r.id, r.op, r.x, r.y, r.dx, r.dy, r.fee_ppm, kind, value, opt(r.shares), opt(r.total_shares)
+    )
+}
+
+/// Valor esperado da linha segundo a especificação exata (erro externo: op sem gerador).
+fn expected(r: &RowSpec) -> Result<std::result::Result<String, AmmError>> {
+    let s = |v: Wad| v.to_string();
+    Ok(match r.op {
+        "OUT" => rf::spec_amount_out(r.x, r.y, r.dx, r.fee_ppm).map(s),
+        "OUT_YX" => rf::spec_amount_out(r.y, r.x, r.dy, r.fee_ppm).map(s),
+        "IN" => rf::spec_amount_in(r.x, r.y, r.dy, r.fee_ppm).map(s),
+        "IN_YX" => rf::spec_amount_in(r.y, r.x, r.dx, r.fee_ppm).map(s),
+        "SPOT" => rf::spec_spot_price_x_in_y(r.x, r.y).map(s),
+        "SPOT_YX" => rf::spec_spot_price_x_in_y(r.y, r.x).map(s),
+        "SLIPPAGE" => rf::spec_slippage_ppm_x_to_y(r.x, r.y, r.dx, r.fee_ppm).map(|v| v.to_string()),
+        "SLIPPAGE_YX" => rf::spec_slippage_ppm_x_to_y(r.y, r.x, r.dy, r.fee_ppm).map(|v| v.to_string()),
+        "MINT" => rf::spec_initial_mint(r.x, r.y).map(s),
+        "ADD" => rf::spec_add_liquidity(r.x, r.y, r.dx, r.dy, r.total_shares).map(s),
+        "REMOVE" => rf::spec_remove_liquidity(r.x, r.y, r.shares, r.total_shares).map(|(a, b)| format!("{};{}", a, b)),
+        other => bail!("{}: op sem gerador {:?}", r.id, other),
+    })
+}
+
+/// Linhas da grade: para cada cenário, swaps nos dois sentidos (1 wei, 0,1%, 10%, até o mínimo),
+/// preços, slippage e liquidez (mint, add 10%, remove 10% e 100%).
+fn grid() -> Vec<RowSpec> {
+    let mut rows = Vec::new();
+    for (name, x, y) in scenarios() {
+        for fee in FEES {
+            for (label, frac) in [("1WEI", 0u128), ("P001", 1_000), ("P10", 10)] {
+                let amt = |r: Wad| r.checked_div(frac).unwrap_or(1); // frac=0 ⇒ 1 wei
+                rows.push(RowSpec { id: format!("{}_OUT_{}_F{}", name, label, fee), op: "OUT", x, y, dx: amt(x), fee_ppm: fee, ..Default::default() });
+                rows.push(RowSpec { id: format!("{}_OUT_YX_{}_F{}", name, label, fee), op: "OUT_YX", x, y, dy: amt(y), fee_ppm: fee, ..Default::default() });
+                rows.push(RowSpec { id: format!("{}_IN_{}_F{}", name, label, fee), op: "IN", x, y, dy: amt(y), fee_ppm: fee, ..Default::default() });
+                rows.push(RowSpec { id: format!("{}_IN_YX_{}_F{}", name, label, fee), op: "IN_YX", x, y, dx: amt(x), fee_ppm: fee, ..Default::default() });
+            }
+            // alvo que esvaziaria o pool até o mínimo ⇒ rejeitado
+            rows.push(RowSpec { id: format!("{}_IN_DRAIN_F{}", name, fee), op: "IN", x, y, dy: y.saturating_sub(MIN_RESERVE), fee_ppm: fee, ..Default::default() });
+            rows.push(RowSpec { id: format!("{}_SLIPPAGE_P10_F{}", name, fee), op: "SLIPPAGE", x, y, dx: x / 10, fee_ppm: fee, ..Default::default() });
+            rows.push(RowSpec { id: format!("{}_SLIPPAGE_YX_P10_F{}", name, fee), op: "SLIPPAGE_YX", x, y, dy: y / 10, fee_ppm: fee, ..Default::default() });
+        }
+        // bordas de taxa (ADR-0002 §4): 999_999 é válido; 1e6 e u32::MAX são rejeitados
+        for fee in [999_999, 1_000_000, u32::MAX] {
+            rows.push(RowSpec { id: format!("{}_OUT_P001_F{}", name, fee), op: "OUT", x, y, dx: x / 1_000, fee_ppm: fee, ..Default::default() });
+            rows.push(RowSpec { id: format!("{}_IN_P001_F{}", name, fee), op: "IN", x, y, dy: y / 1_000, fee_ppm: fee, ..Default::default() });
+        }
+        rows.push(RowSpec { id: format!("{}_SPOT", name), op: "SPOT", x, y, ..Default::default() });
+        rows.push(RowSpec { id: format!("{}_SPOT_YX", name), op: "SPOT_YX", x, y, ..Default::default() });
+        rows.push(RowSpec { id: format!("{}_MINT", name), op: "MINT", x, y, ..Default::default() });
+        let total = rf::spec_initial_mint(x, y).unwrap_or(WAD);
+        rows.push(RowSpec { id: format!("{}_ADD_P10", name), op: "ADD", x, y, dx: x / 10, dy: y / 10, total_shares: total, ..Default::default() });
+        rows.push(RowSpec { id: format!("{}_REMOVE_P10", name), op: "REMOVE", x, y, shares: total / 10, total_shares: total, ..Default::default() });
+        rows.push(RowSpec { id: format!("{}_REMOVE_ALL", name), op: "REMOVE", x, y, shares: total, total_shares: total, ..Default::default() });
+    }
+    rows
+}
+
+/// Conteúdo completo do arquivo `amm_cpmw_v2.csv` (cabeçalho de proveniência + linhas).
+pub fn render_v2() -> Result<String> {
+    let rows = grid().iter().map(|r| Ok(render(r, expected(r)?))).collect::<Result<Vec<String>>>()?;
+    let mut out = String::new();
+    out.push_str(&format!("# format: {}/{}\n", FORMAT_NAME, FORMAT_VERSION));
+    out.push_str("# generator: golden_gen (src/golden/gen.rs)\n");
+    out.push_str("# reference: ref_bigdecimal::spec_* (racionais exatos)\n");
+    out.push_str("# rounding: ADR-0001 out=floor in=ceil fee=ceil mint=floor burn=floor precos=nearest-even\n");
+    out.push_str(&format!("# grid: {}\n", GRID_ID));
+    out.push_str(&format!("# rows: {}\n", rows.len()));
+    out.push_str(HEADER);
+    out.push('\n');
+    for r in rows {
+        out.push_str(&r);
+        out.push('\n');
+    }
+    Ok(out)
+}
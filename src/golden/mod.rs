@@ -0,0 +1,291 @@
+//! Runner de goldens do AMM: lê o CSV (`goldens/amm_cpmw_v1.csv`), confere o `.sha256`
+//! e executa cada linha contra as funções do core.
+//!
+//! Colunas: `id,op,x_wad,y_wad,dx_wad,dy_wad,fee_ppm,expect_kind,expect_wad[,shares_wad,total_shares_wad]`.
+//! Campos vazios valem 0. `expect_kind` é `ok` ou `err:<Variant>` (nome da variante de `AmmError`).
+//!
+//! Formato versionado: a partir da v2 o arquivo abre com linhas `# chave: valor` de proveniência
+//! (a primeira é `# format: amm_cpmw/<versão>`). Arquivos sem cabeçalho são v1.
+//!
+//! | op | entradas | esperado (`expect_wad`) |
+//! |---|---|---|
+//! | `OUT` / `OUT_YX` | `dx` / `dy` de entrada | output |
+//! | `IN` / `IN_YX` | `dy` / `dx` alvo | input bruto mínimo |
+//! | `MINT` | `x`, `y` | shares iniciais |
+//! | `ADD` | `dx`, `dy`, `total_shares_wad` | shares mintados |
+//! | `REMOVE` | `shares_wad` (burn), `total_shares_wad` | `x_out;y_out` |
+//! | `SPOT` / `SPOT_YX` | `x`, `y` | preço em WAD |
+//! | `SLIPPAGE` / `SLIPPAGE_YX` | `dx` / `dy`, `fee_ppm` | slippage em ppm |
+
+use anyhow::{anyhow, bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::amm::errors::AmmError;
+use crate::amm::liquidity::{add_liquidity, initial_mint, remove_liquidity};
+use crate::amm::pricing::{slippage_ppm_dir, spot_price_dir};
+use crate::amm::swap::{get_amount_in_dir, get_amount_out_dir};
+use crate::amm::types::{Direction, Ppm, Wad};
+
+pub mod clmm;
+pub mod clmm_pool;
+pub mod gen;
+
+/// Nome do formato na linha `# format:`.
+pub const FORMAT_NAME: &str = "amm_cpmw";
+/// Maior versão de formato que este runner entende.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Metadados de proveniência (`# chave: valor`) do topo do arquivo.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoldenMeta {
+    pub version: u32,
+    pub entries: Vec<(String, String)>,
+}
+
+impl GoldenMeta {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenRow {
+    pub id: String,
+    pub op: String,
+    pub x: Wad,
+    pub y: Wad,
+    pub dx: Wad,
+    pub dy: Wad,
+    pub fee_ppm: Ppm,
+    pub shares: Wad,
+    pub total_shares: Wad,
+    pub expect_kind: String,
+    pub expect: String,
+}
+
+/// Resultado de uma linha: `Err` traz a descrição da divergência.
+pub type RowOutcome = std::result::Result<(), String>;
+
+/// Hex do sha256 de `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Confere `bytes` contra o conteúdo de um arquivo `.sha256` (formato `sha256sum`).
+pub fn verify_sha256(bytes: &[u8], sha_file: &str) -> Result<()> {
+    let expected = sha_file.split_whitespace().next().ok_or_else(|| anyhow!("arquivo .sha256 vazio"))?;
+    let actual = sha256_hex(bytes);
+    if !expected.eq_ignore_ascii_case(&actual) {
+        bail!("sha256 divergente: esperado {}, obtido {}", expected, actual);
+    }
+    Ok(())
+}
+
+fn parse_u128(field: &str, col: &str, id: &str) -> Result<u128> {
+    if field.is_empty() { return Ok(0); }
+    field.parse::<u128>().with_context(|| format!("{}: coluna {} inválida: {:?}", id, col, field))
+}
+
+/// Lê as linhas `# chave: valor` do topo; sem `# format:` o arquivo é v1.
+pub fn parse_meta(text: &str) -> Result<GoldenMeta> { parse_meta_as(text, FORMAT_NAME, FORMAT_VERSION) }
+
+/// `parse_meta` para outro formato (`# format: <name>/<versão>`, até `max_version`).
+pub fn parse_meta_as(text: &str, name: &str, max_version: u32) -> Result<GoldenMeta> {
+    let mut meta = GoldenMeta { version: 1, entries: Vec::new() };
+    for line in text.lines().take_while(|l| l.starts_with('#')) {
+        let (k, v) = line.trim_start_matches('#').split_once(':').ok_or_else(|| anyhow!("metadado inválido: {:?}", line))?;
+        meta.entries.push((k.trim().to_string(), v.trim().to_string()));
+    }
+    if let Some(format) = meta.get("format") {
+        let version = format
+            .strip_prefix(name)
+            .and_then(|v| v.strip_prefix('/'))
+            .and_then(|v| v.parse::<u32>().ok())
+            .ok_or_else(|| anyhow!("format inválido: {:?}", format))?;
+        meta.version = version;
+    }
+    if meta.version > max_version {
+        bail!("formato v{} não suportado (máximo v{})", meta.version, max_version);
+    }
+    Ok(meta)
+}
+
+/// Faz o parse do CSV (colunas localizadas pelo cabeçalho; linhas `#` são metadados).
+pub fn parse_csv(text: &str) -> Result<Vec<GoldenRow>> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+    let header: Vec<&str> = lines.next().ok_or_else(|| anyhow!("CSV sem cabeçalho"))?.split(',').map(str::trim).collect();
+    let col = |name: &str| header.iter().position(|h| *h == name);
+    for required in ["id", "op", "x_wad", "y_wad", "expect_kind", "expect_wad"] {
+        if col(required).is_none() { bail!("coluna obrigatória ausente: {}", required); }
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let get = |name: &str| col(name).and_then(|i| fields.get(i).copied()).unwrap_or("");
+        let id = get("id").to_string();
+        let fee = parse_u128(get("fee_ppm"), "fee_ppm", &id)?;
+        rows.push(GoldenRow {
+            op: get("op").to_string(),
+            x: parse_u128(get("x_wad"), "x_wad", &id)?,
+            y: parse_u128(get("y_wad"), "y_wad", &id)?,
+            dx: parse_u128(get("dx_wad"), "dx_wad", &id)?,
+            dy: parse_u128(get("dy_wad"), "dy_wad", &id)?,
+            fee_ppm: Ppm::try_from(fee).with_context(|| format!("{}: fee_ppm fora de u32", id))?,
+            shares: parse_u128(get("shares_wad"), "shares_wad", &id)?,
+            total_shares: parse_u128(get("total_shares_wad"), "total_shares_wad", &id)?,
+            expect_kind: get("expect_kind").to_string(),
+            expect: get("expect_wad").to_string(),
+            id,
+        });
+    }
+    Ok(rows)
+}
+
+/// Nome da variante de um `AmmError` (ex.: `SlippageExceeded`).
+pub fn variant_name(e: &AmmError) -> String {
+    format!("{:?}", e).chars().take_while(|c| c.is_alphanumeric()).collect()
+}
+
+/// Executa a operação da linha e devolve o valor obtido no formato de `expect_wad`.
+pub fn eval_row(row: &GoldenRow) -> Result<std::result::Result<String, AmmError>> {
+    let dir = if row.op.ends_with("_YX") { Direction::YtoX } else { Direction::XtoY };
+    // input no lado de entrada do sentido; alvo no lado de saída
+    let (d_in, d_out) = match dir { Direction::XtoY => (row.dx, row.dy), Direction::YtoX => (row.dy, row.dx) };
+    let base = row.op.trim_end_matches("_YX");
+    let got = match base {
+        "OUT" => get_amount_out_dir(dir, row.x, row.y, d_in, row.fee_ppm).map(|v| v.to_string()),
+        "IN" => get_amount_in_dir(dir, row.x, row.y, d_out, row.fee_ppm).map(|v| v.to_string()),
+        "SPOT" => spot_price_dir(dir, row.x, row.y).map(|v| v.to_string()),
+        "SLIPPAGE" => slippage_ppm_dir(dir, row.x, row.y, d_in, row.fee_ppm).map(|v| v.to_string()),
+        "MINT" => initial_mint(row.x, row.y).map(|v| v.to_string()),
+        "ADD" => add_liquidity(row.x, row.y, row.dx, row.dy, row.total_shares).map(|v| v.to_string()),
+        "REMOVE" => remove_liquidity(row.x, row.y, row.shares, row.total_shares).map(|(a, b)| format!("{};{}", a, b)),
+        other => bail!("{}: op desconhecida {:?}", row.id, other),
+    };
+    Ok(got)
+}
+
+/// Executa uma linha e compara com o esperado.
+pub fn run_row(row: &GoldenRow) -> Result<RowOutcome> {
+    compare(&row.id, &row.expect_kind, &row.expect, eval_row(row)?)
+}
+
+/// Compara o obtido com `expect_kind`/`expect` (`ok` + valor, ou `err:<Variant>`).
+pub fn compare(id: &str, expect_kind: &str, expect: &str, got: std::result::Result<String, AmmError>) -> Result<RowOutcome> {
+    let outcome = match (expect_kind, got) {
+        ("ok", Ok(v)) if v == expect => Ok(()),
+        ("ok", Ok(v)) => Err(format!("esperado {}, obtido {}", expect, v)),
+        ("ok", Err(e)) => Err(format!("esperado {}, obtido err:{}", expect, variant_name(&e))),
+        (kind, got) => match kind.strip_prefix("err:") {
+            None => bail!("{}: expect_kind inválido {:?}", id, kind),
+            Some(want) => match got {
+                Err(e) if variant_name(&e) == want => Ok(()),
+                Err(e) => Err(format!("esperado err:{}, obtido err:{}", want, variant_name(&e))),
+                Ok(v) => Err(format!("esperado err:{}, obtido {}", want, v)),
+            },
+        },
+    };
+    Ok(outcome)
+}
+
+/// Relatório de execução de um arquivo golden.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub passed: usize,
+    /// `(id, descrição da divergência)`
+    pub failures: Vec<(String, String)>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool { self.failures.is_empty() }
+}
+
+/// Lê `csv_path` e confere contra `csv_path.sha256`.
+pub fn read_verified(csv_path: &Path) -> Result<String> {
+    let bytes = std::fs::read(csv_path).with_context(|| format!("lendo {}", csv_path.display()))?;
+    let mut sha_path = csv_path.as_os_str().to_owned();
+    sha_path.push(".sha256");
+    let sha = std::fs::read_to_string(&sha_path).with_context(|| format!("lendo {:?}", sha_path))?;
+    verify_sha256(&bytes, &sha)?;
+    String::from_utf8(bytes).context("CSV não é UTF-8")
+}
+
+/// Confere a contagem `# rows:` do cabeçalho, quando presente.
+pub fn check_row_count(meta: &GoldenMeta, actual: usize) -> Result<()> {
+    if let Some(rows) = meta.get("rows") {
+        let declared: usize = rows.parse().with_context(|| format!("rows inválido: {:?}", rows))?;
+        if declared != actual { bail!("cabeçalho declara {} linhas, arquivo tem {}", declared, actual); }
+    }
+    Ok(())
+}
+
+/// Lê `csv_path`, confere `csv_path.sha256` e executa todas as linhas.
+pub fn run_file(csv_path: &Path) -> Result<Report> {
+    let text = read_verified(csv_path)?;
+    let meta = parse_meta(&text)?;
+    check_row_count(&meta, parse_csv(&text)?.len())?;
+    let mut report = Report::default();
+    for row in parse_csv(&text)? {
+        match run_row(&row)? {
+            Ok(()) => report.passed += 1,
+            Err(msg) => report.failures.push((row.id, msg)),
+        }
+    }
+    Ok(report)
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "id,op,x_wad,y_wad,dx_wad,dy_wad,fee_ppm,expect_kind,expect_wad\n\
+        A,OUT,1000000000000000000000000,1000000000000000000000000,10000000000000000000000,0,0,ok,9900990099009900990099\n\
+        B,OUT,5000000000000000000000000,4000000000000000000000000,1,0,3000,err:InputTooSmall,\n";
+
+    #[test]
+    fn t_parse_and_run() {
+        let rows = parse_csv(CSV).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].expect_kind, "err:InputTooSmall");
+        assert_eq!(rows[0].total_shares, 0); // coluna ausente ⇒ 0
+        for r in &rows { assert_eq!(run_row(r).unwrap(), Ok(())); }
+    }
+
+    #[test]
+    fn t_mismatch_reported() {
+        let mut row = parse_csv(CSV).unwrap().remove(0);
+        row.expect = "1".into();
+        assert!(run_row(&row).unwrap().is_err());
+        row.expect_kind = "err:Overflow".into();
+        assert!(run_row(&row).unwrap().is_err());
+    }
+
+    #[test]
+    fn t_meta_versions() {
+        assert_eq!(parse_meta(CSV).unwrap().version, 1);
+        let v2 = format!("# format: amm_cpmw/2\n# generator: golden_gen\n{}", CSV);
+        let meta = parse_meta(&v2).unwrap();
+        assert_eq!(meta.version, 2);
+        assert_eq!(meta.get("generator"), Some("golden_gen"));
+        assert_eq!(parse_csv(&v2).unwrap().len(), 2);
+        assert!(parse_meta("# format: amm_cpmw/99\n").is_err());
+    }
+
+    #[test]
+    fn t_sha256() {
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert!(verify_sha256(b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  f.csv").is_ok());
+        assert!(verify_sha256(b"abd", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").is_err());
+    }
+
+    #[test]
+    fn t_variant_name() {
+        assert_eq!(variant_name(&AmmError::SlippageExceeded { expected: 1, actual: 0 }), "SlippageExceeded");
+        assert_eq!(variant_name(&AmmError::Overflow), "Overflow");
+    }
+}
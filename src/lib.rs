@@ -1,5 +1,8 @@
 /* lib (CRD-7-10 FINAL) */
 pub mod amm; // existe
 pub mod ce_core; // expõe o namespace ce_core
+pub mod credit; // linhas de crédito sobre os pools do AMM
 
+pub mod golden; // runner de goldens (CSV + sha256)
+pub mod store; // persistência do pool (WAL + snapshots)
 pub mod telemetry;
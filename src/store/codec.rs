@@ -0,0 +1,182 @@
+//! Codificação em texto (uma linha, campos separados por `,`) de eventos e snapshots.
+//!
+//! - evento: `seq,<op>,<args...>,<outcome>,<args...>,<state_hash hex>`
+//! - snapshot: `seq,x,y,total_shares,fee_ppm,min_reserve,protocol_fee_ppm,fees_x,fees_y,<hash hex>`
+//!
+//! Tags numéricas (estáveis): ops `0` Create, `1` SwapExactIn, `2` SwapExactOut, `3` AddLiquidity,
+//! `4` RemoveLiquidity, `5` CollectProtocolFees, `6` SetFee, `7` SetProtocolFee; outcomes `0` Created,
+//! `1` Swap, `2` Liquidity, `3` Collected, `4` ParamsChanged; sentido `0` = X→Y, `1` = Y→X.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::str::FromStr;
+
+use crate::amm::fees::ProtocolFees;
+use crate::amm::ledger::{OpOutcome, PoolEvent, PoolOp};
+use crate::amm::pool::{LiquidityReceipt, PoolState, SwapReceipt};
+use crate::amm::types::{Direction, Reserves, U256};
+
+fn hex(bytes: &[u8; 32]) -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() }
+
+fn unhex(s: &str) -> Result<[u8; 32]> {
+    if s.len() != 64 || !s.is_ascii() { bail!("hash inválido: {s:?}"); }
+    let mut out = [0u8; 32];
+    for (i, b) in out.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).with_context(|| format!("hash inválido: {s:?}"))?;
+    }
+    Ok(out)
+}
+
+fn dir_tag(d: Direction) -> u8 { match d { Direction::XtoY => 0, Direction::YtoX => 1 } }
+
+/// Leitor sequencial dos campos de uma linha.
+struct Fields<'a>(std::str::Split<'a, char>);
+
+impl Fields<'_> {
+    fn next_str(&mut self) -> Result<&str> { self.0.next().ok_or_else(|| anyhow!("campo faltando")) }
+
+    fn num<T: FromStr>(&mut self) -> Result<T> {
+        let s = self.next_str()?;
+        s.parse().map_err(|_| anyhow!("número inválido: {s:?}"))
+    }
+
+    fn u256(&mut self) -> Result<U256> {
+        let s = self.next_str()?;
+        U256::from_dec_str(s).map_err(|_| anyhow!("número inválido: {s:?}"))
+    }
+
+    fn dir(&mut self) -> Result<Direction> {
+        match self.num::<u8>()? {
+            0 => Ok(Direction::XtoY),
+            1 => Ok(Direction::YtoX),
+            t => bail!("sentido desconhecido: {t}"),
+        }
+    }
+
+    fn hash(&mut self) -> Result<[u8; 32]> { unhex(self.next_str()?) }
+
+    fn end(mut self) -> Result<()> {
+        if self.0.next().is_some() { bail!("campos sobrando"); }
+        Ok(())
+    }
+}
+
+pub fn encode_event(e: &PoolEvent) -> String {
+    let mut f: Vec<String> = vec![e.seq.to_string()];
+    match e.op {
+        PoolOp::Create { x, y, fee_ppm, min_reserve } => f.extend(["0".into(), x.to_string(), y.to_string(), fee_ppm.to_string(), min_reserve.to_string()]),
+        PoolOp::SwapExactIn { direction, amount_in, min_out } => f.extend(["1".into(), dir_tag(direction).to_string(), amount_in.to_string(), min_out.to_string()]),
+        PoolOp::SwapExactOut { direction, amount_out, max_in } => f.extend(["2".into(), dir_tag(direction).to_string(), amount_out.to_string(), max_in.to_string()]),
+        PoolOp::AddLiquidity { amount_x, amount_y } => f.extend(["3".into(), amount_x.to_string(), amount_y.to_string()]),
+        PoolOp::RemoveLiquidity { shares } => f.extend(["4".into(), shares.to_string()]),
+        PoolOp::CollectProtocolFees => f.push("5".into()),
+        PoolOp::SetFee { fee_ppm } => f.extend(["6".into(), fee_ppm.to_string()]),
+        PoolOp::SetProtocolFee { protocol_fee_ppm } => f.extend(["7".into(), protocol_fee_ppm.to_string()]),
+    }
+    match e.outcome {
+        OpOutcome::Created { shares } => f.extend(["0".into(), shares.to_string()]),
+        OpOutcome::Swap(r) => f.extend([
+            "1".into(),
+            dir_tag(r.direction).to_string(),
+            r.amount_in.to_string(),
+            r.amount_out.to_string(),
+            r.fee.to_string(),
+            r.lp_fee.to_string(),
+            r.protocol_fee.to_string(),
+            r.k.to_string(),
+        ]),
+        OpOutcome::Liquidity(r) => f.extend(["2".into(), r.amount_x.to_string(), r.amount_y.to_string(), r.shares.to_string(), r.k.to_string()]),
+        OpOutcome::Collected(c) => f.extend(["3".into(), c.x.to_string(), c.y.to_string()]),
+        OpOutcome::ParamsChanged => f.push("4".into()),
+    }
+    f.push(hex(&e.state_hash));
+    f.join(",")
+}
+
+pub fn decode_event(line: &str) -> Result<PoolEvent> {
+    let mut f = Fields(line.split(','));
+    let seq = f.num()?;
+    let op = match f.num::<u8>()? {
+        0 => PoolOp::Create { x: f.num()?, y: f.num()?, fee_ppm: f.num()?, min_reserve: f.num()? },
+        1 => PoolOp::SwapExactIn { direction: f.dir()?, amount_in: f.num()?, min_out: f.num()? },
+        2 => PoolOp::SwapExactOut { direction: f.dir()?, amount_out: f.num()?, max_in: f.num()? },
+        3 => PoolOp::AddLiquidity { amount_x: f.num()?, amount_y: f.num()? },
+        4 => PoolOp::RemoveLiquidity { shares: f.num()? },
+        5 => PoolOp::CollectProtocolFees,
+        6 => PoolOp::SetFee { fee_ppm: f.num()? },
+        7 => PoolOp::SetProtocolFee { protocol_fee_ppm: f.num()? },
+        t => bail!("op desconhecida: {t}"),
+    };
+    let outcome = match f.num::<u8>()? {
+        0 => OpOutcome::Created { shares: f.num()? },
+        1 => OpOutcome::Swap(SwapReceipt {
+            direction: f.dir()?,
+            amount_in: f.num()?,
+            amount_out: f.num()?,
+            fee: f.num()?,
+            lp_fee: f.num()?,
+            protocol_fee: f.num()?,
+            k: f.u256()?,
+        }),
+        2 => OpOutcome::Liquidity(LiquidityReceipt { amount_x: f.num()?, amount_y: f.num()?, shares: f.num()?, k: f.u256()? }),
+        3 => OpOutcome::Collected(ProtocolFees { x: f.num()?, y: f.num()? }),
+        4 => OpOutcome::ParamsChanged,
+        t => bail!("outcome desconhecido: {t}"),
+    };
+    let state_hash = f.hash()?;
+    f.end()?;
+    Ok(PoolEvent { seq, op, outcome, state_hash })
+}
+
+pub fn encode_snapshot(seq: u64, s: &PoolState) -> String {
+    let nums = [s.reserves.x, s.reserves.y, s.total_shares, s.fee_ppm.into(), s.min_reserve, s.protocol_fee_ppm.into(), s.protocol_fees.x, s.protocol_fees.y];
+    let mut f: Vec<String> = vec![seq.to_string()];
+    f.extend(nums.iter().map(|n| n.to_string()));
+    f.push(hex(&s.hash()));
+    f.join(",")
+}
+
+/// Decodifica e confere o hash do estado.
+pub fn decode_snapshot(line: &str) -> Result<(u64, PoolState)> {
+    let mut f = Fields(line.split(','));
+    let seq = f.num()?;
+    let state = PoolState {
+        reserves: Reserves::new(f.num()?, f.num()?),
+        total_shares: f.num()?,
+        fee_ppm: f.num()?,
+        min_reserve: f.num()?,
+        protocol_fee_ppm: f.num()?,
+        protocol_fees: ProtocolFees { x: f.num()?, y: f.num()? },
+    };
+    let hash = f.hash()?;
+    f.end()?;
+    if state.hash() != hash { bail!("snapshot {seq}: hash não confere"); }
+    Ok((seq, state))
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::ledger::PoolLedger;
+    use crate::amm::types::{MIN_RESERVE, WAD};
+
+    #[test]
+    fn t_event_and_snapshot_roundtrip() {
+        let mut l = PoolLedger::create(1_000 * WAD, 2_000 * WAD, 3_000, MIN_RESERVE).unwrap();
+        l.apply(PoolOp::SetProtocolFee { protocol_fee_ppm: 100_000 }).unwrap();
+        l.apply(PoolOp::SwapExactOut { direction: Direction::YtoX, amount_out: WAD, max_in: u128::MAX }).unwrap();
+        l.apply(PoolOp::AddLiquidity { amount_x: WAD, amount_y: WAD }).unwrap();
+        l.apply(PoolOp::CollectProtocolFees).unwrap();
+        for e in l.events() {
+            assert_eq!(decode_event(&encode_event(e)).unwrap(), *e);
+        }
+        let s = l.pool().state();
+        assert_eq!(decode_snapshot(&encode_snapshot(4, &s)).unwrap(), (4, s));
+        let tampered = encode_snapshot(4, &s).replacen(&s.reserves.x.to_string(), &(s.reserves.x + 1).to_string(), 1);
+        assert!(decode_snapshot(&tampered).is_err());
+        assert!(decode_event("0,9").is_err());
+        assert!(decode_event(&format!("{},1", encode_event(&l.events()[0]))).is_err());
+    }
+}
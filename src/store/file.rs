@@ -0,0 +1,286 @@
+//! `StateStore` num diretório local:
+//! - `pool.wal`: um evento por linha, `<evento>|<sha256 hex do evento>`, com `fsync` a cada append;
+//! - `snapshot-<seq>.snap`: uma linha de `codec::encode_snapshot`, gravada em `.tmp` e renomeada
+//!   (rename atômico), então um snapshot existe inteiro ou não existe; se a gravação falha, o `.tmp` é
+//!   apagado. Ficam os `KEEP_SNAPSHOTS` mais novos.
+//!
+//! Um append que falha no meio (disco cheio, erro de I/O) trunca o WAL de volta ao tamanho anterior,
+//! para o próximo registro não ficar colado no pedaço gravado.
+//!
+//! Na leitura, um registro final incompleto ou com checksum errado (queda no meio do append) é
+//! descartado e o WAL é truncado no último registro íntegro; um registro ruim **no meio** do WAL é
+//! corrupção e vira erro. Snapshots com hash errado são ignorados.
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::codec::{decode_event, decode_snapshot, encode_event, encode_snapshot};
+use super::{StateStore, StoredLog};
+use crate::amm::ledger::PoolEvent;
+use crate::amm::pool::PoolState;
+
+/// Snapshots mantidos no diretório (os demais são apagados).
+pub const KEEP_SNAPSHOTS: usize = 2;
+
+const WAL_FILE: &str = "pool.wal";
+
+fn checksum(body: &str) -> String { Sha256::digest(body.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect() }
+
+/// Valida e decodifica uma linha do WAL (sem o `\n`).
+fn parse_record(line: &str) -> Option<PoolEvent> {
+    let (body, sum) = line.rsplit_once('|')?;
+    if checksum(body) != sum { return None; }
+    decode_event(body).ok()
+}
+
+#[derive(Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    wal: File,
+    /// Testes: o próximo append grava só esse número de bytes e falha
+    #[cfg(test)]
+    fail_append_after: Option<usize>,
+}
+
+impl FileStore {
+    /// Abre (ou cria) o store em `dir`.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).with_context(|| format!("criando {}", dir.display()))?;
+        let wal = OpenOptions::new().create(true).append(true).open(dir.join(WAL_FILE)).context("abrindo WAL")?;
+        let store = Self {
+            dir,
+            wal,
+            #[cfg(test)]
+            fail_append_after: None,
+        };
+        // a entrada do `pool.wal` recém-criado só é durável depois do fsync do diretório
+        store.sync_dir()?;
+        Ok(store)
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    fn snapshot_path(&self, seq: u64) -> PathBuf { self.dir.join(format!("snapshot-{seq:020}.snap")) }
+
+    /// Snapshots no diretório, `(seq, caminho)` do mais recente ao mais antigo.
+    fn snapshot_files(&self) -> Result<Vec<(u64, PathBuf)>> {
+        let mut out = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            if let Some(seq) = name.strip_prefix("snapshot-").and_then(|n| n.strip_suffix(".snap")).and_then(|n| n.parse::<u64>().ok()) {
+                out.push((seq, path));
+            }
+        }
+        out.sort_by_key(|s| std::cmp::Reverse(s.0));
+        Ok(out)
+    }
+
+    fn write_record(&mut self, record: &[u8]) -> Result<()> {
+        #[cfg(test)]
+        if let Some(n) = self.fail_append_after.take() {
+            self.wal.write_all(&record[..n.min(record.len())])?;
+            bail!("falha simulada no append");
+        }
+        self.wal.write_all(record)?;
+        self.wal.sync_data().context("fsync do WAL")
+    }
+
+    fn sync_dir(&self) -> Result<()> {
+        File::open(&self.dir)?.sync_all().context("fsync do diretório")
+    }
+}
+
+impl StateStore for FileStore {
+    fn append(&mut self, event: &PoolEvent) -> Result<()> {
+        let body = encode_event(event);
+        let len = self.wal.metadata().context("tamanho do WAL")?.len();
+        let written = self.write_record(format!("{}|{}\n", body, checksum(&body)).as_bytes());
+        if let Err(e) = written {
+            // descarta o que chegou a ser gravado; se nem isso der, o `load` trata como registro final torto
+            if let Err(undo) = self.wal.set_len(len).and_then(|()| self.wal.sync_data()) {
+                return Err(e.context(format!("truncando o WAL após append parcial: {}", undo)));
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn write_snapshot(&mut self, seq: u64, state: &PoolState) -> Result<()> {
+        let path = self.snapshot_path(seq);
+        let tmp = path.with_extension("tmp");
+        let written = File::create(&tmp)
+            .and_then(|mut f| {
+                f.write_all(format!("{}\n", encode_snapshot(seq, state)).as_bytes())?;
+                f.sync_all()
+            })
+            .and_then(|()| fs::rename(&tmp, &path));
+        if let Err(e) = written {
+            // não deixa `.tmp` órfão para trás; o snapshot anterior continua valendo
+            let _ = fs::remove_file(&tmp);
+            return Err(e).with_context(|| format!("gravando {}", path.display()));
+        }
+        self.sync_dir()?;
+        for (_, old) in self.snapshot_files()?.into_iter().skip(KEEP_SNAPSHOTS) {
+            fs::remove_file(old)?;
+        }
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<StoredLog> {
+        let path = self.dir.join(WAL_FILE);
+        let bytes = fs::read(&path).context("lendo WAL")?;
+        let mut events = Vec::new();
+        let mut good_len = 0;
+        let mut rest = bytes.as_slice();
+        while !rest.is_empty() {
+            let end = rest.iter().position(|&b| b == b'\n');
+            let record = end.and_then(|e| std::str::from_utf8(&rest[..e]).ok()).and_then(parse_record);
+            match (record, end) {
+                (Some(e), Some(n)) => {
+                    events.push(e);
+                    good_len += n + 1;
+                    rest = &rest[n + 1..];
+                }
+                // registro final (sem `\n` depois dele): append interrompido
+                (_, None) => break,
+                (_, Some(n)) if rest[n + 1..].is_empty() => break,
+                (None, Some(_)) => bail!("WAL corrompido no registro {}", events.len()),
+            }
+        }
+        if good_len < bytes.len() {
+            OpenOptions::new().write(true).open(&path)?.set_len(good_len as u64)?;
+            self.wal.sync_all()?;
+        }
+        let mut snapshots = Vec::new();
+        for (seq, path) in self.snapshot_files()? {
+            let line = fs::read_to_string(&path).unwrap_or_default();
+            if let Ok(snap) = decode_snapshot(line.trim_end()) {
+                if snap.0 == seq { snapshots.push(snap); }
+            }
+        }
+        Ok(StoredLog { events, snapshots })
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::ledger::PoolOp;
+    use crate::amm::types::{Direction, MIN_RESERVE, WAD};
+    use crate::store::PersistentPool;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ce_store_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn populated(dir: &Path) -> PersistentPool<FileStore> {
+        let mut p = PersistentPool::create(FileStore::open(dir).unwrap(), 100_000 * WAD, 50_000 * WAD, 3_000, MIN_RESERVE, 3).unwrap();
+        p.apply(PoolOp::SetProtocolFee { protocol_fee_ppm: 500_000 }).unwrap();
+        for i in 1..=8u128 {
+            let direction = if i % 2 == 0 { Direction::XtoY } else { Direction::YtoX };
+            p.apply(PoolOp::SwapExactIn { direction, amount_in: i * 100 * WAD, min_out: 0 }).unwrap();
+        }
+        p.apply(PoolOp::AddLiquidity { amount_x: 1_000 * WAD, amount_y: 500 * WAD }).unwrap();
+        p
+    }
+
+    #[test]
+    fn t_restart_recovers_state_and_keeps_recent_snapshots() {
+        let dir = temp_dir("restart");
+        let p = populated(&dir);
+        let seqs: Vec<u64> = p.store().snapshot_files().unwrap().iter().map(|s| s.0).collect();
+        assert_eq!(seqs, vec![9, 6]);
+        let expected = p.ledger().clone();
+        drop(p);
+        let mut reopened = PersistentPool::open(FileStore::open(&dir).unwrap(), 3).unwrap();
+        assert_eq!(reopened.ledger(), &expected);
+        // continua gravando depois de reabrir
+        reopened.apply(PoolOp::CollectProtocolFees).unwrap();
+        let again = PersistentPool::open(FileStore::open(&dir).unwrap(), 3).unwrap();
+        assert_eq!(again.ledger(), reopened.ledger());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn t_torn_append_is_truncated() {
+        let dir = temp_dir("torn");
+        let expected = populated(&dir).ledger().clone();
+        let wal = dir.join(WAL_FILE);
+        let clean = fs::read(&wal).unwrap();
+        // queda no meio de um append
+        let mut torn = clean.clone();
+        torn.extend_from_slice(b"11,1,0,123");
+        fs::write(&wal, &torn).unwrap();
+        let p = PersistentPool::open(FileStore::open(&dir).unwrap(), 3).unwrap();
+        assert_eq!(p.ledger(), &expected);
+        assert_eq!(fs::read(&wal).unwrap(), clean);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn t_failed_append_rolls_back_partial_record() {
+        let dir = temp_dir("partial");
+        let expected = populated(&dir).ledger().clone();
+        let wal = dir.join(WAL_FILE);
+        let clean = fs::read(&wal).unwrap();
+        let mut store = FileStore::open(&dir).unwrap();
+        let event = *expected.events().last().unwrap();
+        store.fail_append_after = Some(7);
+        assert!(store.append(&event).is_err());
+        assert_eq!(fs::read(&wal).unwrap(), clean);
+        // o próximo append começa numa linha limpa
+        store.append(&event).unwrap();
+        assert_eq!(store.load().unwrap().events.len(), expected.events().len() + 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn t_failed_snapshot_leaves_no_tmp() {
+        let dir = temp_dir("snapfail");
+        let p = populated(&dir);
+        let state = p.ledger().pool().state();
+        let mut store = FileStore::open(&dir).unwrap();
+        // diretório no lugar do snapshot: o rename falha
+        let blocked = store.snapshot_path(99);
+        fs::create_dir_all(blocked.join("x")).unwrap();
+        assert!(store.write_snapshot(99, &state).is_err());
+        assert!(!blocked.with_extension("tmp").exists());
+        fs::remove_dir_all(&blocked).unwrap();
+        assert_eq!(store.snapshot_files().unwrap().iter().map(|s| s.0).collect::<Vec<_>>(), vec![9, 6]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn t_corruption_detected() {
+        let dir = temp_dir("corrupt");
+        let expected = populated(&dir).ledger().clone();
+        // snapshot mais recente adulterado: recupera pelo anterior
+        let snap = dir.join(format!("snapshot-{:020}.snap", 9));
+        let line = fs::read_to_string(&snap).unwrap();
+        fs::write(&snap, line.replacen(",3000,", ",3001,", 1)).unwrap();
+        let mut store = FileStore::open(&dir).unwrap();
+        assert_eq!(store.load().unwrap().snapshots.iter().map(|s| s.0).collect::<Vec<_>>(), vec![6]);
+        assert_eq!(PersistentPool::open(store, 3).unwrap().ledger(), &expected);
+        // registro no meio do WAL adulterado: erro, nada é truncado
+        let wal = dir.join(WAL_FILE);
+        let text = fs::read_to_string(&wal).unwrap();
+        let mut lines: Vec<&str> = text.lines().collect();
+        let bad = lines[2].replacen(",1,", ",0,", 1);
+        lines[2] = &bad;
+        let tampered = lines.join("\n") + "\n";
+        fs::write(&wal, &tampered).unwrap();
+        assert!(PersistentPool::open(FileStore::open(&dir).unwrap(), 3).is_err());
+        assert_eq!(fs::read_to_string(&wal).unwrap(), tampered);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Persistência do estado do pool: log de eventos (`amm::ledger`) + snapshots periódicos.
+//!
+//! Um `StateStore` guarda o WAL (write-ahead log, append-only) e snapshots `(seq, PoolState)`.
+//! `PersistentPool` grava o evento no store **antes** de aplicá-lo em memória e, a cada
+//! `snapshot_every` eventos, grava um snapshot (falha no snapshot não desfaz o evento já durável:
+//! só é registrada). A recuperação (`PersistentPool::open`) parte do
+//! snapshot íntegro mais recente que bate com o `state_hash` do evento correspondente e reexecuta só a
+//! cauda do WAL; sem snapshot utilizável, reexecuta o log inteiro desde a gênese.
+//!
+//! Implementações: `FileStore` (diretório com WAL + arquivos de snapshot) e `MemoryStore` (testes).
+
+use anyhow::{bail, Result};
+
+use crate::amm::ledger::{OpOutcome, PoolEvent, PoolLedger, PoolOp};
+use crate::amm::pool::{Pool, PoolState};
+use crate::amm::types::{Ppm, Wad};
+
+pub mod codec;
+pub mod file;
+
+pub use file::FileStore;
+
+/// Conteúdo recuperado de um store.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoredLog {
+    /// WAL completo, na ordem de gravação
+    pub events: Vec<PoolEvent>,
+    /// Snapshots íntegros, do mais recente ao mais antigo
+    pub snapshots: Vec<(u64, PoolState)>,
+}
+
+/// Armazenamento durável de um pool.
+pub trait StateStore {
+    /// Acrescenta o evento ao WAL; ao retornar `Ok`, o evento é durável; com `Err`, não fica registro válido.
+    fn append(&mut self, event: &PoolEvent) -> Result<()>;
+    /// Grava o snapshot do estado após o evento `seq` (atômico: inteiro ou ausente).
+    fn write_snapshot(&mut self, seq: u64, state: &PoolState) -> Result<()>;
+    /// Lê o WAL e os snapshots íntegros, descartando um registro final incompleto.
+    fn load(&mut self) -> Result<StoredLog>;
+}
+
+/// Store em memória (sem durabilidade), para testes e simulação.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStore {
+    log: StoredLog,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self { Self::default() }
+}
+
+impl StateStore for MemoryStore {
+    fn append(&mut self, event: &PoolEvent) -> Result<()> {
+        self.log.events.push(*event);
+        Ok(())
+    }
+
+    fn write_snapshot(&mut self, seq: u64, state: &PoolState) -> Result<()> {
+        self.log.snapshots.insert(0, (seq, *state));
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<StoredLog> { Ok(self.log.clone()) }
+}
+
+/// `PoolLedger` com cada evento persistido num `StateStore`.
+#[derive(Debug)]
+pub struct PersistentPool<S: StateStore> {
+    ledger: PoolLedger,
+    store: S,
+    snapshot_every: u64,
+    /// Falha do último snapshot tentado (limpa no próximo que der certo)
+    snapshot_error: Option<anyhow::Error>,
+}
+
+impl<S: StateStore> PersistentPool<S> {
+    /// Cria o pool num store vazio e persiste a gênese. `snapshot_every = 0` desliga os snapshots.
+    pub fn create(mut store: S, x: Wad, y: Wad, fee_ppm: Ppm, min_reserve: Wad, snapshot_every: u64) -> Result<Self> {
+        if !store.load()?.events.is_empty() { bail!("store já contém um pool"); }
+        let ledger = PoolLedger::create(x, y, fee_ppm, min_reserve)?;
+        store.append(&ledger.events()[0])?;
+        Ok(Self { ledger, store, snapshot_every, snapshot_error: None })
+    }
+
+    /// Recupera o pool do store: snapshot mais recente válido + cauda do WAL.
+    pub fn open(mut store: S, snapshot_every: u64) -> Result<Self> {
+        let StoredLog { events, snapshots } = store.load()?;
+        if events.is_empty() { bail!("store vazio"); }
+        // snapshot que não bate com o WAL é ignorado (cai para o anterior ou para a gênese)
+        let ledger = snapshots
+            .into_iter()
+            .find_map(|snap| PoolLedger::restore(events.clone(), Some(snap)).ok())
+            .map_or_else(|| PoolLedger::restore(events, None), Ok)?;
+        Ok(Self { ledger, store, snapshot_every, snapshot_error: None })
+    }
+
+    pub fn ledger(&self) -> &PoolLedger { &self.ledger }
+    pub fn pool(&self) -> &Pool { self.ledger.pool() }
+    pub fn store(&self) -> &S { &self.store }
+    /// Erro do último snapshot periódico, se ele falhou.
+    pub fn snapshot_error(&self) -> Option<&anyhow::Error> { self.snapshot_error.as_ref() }
+
+    /// Executa `op`, persiste o evento e só então aplica em memória.
+    /// `Err` significa que a operação **não** foi aplicada nem gravada. Depois do append a operação
+    /// está valendo: se o snapshot periódico falhar, o erro vai para o log e para `snapshot_error()`
+    /// (a recuperação usa um snapshot anterior e reexecuta mais WAL).
+    pub fn apply(&mut self, op: PoolOp) -> Result<OpOutcome> {
+        let (pool, event) = self.ledger.prepare(op)?;
+        self.store.append(&event)?;
+        self.ledger.commit(pool, event);
+        if self.snapshot_every > 0 && event.seq % self.snapshot_every == 0 {
+            self.snapshot_error = self.store.write_snapshot(event.seq, &self.ledger.pool().state()).err();
+            if let Some(e) = &self.snapshot_error {
+                tracing::warn!(seq = event.seq, error = %e, "snapshot não gravado; evento já está no WAL");
+            }
+        }
+        Ok(event.outcome)
+    }
+}
+
+// -------------------------
+// TESTES
+// -------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::errors::AmmError;
+    use crate::amm::types::{Direction, MIN_RESERVE, WAD};
+
+    fn ops() -> Vec<PoolOp> {
+        vec![
+            PoolOp::SetProtocolFee { protocol_fee_ppm: 200_000 },
+            PoolOp::SwapExactIn { direction: Direction::XtoY, amount_in: 1_000 * WAD, min_out: 0 },
+            PoolOp::AddLiquidity { amount_x: 10_000 * WAD, amount_y: 10_000 * WAD },
+            PoolOp::SwapExactOut { direction: Direction::YtoX, amount_out: 500 * WAD, max_in: Wad::MAX },
+            PoolOp::CollectProtocolFees,
+            PoolOp::RemoveLiquidity { shares: 5_000 * WAD },
+        ]
+    }
+
+    #[test]
+    fn t_open_recovers_same_state() {
+        let mut p = PersistentPool::create(MemoryStore::new(), 100_000 * WAD, 100_000 * WAD, 3_000, MIN_RESERVE, 2).unwrap();
+        for op in ops() { p.apply(op).unwrap(); }
+        assert_eq!(p.store().log.snapshots.iter().map(|s| s.0).collect::<Vec<_>>(), vec![6, 4, 2]);
+        let reopened = PersistentPool::open(p.store.clone(), 2).unwrap();
+        assert_eq!(reopened.ledger(), p.ledger());
+    }
+
+    /// Store cujo snapshot sempre falha.
+    #[derive(Debug, Default)]
+    struct NoSnapshots(MemoryStore);
+
+    impl StateStore for NoSnapshots {
+        fn append(&mut self, event: &PoolEvent) -> Result<()> { self.0.append(event) }
+        fn write_snapshot(&mut self, _seq: u64, _state: &PoolState) -> Result<()> { bail!("disco cheio") }
+        fn load(&mut self) -> Result<StoredLog> { self.0.load() }
+    }
+
+    #[test]
+    fn t_snapshot_failure_does_not_fail_applied_op() {
+        let mut p = PersistentPool::create(NoSnapshots::default(), 100_000 * WAD, 100_000 * WAD, 3_000, MIN_RESERVE, 2).unwrap();
+        let seqs: Vec<u64> = ops().into_iter().map(|op| {
+            p.apply(op).unwrap();
+            p.ledger().events().last().unwrap().seq
+        }).collect();
+        assert_eq!(seqs, (1..=6).collect::<Vec<u64>>());
+        assert!(p.snapshot_error().is_some());
+        assert_eq!(p.store().0.log.events.len(), 7);
+        let reopened = PersistentPool::open(p.store().0.clone(), 2).unwrap();
+        assert_eq!(reopened.ledger(), p.ledger());
+        assert!(reopened.snapshot_error().is_none());
+    }
+
+    #[test]
+    fn t_failed_op_not_persisted_and_bad_snapshot_skipped() {
+        let mut p = PersistentPool::create(MemoryStore::new(), 100_000 * WAD, 100_000 * WAD, 3_000, MIN_RESERVE, 0).unwrap();
+        for op in ops() { p.apply(op).unwrap(); }
+        let err = p.apply(PoolOp::SetFee { fee_ppm: 1_000_000 }).unwrap_err();
+        assert_eq!(err.downcast_ref::<AmmError>(), Some(&AmmError::FeeTooHigh { fee_ppm: 1_000_000 }));
+        assert_eq!(p.store().log.events.len(), 7);
+        assert!(p.store().log.snapshots.is_empty());
+        // snapshot que não bate com o WAL: recuperação volta para a gênese
+        let mut store = p.store.clone();
+        let mut wrong = p.pool().state();
+        wrong.total_shares += 1;
+        store.write_snapshot(5, &wrong).unwrap();
+        assert_eq!(PersistentPool::open(store, 0).unwrap().ledger(), p.ledger());
+        assert!(PersistentPool::create(p.store.clone(), WAD, WAD, 0, MIN_RESERVE, 0).is_err());
+        assert!(PersistentPool::open(MemoryStore::new(), 0).is_err());
+    }
+}
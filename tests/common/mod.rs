@@ -0,0 +1,21 @@
+//! Fixtures compartilhadas pelos testes de integração (`mod common;`).
+use proptest::prelude::*;
+use credit_engine_core::amm::pool::Pool;
+use credit_engine_core::amm::router::{Router, TokenId};
+use credit_engine_core::amm::types::{Wad, WAD};
+
+
+/// Até 9 pools `(token_a, token_b, x, y, fee_ppm)` entre 5 tokens (pares repetidos e laços incluídos).
+pub fn graph() -> impl Strategy<Value = Vec<(TokenId, TokenId, Wad, Wad, u32)>> {
+prop::collection::vec((0u32..5, 0u32..5, 1_000u128..=10_000_000u128, 1_000u128..=10_000_000u128, 0u32..=10_000u32), 1..10)
+}
+
+
+/// Router com os pools de `graph()` (laços `a == b` são pulados).
+pub fn build(pools: &[(TokenId, TokenId, Wad, Wad, u32)]) -> Router {
+let mut r = Router::new();
+for &(a, b, x, y, fee) in pools {
+if a != b { r.add_pool(a, b, Pool::new(x * WAD, y * WAD, fee).unwrap()).unwrap(); }
+}
+r
+}
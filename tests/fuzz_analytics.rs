@@ -0,0 +1,50 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::analytics::{impermanent_loss_wad, pool_pnl, position_pnl, PoolSnapshot};
+use credit_engine_core::amm::pool::Pool;
+use credit_engine_core::amm::types::{Direction, WAD};
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 300, .. ProptestConfig::default() })]
+
+
+#[test]
+fn pnl_decomposes_into_fees_and_il(
+x in 1_000u128..=1_000_000u128,
+y in 1_000u128..=1_000_000u128,
+fee_ppm in 0u32..=10_000,
+swaps in prop::collection::vec((any::<bool>(), 1u128..=100_000u128), 1..20),
+part in 1u128..=100,
+dt in 0u64..=100_000_000,
+) {
+let mut pool = Pool::new(x * WAD, y * WAD, fee_ppm).unwrap();
+let entry = PoolSnapshot::of(&pool, 1_000);
+for (xy, v) in swaps {
+let _ = pool.swap_exact_in(if xy { Direction::XtoY } else { Direction::YtoX }, v * WAD / 10, 0);
+}
+let now = PoolSnapshot::of(&pool, 1_000 + dt);
+let shares = entry.total_shares * part / 100;
+let r = position_pnl(&entry, &now, shares).unwrap();
+// (A1) IL ≤ 0
+prop_assert!(r.impermanent_loss <= 0 && r.impermanent_loss_wad <= 0);
+// (A2) LP − hold = taxa + IL, a menos do floor
+let lhs = r.lp_value as i128 - r.hold_value as i128;
+let rhs = r.fee_income as i128 + r.impermanent_loss;
+prop_assert!((lhs - rhs).abs() <= 4, "{} vs {}", lhs, rhs);
+// (A3) sem taxa, sem receita
+if fee_ppm == 0 { prop_assert!(r.fee_income <= 2); }
+// (A4) sem tempo, sem APR
+if dt == 0 { prop_assert_eq!((r.fee_apr_wad, r.net_apr_wad), (0, 0)); }
+// (A5) pool inteiro ≥ qualquer fatia
+prop_assert!(pool_pnl(&entry, &now).unwrap().fee_income + 2 >= r.fee_income);
+}
+
+
+#[test]
+fn il_formula_is_symmetric_and_bounded(r in 1u128..=1_000_000u128) {
+let a = impermanent_loss_wad(r * WAD / 1_000).unwrap();
+let b = impermanent_loss_wad(WAD * 1_000 / r).unwrap();
+prop_assert!((-(WAD as i128)..=0).contains(&a));
+prop_assert!((a - b).abs() <= 1_000_000, "{} vs {}", a, b);
+}
+}
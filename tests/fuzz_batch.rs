@@ -0,0 +1,69 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::batch::{clear_batch, SwapIntent};
+use credit_engine_core::amm::pool::Pool;
+use credit_engine_core::amm::types::{Direction, Reserves, Wad, WAD};
+
+
+fn intents() -> impl Strategy<Value = Vec<SwapIntent>> {
+prop::collection::vec((any::<bool>(), 1u128..=50_000u128 * WAD, 0u128..=60_000u128 * WAD), 0..8).prop_map(|v| {
+v.into_iter().map(|(x_to_y, amount_in, min_out)| SwapIntent {
+direction: if x_to_y { Direction::XtoY } else { Direction::YtoX },
+amount_in,
+// metade das ordens sem limite
+min_out: if amount_in % 2 == 0 { 0 } else { min_out },
+}).collect()
+})
+}
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 300, .. ProptestConfig::default() })]
+
+
+#[test]
+fn batch_conserves_respects_limits_and_is_order_independent(
+x in 100_000u128..=10_000_000u128,
+y in 100_000u128..=10_000_000u128,
+fee in 0u32..=10_000u32,
+batch in intents(),
+) {
+let pool = Pool::new(x * WAD, y * WAD, fee).unwrap();
+let Ok(c) = clear_batch(pool.reserves(), fee, pool.min_reserve(), &batch) else { return Ok(()); };
+let cert = c.certificate;
+
+
+// (B1) fills respeitam min_out; excluídas não trocam nada
+for (i, f) in batch.iter().zip(&c.fills) {
+prop_assert_eq!(f.direction, i.direction);
+if f.is_filled() {
+prop_assert_eq!(f.amount_in, i.amount_in);
+prop_assert!(f.amount_out >= i.min_out.max(1));
+} else {
+prop_assert_eq!(f.amount_out, 0);
+}
+}
+// (B2) conservação exata: entradas + perna do pool = saídas + dust
+let sum = |dir: Direction, out: bool| c.fills.iter().filter(|f| f.direction == dir).map(|f| if out { f.amount_out } else { f.amount_in }).sum::<Wad>();
+prop_assert_eq!(sum(Direction::XtoY, false), cert.total_in_x);
+prop_assert_eq!(sum(Direction::YtoX, false), cert.total_in_y);
+let (pool_x, pool_y) = match cert.net_direction {
+Direction::XtoY => (-(cert.pool_in as i128), cert.pool_out as i128),
+Direction::YtoX => (cert.pool_out as i128, -(cert.pool_in as i128)),
+};
+prop_assert_eq!(cert.total_in_x as i128 + pool_x, (sum(Direction::YtoX, true) + cert.dust_x) as i128);
+prop_assert_eq!(cert.total_in_y as i128 + pool_y, (sum(Direction::XtoY, true) + cert.dust_y) as i128);
+// (B3) a perna é exatamente um swap do pool
+if cert.pool_in > 0 {
+prop_assert_eq!(pool.clone().swap_exact_in(cert.net_direction, cert.pool_in, 0).unwrap().amount_out, cert.pool_out);
+}
+// (B4) a ordem de chegada não muda o preço nem os fills
+let mut rev = batch.clone();
+rev.reverse();
+let r = clear_batch(pool.reserves(), fee, pool.min_reserve(), &rev).unwrap();
+prop_assert_eq!(r.certificate, cert);
+prop_assert!(r.fills.iter().rev().eq(c.fills.iter()));
+// (B5) o certificado se verifica offline
+prop_assert!(cert.verify(&batch, &c.fills));
+prop_assert_eq!(cert.reserves, Reserves::new(x * WAD, y * WAD));
+}
+}
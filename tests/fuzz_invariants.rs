@@ -47,3 +47,66 @@ prop_assert!(k1 >= k0, "k' < k with fee: k0={}, k1={}, fee_ppm={}", k0, k1, fee_
 }
 }
 }
+
+
+use credit_engine_core::amm::pool::Pool;
+use credit_engine_core::amm::fees::ProtocolFees;
+use credit_engine_core::amm::types::Direction;
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 1_000, .. ProptestConfig::default() })]
+
+
+#[test]
+fn protocol_fee_accumulator_invariants(
+protocol_ppm in 0u32..=1_000_000u32,
+fee_ppm in 0u32..=10_000u32,
+swaps in proptest::collection::vec((any::<bool>(), 1u128..=50_000u128), 1..20),
+) {
+let (x0, y0) = (to_wad(1_000_000), to_wad(1_000_000));
+let mut pool = Pool::new(x0, y0, fee_ppm as Ppm).expect("pool ok");
+pool.set_protocol_fee_ppm(protocol_ppm).expect("protocol fee ok");
+
+
+// fluxos líquidos por ativo (entradas - saídas) e taxas do protocolo somadas por recibo
+let (mut net_x, mut net_y) = (0i128, 0i128);
+let mut expected = ProtocolFees::default();
+for (x_to_y, amount) in swaps {
+let dir = if x_to_y { Direction::XtoY } else { Direction::YtoX };
+let k0 = pool.k();
+let r = match pool.swap_exact_in(dir, to_wad(amount), 0) { Ok(r) => r, Err(_) => continue };
+
+
+// (F1) split fecha com a taxa total; protocolo = floor
+prop_assert_eq!(r.lp_fee + r.protocol_fee, r.fee);
+prop_assert!(U256::from(r.protocol_fee) * U256::from(1_000_000u64) <= U256::from(r.fee) * U256::from(protocol_ppm as u64));
+// (F2) a parte do protocolo sai das reservas e mesmo assim k' ≥ k enquanto sobra taxa
+// para os LPs; com lp_fee = 0 vale a conservação de P1: y* é nearest-even (ADR-0002),
+// então k pode cair até ½ wei vezes a reserva de entrada
+if r.lp_fee > 0 {
+prop_assert!(pool.k() >= k0, "k' < k com lp_fee>0: k0={}, k1={}, lp_fee={}", k0, pool.k(), r.lp_fee);
+} else {
+let slack = U256::from(pool.reserves().x.max(pool.reserves().y)) / U256::from(2u8);
+prop_assert!(pool.k() + slack >= k0, "k caiu: k0={}, k1={}", k0, pool.k());
+}
+
+
+match dir {
+Direction::XtoY => { net_x += r.amount_in as i128; net_y -= r.amount_out as i128; expected.x += r.protocol_fee; }
+Direction::YtoX => { net_y += r.amount_in as i128; net_x -= r.amount_out as i128; expected.y += r.protocol_fee; }
+}
+}
+
+
+// (F3) acumulador == soma dos recibos
+prop_assert_eq!(pool.protocol_fees(), expected);
+// (F4) conservação: reservas + resgatável == inicial + fluxo líquido
+let (rx, ry) = (pool.reserves().x as i128, pool.reserves().y as i128);
+prop_assert_eq!(rx + expected.x as i128, x0 as i128 + net_x);
+prop_assert_eq!(ry + expected.y as i128, y0 as i128 + net_y);
+// (F5) collect entrega tudo e zera
+prop_assert_eq!(pool.collect_protocol_fees(), (expected.x, expected.y));
+prop_assert_eq!(pool.protocol_fees(), ProtocolFees::default());
+}
+}
@@ -0,0 +1,85 @@
+use proptest::prelude::*;
+use credit_engine_core::credit::{CreditMarket, CreditParams, KinkedRate, PriceSource};
+use credit_engine_core::amm::types::{AccountId, Direction, Reserves, Wad, WAD};
+
+
+#[derive(Clone, Debug)]
+enum Op {
+Supply(AccountId, Wad),
+WithdrawSupply(AccountId, Wad),
+Deposit(AccountId, Wad),
+Withdraw(AccountId, Wad),
+Borrow(AccountId, Wad),
+Repay(AccountId, Wad),
+Wait(u64),
+}
+
+
+fn op() -> impl Strategy<Value = Op> {
+let acc = 0u64..3;
+let amt = 1u128..=1_000u128 * WAD;
+prop_oneof![
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::Supply(a, v)),
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::WithdrawSupply(a, v)),
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::Deposit(a, v)),
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::Withdraw(a, v)),
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::Borrow(a, v)),
+(acc, amt).prop_map(|(a, v)| Op::Repay(a, v)),
+(0u64..=30 * 86_400).prop_map(Op::Wait),
+]
+}
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 300, .. ProptestConfig::default() })]
+
+
+#[test]
+fn lines_stay_within_ltv_and_market_stays_solvent(
+x in 1_000u128..=1_000_000u128,
+y in 1_000u128..=1_000_000u128,
+haircut_ppm in 0u32..=500_000,
+ltv in 100_000u32..=900_000,
+base in 0u128..=WAD / 10,
+ops in prop::collection::vec(op(), 0..40),
+) {
+let params = CreditParams { collateral: Direction::XtoY, haircut_ppm, max_ltv_ppm: ltv, liquidation_threshold_ppm: ltv + 50_000 };
+let model = KinkedRate::new(base, WAD / 10, 2 * WAD, 8 * WAD / 10).unwrap();
+let price = PriceSource::Spot(Reserves::new(x * WAD, y * WAD));
+let mut m = CreditMarket::new(params, model, 0).unwrap();
+let mut now = 0u64;
+for op in ops {
+let before = m.clone();
+let res = match op {
+Op::Supply(a, v) => m.supply(a, v, now).map(|_| a),
+Op::WithdrawSupply(a, v) => m.withdraw_supply(a, v, now).map(|_| a),
+Op::Deposit(a, v) => m.deposit_collateral(a, v, now).map(|_| a),
+Op::Withdraw(a, v) => m.withdraw_collateral(a, v, &price, now).map(|_| a),
+Op::Borrow(a, v) => m.borrow(a, v, &price, now).map(|_| a),
+Op::Repay(a, v) => {
+let debt = m.position(a, &price, now).unwrap().debt;
+let paid = m.repay(a, v, now);
+if let Ok(p) = paid { prop_assert_eq!(p, v.min(debt)); }
+paid.map(|_| a)
+}
+Op::Wait(dt) => { now += dt; Ok(0) }
+};
+match res {
+// (L1) erro não muda nada
+Err(_) => prop_assert_eq!(&m, &before),
+// (L2) saque e empréstimo deixam a linha dentro do LTV
+Ok(a) => if matches!(op, Op::Withdraw(..) | Op::Borrow(..)) {
+let pos = m.position(a, &price, now).unwrap();
+prop_assert!(pos.debt <= pos.max_debt);
+prop_assert!(pos.health_factor >= WAD);
+},
+}
+// (L3) totais = soma das linhas
+let (mut c, mut d, mut s) = (0u128, 0u128, 0u128);
+for (_, l) in m.lines() { c += l.collateral; d += l.scaled_debt; s += l.scaled_supply; }
+prop_assert_eq!((c, d, s), (m.total_collateral(), m.total_scaled_debt(), m.total_scaled_supply()));
+// (L4) solvência: oferta ≤ caixa + dívida
+prop_assert!(m.total_supply().unwrap() <= m.cash() + m.total_debt().unwrap());
+}
+}
+}
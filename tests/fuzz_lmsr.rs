@@ -0,0 +1,49 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::errors::AmmError;
+use credit_engine_core::amm::lmsr::Lmsr;
+use credit_engine_core::amm::types::{Wad, WAD};
+
+
+fn trade() -> impl Strategy<Value = (usize, Wad, bool)> {
+(0usize..5, 1u128..=500u128 * WAD, any::<bool>())
+}
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 500, .. ProptestConfig::default() })]
+
+
+#[test]
+fn lmsr_invariants_hold_over_trade_sequences(
+b_units in 1u128..=10_000u128,
+outcomes in 2usize..=5,
+trades in prop::collection::vec(trade(), 1..30),
+) {
+let mut m = Lmsr::new(b_units * WAD, outcomes).expect("mercado ok");
+let bound = m.worst_case_loss().expect("loss ok");
+for (o, shares, is_buy) in trades {
+let o = o % outcomes;
+if is_buy { let _ = m.buy(o, shares, u128::MAX); } else { let _ = m.sell(o, shares.min(m.quantities()[o]), 0); }
+
+
+// (L1) preços somam exatamente 1 e ficam em [0, 1]
+let p = m.prices().expect("preços ok");
+prop_assert_eq!(p.iter().sum::<Wad>(), WAD);
+// (L2) perda no pior outcome nunca passa de b·ln N
+prop_assert!(m.exposure() <= bound, "exposure={} bound={}", m.exposure(), bound);
+}
+
+
+// (L3) desfazer todas as posições nunca exige mais colateral do que o maker recebeu
+let paid = m.collected();
+for o in 0..outcomes {
+let q = m.quantities()[o];
+if q == 0 { continue; }
+match m.sell(o, q, 0) {
+Ok(_) | Err(AmmError::InputTooSmall) => {}
+Err(e) => prop_assert!(false, "unwind falhou: {:?}", e),
+}
+}
+prop_assert!(m.collected() <= paid);
+}
+}
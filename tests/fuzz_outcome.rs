@@ -0,0 +1,60 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::outcome::{Outcome, OutcomeMarket};
+use credit_engine_core::amm::types::{Wad, WAD};
+
+
+#[derive(Clone, Debug)]
+enum Op { Buy(bool, Wad), Sell(bool, Wad), Mint(Wad), Burn(Wad) }
+
+
+fn op() -> impl Strategy<Value = Op> {
+let amt = 1u128..=5_000u128 * WAD;
+prop_oneof![
+(any::<bool>(), amt.clone()).prop_map(|(y, a)| Op::Buy(y, a)),
+(any::<bool>(), amt.clone()).prop_map(|(y, a)| Op::Sell(y, a)),
+amt.clone().prop_map(Op::Mint),
+amt.prop_map(Op::Burn),
+]
+}
+
+
+#[inline]
+fn side(yes: bool) -> Outcome { if yes { Outcome::Yes } else { Outcome::No } }
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 1_000, .. ProptestConfig::default() })]
+
+
+#[test]
+fn outcome_market_collateral_covers_winners(
+funding_base in 100u128..=1_000_000u128,
+fee_ppm in 0u32..=30_000u32,
+ops in prop::collection::vec(op(), 1..40),
+yes_wins in any::<bool>(),
+) {
+let mut m = OutcomeMarket::new(funding_base * WAD, fee_ppm).expect("mercado ok");
+for op in ops {
+// erros (slippage, liquidez, saldo) são permitidos; estado deve continuar consistente
+let _ = match op {
+Op::Buy(y, a) => m.buy(side(y), a, 0).map(|_| ()),
+Op::Sell(y, a) => m.sell(side(y), a, u128::MAX).map(|_| ()),
+Op::Mint(a) => m.mint_sets(a),
+Op::Burn(a) => m.burn_sets(a),
+};
+// (O1) conservação por outcome: em mãos + pool = colateral
+prop_assert!(m.is_balanced(), "desbalanceado: {:?}", m);
+// (O2) preços somam 1
+prop_assert_eq!(m.price(Outcome::Yes).unwrap() + m.price(Outcome::No).unwrap(), WAD);
+}
+
+
+// (O3) após resolver, todo vencedor em mãos de traders é resgatável e o resto cobre o pool
+let winner = side(yes_wins);
+m.resolve(winner).expect("resolve ok");
+let held = m.held(winner);
+if held > 0 { prop_assert_eq!(m.redeem(held).expect("redeem ok"), held); }
+let r = m.pool().reserves();
+prop_assert_eq!(m.collateral(), if yes_wins { r.x } else { r.y });
+}
+}
@@ -0,0 +1,56 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::pool::Pool;
+use credit_engine_core::amm::positions::LpBook;
+use credit_engine_core::amm::types::{AccountId, Direction, Wad, WAD};
+
+
+#[derive(Clone, Debug)]
+enum Op { Add(AccountId, Wad, Wad), Remove(AccountId, Wad), Transfer(AccountId, AccountId, Wad), Swap(bool, Wad) }
+
+
+fn op() -> impl Strategy<Value = Op> {
+let acc = 0u64..4;
+let amt = 1u128..=10_000u128 * WAD;
+prop_oneof![
+(acc.clone(), amt.clone(), amt.clone()).prop_map(|(a, x, y)| Op::Add(a, x, y)),
+(acc.clone(), amt.clone()).prop_map(|(a, s)| Op::Remove(a, s)),
+(acc.clone(), acc, amt.clone()).prop_map(|(f, t, s)| Op::Transfer(f, t, s)),
+(any::<bool>(), amt).prop_map(|(d, v)| Op::Swap(d, v)),
+]
+}
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 300, .. ProptestConfig::default() })]
+
+
+#[test]
+fn balances_always_sum_to_supply(
+x in 1_000u128..=1_000_000u128,
+y in 1_000u128..=1_000_000u128,
+ops in prop::collection::vec(op(), 0..40),
+) {
+let mut b = LpBook::new(0, Pool::new(x * WAD, y * WAD, 3_000).unwrap());
+for op in ops {
+let before = b.clone();
+let res = match op {
+Op::Add(a, dx, dy) => b.add_liquidity(a, dx, dy).map(|_| ()),
+Op::Remove(a, s) => b.remove_liquidity(a, s).map(|_| ()),
+Op::Transfer(f, t, s) => b.transfer(f, t, s),
+Op::Swap(xy, v) => b.swap_exact_in(if xy { Direction::XtoY } else { Direction::YtoX }, v, 0).map(|_| ()),
+};
+if res.is_err() { prop_assert_eq!(&b, &before); }
+// (P1) soma dos saldos = supply
+prop_assert!(b.check_supply().is_ok());
+// (P2) valores floor: a soma nunca passa das reservas
+let r = b.pool().reserves();
+let (mut vx, mut vy) = (0u128, 0u128);
+for (o, _) in b.owners() {
+let v = b.value_of(o).unwrap();
+vx += v.amount_x;
+vy += v.amount_y;
+}
+prop_assert!(vx <= r.x && vy <= r.y);
+}
+}
+}
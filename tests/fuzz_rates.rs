@@ -0,0 +1,73 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::types::{Direction, Wad, SECONDS_PER_YEAR as YEAR, WAD};
+use credit_engine_core::credit::rates::grow_index;
+use credit_engine_core::credit::{AdaptiveRate, CreditMarket, CreditParams, FixedRate, InterestRateModel, KinkedRate, PriceSource};
+
+
+/// Índice depois de `total` segundos em um accrual e depois dividido em `cuts`.
+fn split_vs_single<M: InterestRateModel + Clone>(model: &M, u: Wad, total: u64, cuts: &[u64]) -> (Wad, Wad) {
+let mut single = model.clone();
+let one = grow_index(WAD, single.accrue(u, total).unwrap()).unwrap();
+let mut points: Vec<u64> = cuts.iter().map(|c| c % (total + 1)).collect();
+points.push(total);
+points.sort();
+let (mut m, mut idx, mut last) = (model.clone(), WAD, 0u64);
+for p in points {
+idx = grow_index(idx, m.accrue(u, p - last).unwrap()).unwrap();
+last = p;
+}
+(one, idx)
+}
+
+
+/// |a − b| ≤ a·1e-15 + `abs` wei.
+fn close(a: Wad, b: Wad, abs: u128) -> bool { a.abs_diff(b) <= a / 1_000_000_000_000_000 + abs }
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 300, .. ProptestConfig::default() })]
+
+
+#[test]
+fn accrual_is_path_independent(
+rate in 0u128..=5 * WAD,
+u in 0u128..=WAD,
+total in 1u64..=2 * YEAR,
+cuts in prop::collection::vec(any::<u64>(), 0..30),
+) {
+// (R1) taxa constante: só o ceil de cada passo (1 wei por accrual)
+let (a, b) = split_vs_single(&FixedRate(rate), u, total, &cuts);
+prop_assert!(close(a, b, cuts.len() as u128 + 1), "{} vs {}", a, b);
+let kinked = KinkedRate::new(rate / 10, rate / 2, rate, 8 * WAD / 10).unwrap();
+let (a, b) = split_vs_single(&kinked, u, total, &cuts);
+prop_assert!(close(a, b, cuts.len() as u128 + 1), "{} vs {}", a, b);
+// (R2) adaptativa: forma fechada, inclusive quando bate nos limites
+let adaptive = AdaptiveRate::new(4 * WAD / 100, 9 * WAD / 10, 50 * WAD, WAD / 100, 2 * WAD).unwrap();
+let (a, b) = split_vs_single(&adaptive, u, total, &cuts);
+prop_assert!(close(a, b, cuts.len() as u128 + 1), "{} vs {}", a, b);
+}
+
+
+#[test]
+fn market_debt_is_path_independent(
+rate in 0u128..=WAD,
+borrow in 1u128..=1_000u128,
+total in 1u64..=YEAR,
+cuts in prop::collection::vec(0u64..=YEAR, 0..20),
+) {
+let params = CreditParams { collateral: Direction::XtoY, haircut_ppm: 0, max_ltv_ppm: 1_000_000, liquidation_threshold_ppm: 1_000_000 };
+let price = PriceSource::Averaged(WAD);
+let mut m = CreditMarket::new(params, FixedRate(rate), 0).unwrap();
+m.supply(1, 10_000 * WAD, 0).unwrap();
+m.deposit_collateral(2, 10_000 * WAD, 0).unwrap();
+m.borrow(2, borrow * WAD, &price, 0).unwrap();
+let single = m.position(2, &price, total).unwrap().debt;
+let mut points: Vec<u64> = cuts.iter().map(|c| c % (total + 1)).collect();
+points.sort();
+for p in points { m.accrue(p).unwrap(); }
+m.accrue(total).unwrap();
+// (R3) dívida e índice não dependem da divisão em accruals, a menos do arredondamento
+let split = m.debt_of(2).unwrap();
+prop_assert!(close(single, split, borrow * (cuts.len() as u128 + 2)), "{} vs {}", single, split);
+}
+}
@@ -0,0 +1,49 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::types::{Direction, WAD};
+
+mod common;
+use common::{build, graph};
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 500, .. ProptestConfig::default() })]
+
+
+#[test]
+fn router_best_route_dominates_and_executes_as_quoted(
+pools in graph(),
+from in 0u32..5,
+to in 0u32..5,
+amount in 1u128..=100_000u128 * WAD,
+) {
+let mut r = build(&pools);
+let Ok(q) = r.quote_exact_in(from, to, amount, 3) else { return Ok(()); };
+
+
+// (R1) a melhor rota nunca entrega menos que o pool direto
+if let Some(p) = r.pool(from, to) {
+let dir = if r.pools().any(|(k, _)| k == (from, to)) { Direction::XtoY } else { Direction::YtoX };
+if let Ok(direct) = p.clone().swap_exact_in(dir, amount, 0) { prop_assert!(q.amount_out >= direct.amount_out); }
+}
+// (R2) hops encadeados: o output de um é o input do próximo
+for w in q.hops.windows(2) { prop_assert_eq!(w[0].amount_out, w[1].amount_in); }
+// (R3) executar devolve exatamente a cotação
+prop_assert_eq!(r.swap_exact_in(from, to, amount, q.amount_out, 3).expect("execução ok"), q);
+}
+
+
+#[test]
+fn router_exact_out_input_suffices(
+pools in graph(),
+from in 0u32..5,
+to in 0u32..5,
+amount in 1u128..=10_000u128 * WAD,
+) {
+let r = build(&pools);
+let Ok(q) = r.quote_exact_out(from, to, amount, 3) else { return Ok(()); };
+prop_assert_eq!(q.amount_out, amount);
+// (R4) vender o input cotado exact-in alcança o output pedido
+let back = r.quote_exact_in(from, to, q.amount_in, 3).expect("rota exact-in");
+prop_assert!(back.amount_out >= amount);
+}
+}
@@ -0,0 +1,80 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::outcome::Outcome;
+use credit_engine_core::amm::settlement::{ManualResolution, MarketLifecycle, Payout, Resolution};
+use credit_engine_core::amm::types::{AccountId, Wad, WAD};
+
+
+#[derive(Clone, Debug)]
+enum Op { Buy(AccountId, bool, Wad), Sell(AccountId, bool, Wad), Mint(AccountId, Wad), Burn(AccountId, Wad), Fund(AccountId, Wad) }
+
+
+fn op() -> impl Strategy<Value = Op> {
+let acc = 0u64..4;
+let amt = 1u128..=5_000u128 * WAD;
+prop_oneof![
+(acc.clone(), any::<bool>(), amt.clone()).prop_map(|(a, y, v)| Op::Buy(a, y, v)),
+(acc.clone(), any::<bool>(), amt.clone()).prop_map(|(a, y, v)| Op::Sell(a, y, v)),
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::Mint(a, v)),
+(acc.clone(), amt.clone()).prop_map(|(a, v)| Op::Burn(a, v)),
+(acc, amt).prop_map(|(a, v)| Op::Fund(a, v)),
+]
+}
+
+
+fn resolution() -> impl Strategy<Value = Resolution> {
+prop_oneof![
+Just(Resolution::Winner(Outcome::Yes)),
+Just(Resolution::Winner(Outcome::No)),
+Just(Resolution::Invalid),
+(0u128..=WAD).prop_map(|y| Resolution::Split(Payout::new(y, WAD - y).unwrap())),
+]
+}
+
+
+#[inline]
+fn side(yes: bool) -> Outcome { if yes { Outcome::Yes } else { Outcome::No } }
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 1_000, .. ProptestConfig::default() })]
+
+
+#[test]
+fn settlement_pays_out_exactly_the_collateral(
+funding_base in 100u128..=1_000_000u128,
+fee_ppm in 0u32..=30_000u32,
+ops in prop::collection::vec(op(), 1..30),
+res in resolution(),
+) {
+let mut m = MarketLifecycle::open(0, funding_base * WAD, fee_ppm).expect("open ok");
+for op in ops {
+let _ = match op {
+Op::Buy(a, y, v) => m.buy(a, side(y), v, 0).map(|_| ()),
+Op::Sell(a, y, v) => m.sell(a, side(y), v, u128::MAX).map(|_| ()),
+Op::Mint(a, v) => m.mint_sets(a, v),
+Op::Burn(a, v) => m.burn_sets(a, v),
+Op::Fund(a, v) => m.add_funding(a, v).map(|_| ()),
+};
+prop_assert!(m.market().is_balanced());
+}
+
+
+let mut src = ManualResolution::new();
+src.set(res).unwrap();
+m.halt().unwrap();
+m.resolve(&src).unwrap();
+let total = m.collateral_left();
+prop_assert_eq!(total, m.market().collateral());
+
+
+// (S1) holders e LPs recebem, somados, exatamente o colateral (o último LP absorve a poeira)
+let mut paid = 0u128;
+for a in 0..4 {
+if m.position(a) != Default::default() { paid += m.redeem(a).expect("redeem ok"); }
+if m.lp_shares(a) > 0 { paid += m.withdraw_lp(a).expect("withdraw ok"); }
+}
+prop_assert_eq!(paid, total);
+// (S2) com tudo pago, o settlement fecha sem poeira
+prop_assert_eq!(m.settle().expect("settle ok"), 0);
+}
+}
@@ -0,0 +1,39 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::router::TokenId;
+use credit_engine_core::amm::types::WAD;
+
+mod common;
+use common::{build, graph};
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 200, .. ProptestConfig::default() })]
+
+
+#[test]
+fn split_dominates_single_route_and_executes_as_quoted(
+pools in graph(),
+from in 0u32..5,
+to in 0u32..5,
+amount in 1u128..=1_000_000u128 * WAD,
+) {
+let mut r = build(&pools);
+let Ok(plan) = r.quote_split_exact_in(from, to, amount, 3, 4) else { return Ok(()); };
+
+
+// (S1) nunca pior que a melhor rota única
+prop_assert!(plan.amount_out >= plan.best_single.amount_out);
+prop_assert_eq!(plan.improvement, plan.amount_out - plan.best_single.amount_out);
+// (S2) alocações inteiras que somam o input; output = soma dos outputs das pernas
+prop_assert_eq!(plan.legs.iter().map(|l| l.amount_in).sum::<u128>(), amount);
+prop_assert_eq!(plan.legs.iter().map(|l| l.amount_out).sum::<u128>(), plan.amount_out);
+// (S3) pernas sem pool em comum
+let mut pairs: Vec<(TokenId, TokenId)> = plan.legs.iter().flat_map(|l| l.hops.iter().map(|h| (h.token_in.min(h.token_out), h.token_in.max(h.token_out)))).collect();
+let n = pairs.len();
+pairs.sort_unstable();
+pairs.dedup();
+prop_assert_eq!(pairs.len(), n);
+// (S4) executar devolve exatamente o plano
+prop_assert_eq!(r.swap_split_exact_in(from, to, amount, plan.amount_out, 3, 4).expect("execução ok"), plan);
+}
+}
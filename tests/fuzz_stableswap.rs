@@ -0,0 +1,42 @@
+use proptest::prelude::*;
+use credit_engine_core::amm::stableswap::{get_amount_in, get_amount_out, get_d, Amp};
+use credit_engine_core::amm::types::{Wad, WAD, Ppm};
+
+
+#[inline]
+fn to_wad(v: u128) -> Wad { v * WAD }
+
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 2_000, .. ProptestConfig::default() })]
+
+
+#[test]
+fn stableswap_d_never_decreases(
+rx_base in 1_000u128..=1_000_000_000u128,
+ry_base in 1_000u128..=1_000_000_000u128,
+dx_wei in 1u128..=1_000_000u128 * WAD,
+fee_ppm in 0u32..=3000u32,
+amp in 1u64..=5_000u64,
+x_to_y in any::<bool>(),
+) {
+let (rx, ry) = (to_wad(rx_base), to_wad(ry_base));
+let (r_in, r_out) = if x_to_y { (rx, ry) } else { (ry, rx) };
+let d0 = get_d(r_in, r_out, amp as Amp).expect("D ok");
+
+
+if let Ok(dy) = get_amount_out(r_in, r_out, dx_wei, fee_ppm as Ppm, amp) {
+// (S1) sanidade: 0 < dy < reserva de saída
+prop_assert!(dy > 0 && dy < r_out, "dy fora da faixa: dy={}, r_out={}", dy, r_out);
+// (S2) D' ≥ D após o swap (taxa e wei de segurança ficam no pool)
+let d1 = get_d(r_in + dx_wei, r_out - dy, amp).expect("D' ok");
+prop_assert!(d1 >= d0, "D caiu: d0={}, d1={}, dx={}, dy={}", d0, d1, dx_wei, dy);
+
+
+// (S3) amount_in é mínimo e nunca maior que o dx usado
+let dx_min = get_amount_in(r_in, r_out, dy, fee_ppm as Ppm, amp).expect("in ok");
+prop_assert!(dx_min <= dx_wei);
+prop_assert!(get_amount_out(r_in, r_out, dx_min, fee_ppm as Ppm, amp).expect("out ok") >= dy);
+}
+}
+}
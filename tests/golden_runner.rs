@@ -0,0 +1,61 @@
+//! Executa todas as linhas dos goldens versionados (com checagem de sha256) e garante que
+//! o v2 commitado é exatamente o que o gerador produz hoje.
+use std::path::{Path, PathBuf};
+
+use credit_engine_core::golden;
+
+fn golden_path(name: &str) -> PathBuf { Path::new(env!("CARGO_MANIFEST_DIR")).join("goldens").join(name) }
+
+fn run_all(name: &str) {
+    let path = golden_path(name);
+    let report = if name.contains("clmm_pool") {
+        golden::clmm_pool::run_file(&path)
+    } else if name.contains("clmm") {
+        golden::clmm::run_file(&path)
+    } else {
+        golden::run_file(&path)
+    };
+    let report = report.expect("golden legível e íntegro");
+    assert!(report.passed > 0);
+    assert!(report.is_ok(), "{}: divergências: {:#?}", name, report.failures);
+}
+
+#[test]
+fn golden_csv_v1_all_rows() { run_all("amm_cpmw_v1.csv"); }
+
+#[test]
+fn golden_csv_v2_all_rows() { run_all("amm_cpmw_v2.csv"); }
+
+#[test]
+fn golden_csv_v2_matches_generator() {
+    let committed = std::fs::read_to_string(golden_path("amm_cpmw_v2.csv")).unwrap();
+    let meta = golden::parse_meta(&committed).unwrap();
+    assert_eq!(meta.version, golden::FORMAT_VERSION);
+    assert_eq!(meta.get("grid"), Some(golden::gen::GRID_ID));
+    // divergência aqui = política de arredondamento mudou: rode `cargo run --bin golden_gen` e revise o diff
+    assert!(committed == golden::gen::render_v2().unwrap(), "amm_cpmw_v2.csv desatualizado em relação ao gerador");
+}
+
+#[test]
+fn golden_csv_clmm_v1_all_rows() { run_all("amm_clmm_v1.csv"); }
+
+#[test]
+fn golden_csv_clmm_v1_matches_generator() {
+    let committed = std::fs::read_to_string(golden_path("amm_clmm_v1.csv")).unwrap();
+    let meta = golden::parse_meta_as(&committed, golden::clmm::FORMAT_NAME, golden::clmm::FORMAT_VERSION).unwrap();
+    assert_eq!(meta.get("grid"), Some(golden::clmm::GRID_ID));
+    // divergência aqui = matemática de ticks mudou: rode `cargo run --bin golden_gen -- clmm` e revise o diff
+    assert!(committed == golden::clmm::render_v1().unwrap(), "amm_clmm_v1.csv desatualizado em relação ao gerador");
+}
+
+#[test]
+fn golden_csv_clmm_pool_v1_all_rows() { run_all("amm_clmm_pool_v1.csv"); }
+
+#[test]
+fn golden_csv_clmm_pool_v1_matches_generator() {
+    let committed = std::fs::read_to_string(golden_path("amm_clmm_pool_v1.csv")).unwrap();
+    let meta = golden::parse_meta_as(&committed, golden::clmm_pool::FORMAT_NAME, golden::clmm_pool::FORMAT_VERSION).unwrap();
+    assert_eq!(meta.get("grid"), Some(golden::clmm_pool::GRID_ID));
+    // divergência aqui = contabilidade do pool concentrado mudou: rode `cargo run --bin golden_gen -- clmm-pool` e revise o diff
+    assert!(committed == golden::clmm_pool::render_v1().unwrap(), "amm_clmm_pool_v1.csv desatualizado em relação ao gerador");
+}
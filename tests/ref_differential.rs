@@ -0,0 +1,104 @@
+//! Teste diferencial: core U256 vs modelo de referência exato (`ref_bigdecimal`).
+//! Cada operação deve bater com o valor exato dentro da política ADR-0001 (≤ 1 wei).
+use proptest::prelude::*;
+use credit_engine_core::amm::liquidity::{add_liquidity, initial_mint, remove_liquidity};
+use credit_engine_core::amm::pricing::{execution_price_x_to_y, slippage_ppm_x_to_y, spot_price_x_in_y, spot_price_y_in_x};
+use credit_engine_core::amm::ref_bigdecimal as rf;
+use credit_engine_core::amm::swap::{get_amount_in, get_amount_out};
+use credit_engine_core::amm::types::{Wad, WAD, MIN_RESERVE};
+use credit_engine_core::amm::weighted;
+
+fn reserve() -> impl Strategy<Value = Wad> { (1u128..=1_000_000_000u128, 0u128..WAD).prop_map(|(a, b)| a * WAD + b) }
+
+// pesos múltiplos de 10%: razão reduzida com denominador ≤ 9 (modelo exato viável)
+fn weight() -> impl Strategy<Value = Wad> { (1u128..=9u128).prop_map(|d| d * WAD / 10) }
+
+proptest! {
+#![proptest_config(ProptestConfig { cases: 2_000, .. ProptestConfig::default() })]
+
+#[test]
+fn diff_amount_out(x in reserve(), y in reserve(), dx in 1u128..=1_000_000u128 * WAD, fee in 0u32..=10_000u32) {
+    let core = get_amount_out(x, y, dx, fee);
+    let reference = rf::ref_amount_out(x, y, dx, fee);
+    match (core, reference) {
+        (Ok(c), Ok(r)) => {
+            // y* nearest-even ⇒ |core - floor(exato)| ≤ 1 wei
+            prop_assert!(c.abs_diff(r) <= 1, "core={} ref={}", c, r);
+            let exact = rf::amount_out_exact(x, y, dx, fee).unwrap();
+            prop_assert!(rf::abs_diff(&rf::q(c), &exact) < rf::q(1));
+        }
+        (Err(_), Err(_)) => {}
+        // fronteira de MIN_RESERVE: divergência só na borda de 1 wei
+        (Ok(c), Err(_)) | (Err(_), Ok(c)) => prop_assert!(y - c <= MIN_RESERVE + 1),
+    }
+}
+
+#[test]
+fn diff_amount_in(x in reserve(), y in reserve(), pct in 1u128..=500u128, fee in 0u32..=10_000u32) {
+    let dy = y / 1_000 * pct / 1_000;
+    prop_assume!(dy > 0);
+    let core = get_amount_in(x, y, dy, fee).unwrap();
+    let reference = rf::ref_amount_in(x, y, dy, fee).unwrap();
+    // core aceita out arredondado ≥ dy ⇒ nunca cobra mais que a referência...
+    prop_assert!(core <= reference, "core={} ref={}", core, reference);
+    // ...e no exato entrega no máximo 1 wei a menos que dy
+    let exact = rf::amount_out_exact(x, y, core, fee).unwrap();
+    prop_assert!(exact + rf::q(1) >= rf::q(dy));
+}
+
+#[test]
+fn diff_liquidity(x in reserve(), y in reserve(), d in 1u128..=1_000_000u128 * WAD, burn_pct in 1u128..=900u128) {
+    let s = initial_mint(x, y).unwrap();
+    prop_assert_eq!(rf::int(s), rf::initial_mint_exact(x, y));
+
+    let mint = add_liquidity(x, y, d, d, s);
+    let exact = rf::add_liquidity_exact(x, y, d, d, s);
+    match mint {
+        Ok(m) => prop_assert_eq!(Some(m), rf::floor_u128(&exact)),
+        Err(_) => prop_assert_eq!(rf::floor_u128(&exact), Some(0)),
+    }
+
+    let burn = s / 1_000 * burn_pct;
+    prop_assume!(burn > 0);
+    if let Ok((xo, yo)) = remove_liquidity(x, y, burn, s) {
+        let (ex, ey) = rf::remove_liquidity_exact(x, y, burn, s);
+        prop_assert_eq!(Some(xo), rf::floor_u128(&ex));
+        prop_assert_eq!(Some(yo), rf::floor_u128(&ey));
+    }
+}
+
+#[test]
+fn diff_pricing(x in reserve(), y in reserve(), dx in WAD..=1_000_000u128 * WAD) {
+    prop_assert_eq!(Some(spot_price_x_in_y(x, y).unwrap()), rf::round_half_even_u128(&rf::spot_price_x_in_y_exact(x, y)));
+    prop_assert_eq!(Some(spot_price_y_in_x(x, y).unwrap()), rf::round_half_even_u128(&rf::spot_price_y_in_x_exact(x, y)));
+
+    if let Ok(out) = get_amount_out(x, y, dx, 3000) {
+        let p = execution_price_x_to_y(x, y, dx, 3000).unwrap();
+        prop_assert_eq!(Some(p), rf::round_half_even_u128(&rf::execution_price_exact(out, dx)));
+        // slippage: nearest sobre spot/exec já arredondados ⇒ ≤ 1 ppm do exato
+        let s = slippage_ppm_x_to_y(x, y, dx, 3000).unwrap();
+        let exact = rf::slippage_ppm_exact(&rf::spot_price_x_in_y_exact(x, y), &rf::execution_price_exact(out, dx));
+        prop_assert!(rf::abs_diff(&rf::q(s as u128), &exact) <= rf::q(1), "s={} exato={}", s, exact);
+    }
+}
+
+#[test]
+fn diff_weighted(b_in in reserve(), b_out in reserve(), w_in in weight(), w_out in weight(), dx in 1u128..=1_000_000u128 * WAD, pct in 1u128..=500u128, fee in 0u32..=10_000u32) {
+    // out: core arredonda a favor do pool ⇒ nunca acima do floor exato, e no máximo 2 wei abaixo
+    match (weighted::get_amount_out(b_in, w_in, b_out, w_out, dx, fee), rf::ref_weighted_amount_out(b_in, w_in, b_out, w_out, dx, fee)) {
+        (Ok(c), Ok(r)) => prop_assert!(c <= r && r - c <= 2, "core={} ref={}", c, r),
+        (Err(_), Err(_)) => {}
+        (Ok(c), Err(_)) | (Err(_), Ok(c)) => prop_assert!(c <= 2 || b_out - c <= MIN_RESERVE + 2),
+    }
+
+    // in: nunca abaixo do mínimo exato, e o excesso é só o arredondamento do lado exact-in
+    let dy = b_out / 1_000 * pct / 1_000;
+    prop_assume!(dy > 0);
+    let core = weighted::get_amount_in(b_in, w_in, b_out, w_out, dy, fee).unwrap();
+    let reference = rf::ref_weighted_amount_in(b_in, w_in, b_out, w_out, dy, fee).unwrap();
+    prop_assert!(core >= reference, "core={} ref={}", core, reference);
+    prop_assert!(weighted::get_amount_out(b_in, w_in, b_out, w_out, core, fee).unwrap() >= dy);
+    let slack = rf::ref_weighted_amount_in(b_in, w_in, b_out, w_out, dy + 2, fee).unwrap();
+    prop_assert!(core <= slack, "core={} ref(dy+2)={}", core, slack);
+}
+}